@@ -1,10 +1,20 @@
 #![feature(portable_simd)]
 mod math;
 mod rasterizer;
+pub mod recorder;
+pub mod tracer;
 use math::point_vec::{Point, V3D};
 pub use math::*;
-pub use rasterizer::TriangleRaster;
+pub use rasterizer::{
+    viewport, DepthBuffer, DirectionalLight, FillRule, FontError, Path, ShadedVertex, ShadowMap,
+    TextAlign, TextStyle, TriangleRaster, TrueTypeFont,
+};
 use serde::{Deserialize, Serialize};
+use tracer::{
+    description::SceneInfo,
+    render::{ProgressiveRender, RenderState},
+    scene::Scene,
+};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(typescript_custom_section)]
@@ -21,6 +31,118 @@ interface TimeResult {
     simd: string;
     no_simd: string;
 }
+
+type Vec3 = [number, number, number];
+
+interface SceneInfo {
+    width: number;
+    height: number;
+    samples?: number;
+    camera: CameraInfo;
+    lights?: LightInfo[];
+    objects?: ObjectInfo[];
+    mode?: { type: "whitted" } | { type: "path"; max_bounces?: number; seed?: number };
+    /** defaults to "clamp" for whitted renders and "aces" for path tracing */
+    tone_mapping?: "clamp" | "reinhard" | "aces";
+    exposure?: number;
+    /** seen by rays that miss everything, black by default */
+    environment?: EnvironmentInfo;
+}
+
+/** hdr pixels are linear rgb floats, three per pixel, rows from the top */
+type EnvironmentInfo =
+    | { type: "color"; color: Vec3 }
+    | { type: "sky"; zenith?: Vec3; horizon?: Vec3; ground?: Vec3 }
+    | { type: "equirectangular"; width: number; height: number; pixels: number[] }
+    | { type: "cube_map"; size: number; faces: [number[], number[], number[], number[], number[], number[]] };
+
+interface CameraInfo {
+    from: Vec3;
+    to: Vec3;
+    up?: Vec3;
+    /** field of view in radians */
+    fov: number;
+    /** lens diameter for depth of field, 0 (the default) is a pinhole */
+    aperture?: number;
+    /** distance in focus, defaults to the distance from `from` to `to` */
+    focal_distance?: number;
+    lens_seed?: number;
+}
+
+interface LightInfo {
+    position: Vec3;
+    intensity?: Vec3;
+}
+
+type ShapeInfo =
+    | { shape: "sphere" }
+    | { shape: "plane" }
+    | { shape: "cube" }
+    | { shape: "cylinder"; minimum?: number; maximum?: number; closed?: boolean }
+    | { shape: "triangle"; points: [Vec3, Vec3, Vec3]; normals?: [Vec3, Vec3, Vec3]; uvs?: [[number, number], [number, number], [number, number]] }
+    | { shape: "group"; children: ObjectInfo[] }
+    | {
+          shape: "csg";
+          operation: "union" | "intersection" | "difference";
+          left: ObjectInfo;
+          right: ObjectInfo;
+      };
+
+/** applied in order, the first transform of the list is applied first */
+type TransformInfo =
+    | { translate: Vec3 }
+    | { scale: Vec3 }
+    | { rotate_x: number }
+    | { rotate_y: number }
+    | { rotate_z: number };
+
+interface MaterialInfo {
+    color?: Vec3;
+    ambient?: number;
+    diffuse?: number;
+    specular?: number;
+    shininess?: number;
+    reflective?: number;
+    transparency?: number;
+    refractive_index?: number;
+    emissive?: Vec3;
+    /** takes precedence over `bump` */
+    normal_map?: NormalMapInfo;
+    bump?: BumpInfo;
+}
+
+/** tangent space normals, pixels use the same layout as the canvas buffers */
+interface NormalMapInfo {
+    width: number;
+    height: number;
+    pixels: number[];
+    strength?: number;
+}
+
+interface BumpInfo {
+    pattern: "waves" | "ripples";
+    frequency?: number;
+    scale?: number;
+}
+
+type ObjectInfo = ShapeInfo & {
+    transform?: TransformInfo[];
+    material?: MaterialInfo;
+};
+
+interface RenderResult {
+    elapsed: string;
+    /** samples per pixel actually reached, lower than max_samples if cancelled */
+    samples: number;
+}
+
+interface RenderProgress {
+    pass: number;
+    samples_per_pixel: number;
+    max_samples: number;
+    tiles_done: number;
+    tiles_total: number;
+}
 "#;
 
 #[wasm_bindgen]
@@ -29,6 +151,14 @@ extern "C" {
     pub type TriangleInfoJs;
     #[wasm_bindgen(typescript_type = "TimeResult")]
     pub type TimeResultJs;
+    #[wasm_bindgen(typescript_type = "SceneInfo")]
+    pub type SceneInfoJs;
+    #[wasm_bindgen(typescript_type = "RenderResult")]
+    pub type RenderResultJs;
+    #[wasm_bindgen(typescript_type = "RenderProgress")]
+    pub type RenderProgressJs;
+    #[wasm_bindgen(typescript_type = "(progress: RenderProgress) => void")]
+    pub type ProgressCallbackJs;
 }
 
 #[derive(Serialize, Deserialize)]
@@ -46,6 +176,12 @@ pub struct TimeResult {
     pub no_simd: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct RenderResult {
+    pub elapsed: String,
+    pub samples: u32,
+}
+
 #[wasm_bindgen]
 pub fn raster_triangle(
     info: TriangleInfoJs,
@@ -72,6 +208,80 @@ pub fn raster_triangle(
     TimeResultJs { obj: jsvalue }
 }
 
+/// Renders the whole scene before returning, which blocks the main thread;
+/// use [`SceneRender`] to spread the work over several frames instead.
+/// Fails with a message when the scene can't be parsed or built.
+#[wasm_bindgen]
+pub fn render_scene(scene: SceneInfoJs, canvas_vec: &mut [u32]) -> Result<RenderResultJs, JsValue> {
+    let mut render = build_render(scene)?;
+    let start = web_sys::js_sys::Date::now();
+    render.render();
+    let elapsed = web_sys::js_sys::Date::now() - start;
+    render.write_to(canvas_vec);
+    let result = RenderResult {
+        elapsed: format!("{:.2}ms", elapsed),
+        samples: render.progress().samples_per_pixel,
+    };
+    let jsvalue = serde_wasm_bindgen::to_value(&result).unwrap();
+    Ok(RenderResultJs { obj: jsvalue })
+}
+
+/// A scene render driven from JS: call `step` once per animation frame and
+/// `write_to` whenever the canvas should show the current estimate.
+#[wasm_bindgen]
+pub struct SceneRender {
+    render: ProgressiveRender<Scene>,
+}
+
+#[wasm_bindgen]
+impl SceneRender {
+    /// Fails with a message when the scene can't be parsed or built.
+    #[wasm_bindgen(constructor)]
+    pub fn new(scene: SceneInfoJs) -> Result<SceneRender, JsValue> {
+        Ok(SceneRender {
+            render: build_render(scene)?,
+        })
+    }
+
+    /// Renders at most `max_tiles` tiles, keep calling it while it returns `InProgress`.
+    pub fn step(&mut self, max_tiles: usize) -> RenderState {
+        self.render.step(max_tiles)
+    }
+
+    /// Writes the current estimate, pixels without samples yet are left untouched.
+    pub fn write_to(&self, canvas_vec: &mut [u32]) {
+        self.render.write_to(canvas_vec);
+    }
+
+    pub fn cancel(&self) {
+        self.render.cancel_handle().cancel();
+    }
+
+    pub fn progress(&self) -> RenderProgressJs {
+        let jsvalue = serde_wasm_bindgen::to_value(&self.render.progress()).unwrap();
+        RenderProgressJs { obj: jsvalue }
+    }
+
+    /// `callback` is called with the progress after every `step` that did some work.
+    pub fn on_progress(&mut self, callback: ProgressCallbackJs) {
+        let callback = web_sys::js_sys::Function::from(callback.obj);
+        self.render.on_progress(move |progress| {
+            let jsvalue = serde_wasm_bindgen::to_value(&progress).unwrap();
+            //an exception thrown by the callback must not abort the render
+            let _ = callback.call1(&JsValue::NULL, &jsvalue);
+        });
+    }
+}
+
+fn build_render(scene: SceneInfoJs) -> Result<ProgressiveRender<Scene>, JsValue> {
+    let info = serde_wasm_bindgen::from_value::<SceneInfo>(scene.obj)?;
+    let settings = info.render_settings();
+    let scene = info
+        .to_scene()
+        .map_err(|error| JsValue::from_str(&error.to_string()))?;
+    Ok(ProgressiveRender::new(scene, settings))
+}
+
 fn measure_time<T>(mut f: impl FnMut() -> T) -> String {
     let n = 20;
     let times: Vec<f64> = (0..n)
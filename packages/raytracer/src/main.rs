@@ -1,9 +1,16 @@
 use raytracer::{
+    matrices::Mat4,
     point_vec::{Point, V3D},
-    TriangleRaster,
+    tracer::{
+        bvh::{Bvh, Primitive},
+        camera::Camera,
+        canvas::Canvas,
+        mesh::Mesh,
+        ray::Ray,
+    },
+    TextAlign, TextStyle, TriangleRaster,
 };
-use tracer::canvas::Canvas;
-mod tracer;
+use std::time::Duration;
 
 fn main() {
     let mut canvas = Canvas::new(1504, 1504);
@@ -13,20 +20,69 @@ fn main() {
         Point::new(0.0, canvas.height as f64, 0.0).into(),
         Point::new(canvas.width as f64, canvas.height as f64, 0.0).into(),
     ];
-    measure_time(|| {
+    let scalar = measure_time(|| {
         raster.rasterize(&triangle, &mut canvas.pixels, canvas.width, 0xffaaaaaau32);
     });
-    measure_time(|| {
+    let simd = measure_time(|| {
         raster.rasterize_simd(&triangle, &mut canvas.pixels, canvas.width, 0xffaaaaaau32);
     });
+    let (single, packet4, packet8) = trace_rays();
+    let label = format!(
+        "scalar: {:?}\nsimd:   {:?}\nrays:     {:?}\npacket4: {:?}\npacket8: {:?}",
+        scalar, simd, single, packet4, packet8
+    );
+    let style = TextStyle {
+        align: TextAlign::Right,
+        size: 24.0,
+        ..TextStyle::default()
+    };
+    let top_right = ((canvas.width - 10) as f64, 10.0);
+    raster.draw_text(&label, top_right, &style, &mut canvas.pixels, canvas.width);
     canvas.loop_until_exit();
 }
 
-fn measure_time<T>(mut f: impl FnMut() -> T) {
+/// Times a 128x128 camera view of an icosphere ringed by spheres, one ray at
+/// a time and in packets of 4 and 8.
+fn trace_rays() -> (Duration, Duration, Duration) {
+    let mesh = Mesh::icosphere(3);
+    let mut primitives: Vec<Primitive> = mesh
+        .triangles()
+        .iter()
+        .map(|t| Primitive::triangle(&t.p1, &t.p2, &t.p3))
+        .collect();
+    for i in 0..16 {
+        let angle = i as f64 * std::f64::consts::PI / 8.0;
+        let center = Point::new(angle.cos() * 2.0, angle.sin() * 2.0, 0.0);
+        primitives.push(Primitive::sphere(&center, 0.25));
+    }
+    let bvh = Bvh::new(&primitives);
+    let camera =
+        Camera::new(128, 128, std::f64::consts::FRAC_PI_3).with_transform(Mat4::view_transform(
+            &Point::new(0.0, 0.0, -5.0),
+            &Point::new(0.0, 0.0, 0.0),
+            &V3D::new(0.0, 1.0, 0.0),
+        ));
+    let rays: Vec<Ray> = (0..camera.vsize)
+        .flat_map(|y| (0..camera.hsize).map(move |x| (x, y)))
+        .map(|(x, y)| camera.ray_for_pixel(x, y))
+        .collect();
+    let single = measure_time(|| {
+        rays.iter()
+            .map(|ray| bvh.intersect(ray))
+            .collect::<Vec<_>>()
+    });
+    let packet4 = measure_time(|| bvh.intersect_all::<4>(&rays));
+    let packet8 = measure_time(|| bvh.intersect_all::<8>(&rays));
+    (single, packet4, packet8)
+}
+
+fn measure_time<T>(mut f: impl FnMut() -> T) -> Duration {
     let start = std::time::Instant::now();
     let n = 100;
     for _ in 0..n {
         std::hint::black_box(f());
     }
-    println!("Elapsed: {:?}", start.elapsed() / n);
+    let elapsed = start.elapsed() / n;
+    println!("Elapsed: {:?}", elapsed);
+    elapsed
 }
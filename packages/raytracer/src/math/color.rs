@@ -0,0 +1,114 @@
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Color {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+const EPS: f64 = 1e-4;
+
+impl Color {
+    pub fn new(r: f64, g: f64, b: f64) -> Self {
+        Color { r, g, b }
+    }
+
+    pub fn black() -> Self {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn white() -> Self {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    pub fn from_u32(value: u32) -> Self {
+        let channel = |shift: u32| ((value >> shift) & 0xff) as f64 / 255.0;
+        Color::new(channel(0), channel(8), channel(16))
+    }
+
+    /// Packs the color in the same layout the canvas buffers use: once the
+    /// `u32` slice is viewed as bytes (little endian) it is RGBA, which is what
+    /// `ImageData` expects on the js side.
+    pub fn to_u32(&self) -> u32 {
+        let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u32;
+        0xff000000 | (channel(self.b) << 16) | (channel(self.g) << 8) | channel(self.r)
+    }
+
+    pub fn max_component(&self) -> f64 {
+        self.r.max(self.g).max(self.b)
+    }
+}
+
+impl PartialEq for Color {
+    fn eq(&self, other: &Self) -> bool {
+        (self.r - other.r).abs() < EPS
+            && (self.g - other.g).abs() < EPS
+            && (self.b - other.b).abs() < EPS
+    }
+}
+
+impl Add for Color {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Color::new(self.r + rhs.r, self.g + rhs.g, self.b + rhs.b)
+    }
+}
+
+impl AddAssign for Color {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Color {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Color::new(self.r - rhs.r, self.g - rhs.g, self.b - rhs.b)
+    }
+}
+
+impl Mul for Color {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Color::new(self.r * rhs.r, self.g * rhs.g, self.b * rhs.b)
+    }
+}
+
+impl Mul<f64> for Color {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Color::new(self.r * rhs, self.g * rhs, self.b * rhs)
+    }
+}
+
+impl Div<f64> for Color {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self::Output {
+        Color::new(self.r / rhs, self.g / rhs, self.b / rhs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Color;
+
+    #[test]
+    fn packs_as_rgba_bytes() {
+        let color = Color::new(1.0, 0.5, 0.0);
+        let packed = color.to_u32();
+        assert_eq!(packed.to_le_bytes(), [255, 128, 0, 255]);
+    }
+
+    #[test]
+    fn round_trips_through_u32() {
+        let color = Color::new(0.2, 0.4, 0.6);
+        assert_eq!(Color::from_u32(color.to_u32()), color);
+    }
+
+    #[test]
+    fn clamps_out_of_range() {
+        let color = Color::new(2.0, -1.0, 0.0);
+        assert_eq!(color.to_u32(), 0xff0000ff);
+    }
+}
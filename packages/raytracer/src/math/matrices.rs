@@ -1,4 +1,4 @@
-use super::point_vec::{Point, TupleLike};
+use super::point_vec::{Point, TupleLike, V3D};
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
 pub trait MatTraits:
@@ -12,15 +12,22 @@ pub trait MatTraits:
     + HasOne
     + NearZero
     + Neg<Output = Self>
+    + PartialOrd
     + Trigonometric
+    + SquareRoot
 {
 }
 
+/// Square `N`x`N` matrix, stored column major.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Mat4<T: MatTraits> {
-    data: [T; 16],
+pub struct Mat<T: MatTraits, const N: usize> {
+    data: [[T; N]; N],
 }
 
+pub type Mat2<T> = Mat<T, 2>;
+pub type Mat3<T> = Mat<T, 3>;
+pub type Mat4<T> = Mat<T, 4>;
+
 pub trait HasOne {
     fn one() -> Self;
 }
@@ -28,6 +35,11 @@ pub trait HasOne {
 pub trait Trigonometric {
     fn sin(&self) -> Self;
     fn cos(&self) -> Self;
+    fn acos(&self) -> Self;
+}
+
+pub trait SquareRoot {
+    fn sqrt(&self) -> Self;
 }
 
 pub trait NearZero {
@@ -40,168 +52,445 @@ impl HasOne for i32 {
     }
 }
 
-impl<T: MatTraits> Mat4<T> {
-    fn new() -> Mat4<T> {
-        Mat4 {
-            data: [T::default(); 16],
+impl<T: MatTraits, const N: usize> Mat<T, N> {
+    fn new() -> Mat<T, N> {
+        Mat {
+            data: [[T::default(); N]; N],
+        }
+    }
+
+    /// Builds a matrix from its rows, the way matrices are usually written down.
+    pub fn from_rows(rows: [[T; N]; N]) -> Mat<T, N> {
+        let mut mat = Mat::new();
+        for (row, values) in rows.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                mat.data[col][row] = *value;
+            }
+        }
+        mat
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.data[col][row]
+    }
+
+    pub fn identity() -> Mat<T, N> {
+        let mut mat = Mat::new();
+        for i in 0..N {
+            mat.data[i][i] = T::one();
+        }
+        mat
+    }
+
+    pub fn transpose(&self) -> Mat<T, N> {
+        let mut mat = Mat::new();
+        for col in 0..N {
+            for row in 0..N {
+                mat.data[col][row] = self.data[row][col];
+            }
+        }
+        mat
+    }
+
+    pub fn mul_ref(&self, rhs: &Mat<T, N>) -> Mat<T, N> {
+        let mut mat = Mat::new();
+        for col in 0..N {
+            for row in 0..N {
+                mat.data[col][row] = (0..N).fold(T::default(), |sum, k| {
+                    sum + self.data[k][row] * rhs.data[col][k]
+                });
+            }
+        }
+        mat
+    }
+
+    pub fn determinant(&self) -> T {
+        determinant(self.data.concat(), N)
+    }
+
+    /// Determinant of the matrix without `row` and `col`.
+    pub fn minor(&self, row: usize, col: usize) -> T {
+        let rest: Vec<T> = (0..N)
+            .filter(|c| *c != col)
+            .flat_map(|c| (0..N).filter(|r| *r != row).map(move |r| self.data[c][r]))
+            .collect();
+        determinant(rest, N - 1)
+    }
+
+    /// Minor with the sign of its position in a laplace expansion.
+    pub fn cofactor(&self, row: usize, col: usize) -> T {
+        let minor = self.minor(row, col);
+        if (row + col).is_multiple_of(2) {
+            minor
+        } else {
+            -minor
+        }
+    }
+
+    /// Inverse with gauss-jordan elimination, 4x4 matrices (which every
+    /// transform is) take an unrolled cofactor expansion instead.
+    pub fn inverse(&self) -> Option<Mat<T, N>> {
+        let columns = if N == 4 {
+            inverse4(self.data.as_flattened())?
+        } else {
+            gauss_jordan(self.data.as_flattened(), N)?
+        };
+        let mut mat = Mat::new();
+        for (i, value) in columns.into_iter().enumerate() {
+            mat.data[i / N][i % N] = value;
+        }
+        Some(mat)
+    }
+}
+
+fn abs<T: MatTraits>(value: T) -> T {
+    if value < T::default() {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Determinant of an `n`x`n` column major matrix, with gaussian elimination
+/// and partial pivoting.
+fn determinant<T: MatTraits>(mut m: Vec<T>, n: usize) -> T {
+    let mut det = T::one();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|a, b| {
+                abs(m[col * n + a])
+                    .partial_cmp(&abs(m[col * n + b]))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(col);
+        let value = m[col * n + pivot];
+        if value.near_zero() {
+            return T::default();
+        }
+        if pivot != col {
+            for c in col..n {
+                m.swap(c * n + col, c * n + pivot);
+            }
+            det = -det;
+        }
+        det = det * value;
+        for row in col + 1..n {
+            let factor = m[col * n + row] / value;
+            for c in col..n {
+                m[c * n + row] = m[c * n + row] - factor * m[c * n + col];
+            }
+        }
+    }
+    det
+}
+
+/// Inverse of an `n`x`n` column major matrix, eliminating on a copy while
+/// applying the same row operations to the identity.
+fn gauss_jordan<T: MatTraits>(data: &[T], n: usize) -> Option<Vec<T>> {
+    let mut m = data.to_vec();
+    let mut inv = vec![T::default(); n * n];
+    for i in 0..n {
+        inv[i * n + i] = T::one();
+    }
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|a, b| {
+                abs(m[col * n + a])
+                    .partial_cmp(&abs(m[col * n + b]))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(col);
+        if m[col * n + pivot].near_zero() {
+            return None;
+        }
+        for c in 0..n {
+            m.swap(c * n + col, c * n + pivot);
+            inv.swap(c * n + col, c * n + pivot);
+        }
+        let scale = T::one() / m[col * n + col];
+        for c in 0..n {
+            m[c * n + col] = m[c * n + col] * scale;
+            inv[c * n + col] = inv[c * n + col] * scale;
+        }
+        for row in (0..n).filter(|row| *row != col) {
+            let factor = m[col * n + row];
+            for c in 0..n {
+                m[c * n + row] = m[c * n + row] - factor * m[c * n + col];
+                inv[c * n + row] = inv[c * n + row] - factor * inv[c * n + col];
+            }
         }
     }
+    Some(inv)
+}
 
+/// Unrolled 4x4 inverse of a column major matrix.
+fn inverse4<T: MatTraits>(data: &[T]) -> Option<Vec<T>> {
+    let mut mat = vec![T::default(); 16];
+    let a00 = data[0];
+    let a01 = data[1];
+    let a02 = data[2];
+    let a03 = data[3];
+    let a10 = data[4];
+    let a11 = data[5];
+    let a12 = data[6];
+    let a13 = data[7];
+    let a20 = data[8];
+    let a21 = data[9];
+    let a22 = data[10];
+    let a23 = data[11];
+    let a30 = data[12];
+    let a31 = data[13];
+    let a32 = data[14];
+    let a33 = data[15];
+    let b00 = a00 * a11 - a01 * a10;
+    let b01 = a00 * a12 - a02 * a10;
+    let b02 = a00 * a13 - a03 * a10;
+    let b03 = a01 * a12 - a02 * a11;
+    let b04 = a01 * a13 - a03 * a11;
+    let b05 = a02 * a13 - a03 * a12;
+    let b06 = a20 * a31 - a21 * a30;
+    let b07 = a20 * a32 - a22 * a30;
+    let b08 = a20 * a33 - a23 * a30;
+    let b09 = a21 * a32 - a22 * a31;
+    let b10 = a21 * a33 - a23 * a31;
+    let b11 = a22 * a33 - a23 * a32;
+    let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+    if det.near_zero() {
+        return None;
+    }
+    let det = T::one() / det;
+    mat[0] = (a11 * b11 - a12 * b10 + a13 * b09) * det;
+    mat[1] = (a02 * b10 - a01 * b11 - a03 * b09) * det;
+    mat[2] = (a31 * b05 - a32 * b04 + a33 * b03) * det;
+    mat[3] = (a22 * b04 - a21 * b05 - a23 * b03) * det;
+    mat[4] = (a12 * b08 - a10 * b11 - a13 * b07) * det;
+    mat[5] = (a00 * b11 - a02 * b08 + a03 * b07) * det;
+    mat[6] = (a32 * b02 - a30 * b05 - a33 * b01) * det;
+    mat[7] = (a20 * b05 - a22 * b02 + a23 * b01) * det;
+    mat[8] = (a10 * b10 - a11 * b08 + a13 * b06) * det;
+    mat[9] = (a01 * b08 - a00 * b10 - a03 * b06) * det;
+    mat[10] = (a30 * b04 - a31 * b02 + a33 * b00) * det;
+    mat[11] = (a21 * b02 - a20 * b04 - a23 * b00) * det;
+    mat[12] = (a11 * b07 - a10 * b09 - a12 * b06) * det;
+    mat[13] = (a00 * b09 - a01 * b07 + a02 * b06) * det;
+    mat[14] = (a31 * b01 - a30 * b03 - a32 * b00) * det;
+    mat[15] = (a20 * b03 - a21 * b01 + a22 * b00) * det;
+    Some(mat)
+}
+
+impl<T: MatTraits> Mat4<T> {
     pub fn translation(x: T, y: T, z: T) -> Mat4<T> {
         let mut mat = Mat4::identity();
-        mat.data[12] = x;
-        mat.data[13] = y;
-        mat.data[14] = z;
+        mat.data[3][0] = x;
+        mat.data[3][1] = y;
+        mat.data[3][2] = z;
         mat
     }
 
     pub fn scaling(x: T, y: T, z: T) -> Mat4<T> {
         let mut mat = Mat4::identity();
-        mat.data[0] = x;
-        mat.data[5] = y;
-        mat.data[10] = z;
+        mat.data[0][0] = x;
+        mat.data[1][1] = y;
+        mat.data[2][2] = z;
         mat
     }
 
     pub fn rotation_x(r: T) -> Mat4<T> {
         let mut mat = Mat4::identity();
-        mat.data[5] = r.cos();
-        mat.data[6] = -r.sin();
-        mat.data[9] = r.sin();
-        mat.data[10] = r.cos();
+        mat.data[1][1] = r.cos();
+        mat.data[1][2] = -r.sin();
+        mat.data[2][1] = r.sin();
+        mat.data[2][2] = r.cos();
         mat
     }
 
-    pub fn rotation_z(r: T) -> Mat4<T> {
+    pub fn rotation_y(r: T) -> Mat4<T> {
         let mut mat = Mat4::identity();
-        mat.data[0] = r.cos();
-        mat.data[1] = -r.sin();
-        mat.data[4] = r.sin();
-        mat.data[5] = r.cos();
+        mat.data[0][0] = r.cos();
+        mat.data[0][2] = r.sin();
+        mat.data[2][0] = -r.sin();
+        mat.data[2][2] = r.cos();
         mat
     }
 
-    pub fn identity() -> Mat4<T> {
-        let mut mat = Mat4::new();
-        mat.data[0] = T::one();
-        mat.data[5] = T::one();
-        mat.data[10] = T::one();
-        mat.data[15] = T::one();
+    pub fn rotation_z(r: T) -> Mat4<T> {
+        let mut mat = Mat4::identity();
+        mat.data[0][0] = r.cos();
+        mat.data[0][1] = -r.sin();
+        mat.data[1][0] = r.sin();
+        mat.data[1][1] = r.cos();
         mat
     }
+}
 
-    pub fn transpose(&self) -> Mat4<T> {
-        let mut mat = Mat4::new();
-        for i in 0..4 {
-            for j in 0..4 {
-                mat.data[i * 4 + j] = self.data[j * 4 + i];
-            }
+impl Mat4<f64> {
+    /// Transform that moves the world so the eye sits at `from` looking at `to`.
+    pub fn view_transform(from: &Point, to: &Point, up: &V3D) -> Mat4<f64> {
+        let forward = (*to - *from).normalize();
+        let left = forward.cross(&up.normalize());
+        let true_up = left.cross(&forward);
+        let mut orientation = Mat4::identity();
+        for (col, value) in [left, true_up, -forward].iter().enumerate() {
+            orientation.data[col][0] = value.x;
+            orientation.data[col][1] = value.y;
+            orientation.data[col][2] = value.z;
         }
-        mat
+        orientation.transpose() * Mat4::translation(-from.x, -from.y, -from.z)
     }
 
-    pub fn inverse(&self) -> Option<Mat4<T>> {
-        let mut mat = Mat4::new();
-        let a00 = self.data[0];
-        let a01 = self.data[1];
-        let a02 = self.data[2];
-        let a03 = self.data[3];
-        let a10 = self.data[4];
-        let a11 = self.data[5];
-        let a12 = self.data[6];
-        let a13 = self.data[7];
-        let a20 = self.data[8];
-        let a21 = self.data[9];
-        let a22 = self.data[10];
-        let a23 = self.data[11];
-        let a30 = self.data[12];
-        let a31 = self.data[13];
-        let a32 = self.data[14];
-        let a33 = self.data[15];
-        let b00 = a00 * a11 - a01 * a10;
-        let b01 = a00 * a12 - a02 * a10;
-        let b02 = a00 * a13 - a03 * a10;
-        let b03 = a01 * a12 - a02 * a11;
-        let b04 = a01 * a13 - a03 * a11;
-        let b05 = a02 * a13 - a03 * a12;
-        let b06 = a20 * a31 - a21 * a30;
-        let b07 = a20 * a32 - a22 * a30;
-        let b08 = a20 * a33 - a23 * a30;
-        let b09 = a21 * a32 - a22 * a31;
-        let b10 = a21 * a33 - a23 * a31;
-        let b11 = a22 * a33 - a23 * a32;
-        let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
-        if det.near_zero() {
-            return None;
-        }
-        let det = T::one() / det;
-        mat.data[0] = (a11 * b11 - a12 * b10 + a13 * b09) * det;
-        mat.data[1] = (a02 * b10 - a01 * b11 - a03 * b09) * det;
-        mat.data[2] = (a31 * b05 - a32 * b04 + a33 * b03) * det;
-        mat.data[3] = (a22 * b04 - a21 * b05 - a23 * b03) * det;
-        mat.data[4] = (a12 * b08 - a10 * b11 - a13 * b07) * det;
-        mat.data[5] = (a00 * b11 - a02 * b08 + a03 * b07) * det;
-        mat.data[6] = (a32 * b02 - a30 * b05 - a33 * b01) * det;
-        mat.data[7] = (a20 * b05 - a22 * b02 + a23 * b01) * det;
-        mat.data[8] = (a10 * b10 - a11 * b08 + a13 * b06) * det;
-        mat.data[9] = (a01 * b08 - a00 * b10 - a03 * b06) * det;
-        mat.data[10] = (a30 * b04 - a31 * b02 + a33 * b00) * det;
-        mat.data[11] = (a21 * b02 - a20 * b04 - a23 * b00) * det;
-        mat.data[12] = (a11 * b07 - a10 * b09 - a12 * b06) * det;
-        mat.data[13] = (a00 * b09 - a01 * b07 + a02 * b06) * det;
-        mat.data[14] = (a31 * b01 - a30 * b03 - a32 * b00) * det;
-        mat.data[15] = (a20 * b03 - a21 * b01 + a22 * b00) * det;
-        Some(mat)
+    /// Linear part of the transform, without the translation.
+    pub fn upper_left(&self) -> Mat3<f64> {
+        self.submatrix(3, 3)
     }
 
-    pub fn mul_ref(&self, rhs: &Mat4<T>) -> Mat4<T> {
-        let mut mat = Mat4::new();
-        for i in 0..4 {
-            for j in 0..4 {
-                mat.data[i + j * 4] = self.data[i] * rhs.data[4 * j]
-                    + self.data[i + 4] * rhs.data[4 * j + 1]
-                    + self.data[i + 8] * rhs.data[4 * j + 2]
-                    + self.data[i + 12] * rhs.data[4 * j + 3];
-            }
-        }
-        mat
+    /// Transforms normals the way this matrix transforms points, `None`
+    /// when the transform flattens space.
+    pub fn normal_matrix(&self) -> Option<Mat3<f64>> {
+        Some(self.upper_left().inverse()?.transpose())
     }
-}
 
-impl Mat4<f64> {
     pub fn mul_vec(&self, rhs: &Point) -> Point {
         let mut out = [0.0; 4];
-        for i in 0..4 {
-            out[i] = self.data[i] * rhs.x
-                + self.data[i + 4] * rhs.y
-                + self.data[i + 8] * rhs.z
-                + self.data[i + 12] * rhs.w;
+        for (i, value) in out.iter_mut().enumerate() {
+            *value = self.data[0][i] * rhs.x
+                + self.data[1][i] * rhs.y
+                + self.data[2][i] * rhs.z
+                + self.data[3][i] * rhs.w;
         }
         Point::new(out[0], out[1], out[2])
     }
+
+    /// Maps the box between the planes to the [-1, 1] cube, the eye looks
+    /// down -z so `near` and `far` are distances in front of it.
+    pub fn orthographic(
+        (left, right): (f64, f64),
+        (bottom, top): (f64, f64),
+        (near, far): (f64, f64),
+    ) -> Mat4<f64> {
+        Mat4::from_rows([
+            [
+                2.0 / (right - left),
+                0.0,
+                0.0,
+                -(right + left) / (right - left),
+            ],
+            [
+                0.0,
+                2.0 / (top - bottom),
+                0.0,
+                -(top + bottom) / (top - bottom),
+            ],
+            [0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Perspective projection with a vertical field of view of `fov`
+    /// radians, use with [`Mat4::project`] to divide by w.
+    pub fn perspective(fov: f64, aspect: f64, near: f64, far: f64) -> Mat4<f64> {
+        let f = 1.0 / (fov / 2.0).tan();
+        Mat4::from_rows([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [
+                0.0,
+                0.0,
+                (far + near) / (near - far),
+                2.0 * far * near / (near - far),
+            ],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    /// Transforms the point and divides by w, giving normalized device
+    /// coordinates for projection matrices.
+    pub fn project(&self, rhs: &Point) -> V3D {
+        let row = |i: usize| {
+            self.data[0][i] * rhs.x
+                + self.data[1][i] * rhs.y
+                + self.data[2][i] * rhs.z
+                + self.data[3][i]
+        };
+        let w = row(3);
+        V3D::new(row(0) / w, row(1) / w, row(2) / w)
+    }
 }
 
 impl Mat4<f64> {
     pub fn mul_tuple<V: TupleLike>(&self, rhs: &V) -> V {
         let mut out = [0.0; 4];
-        for i in 0..4 {
-            out[i] = self.data[i] * rhs.get_x()
-                + self.data[i + 4] * rhs.get_y()
-                + self.data[i + 8] * rhs.get_z()
-                + self.data[i + 12] * rhs.get_w();
+        for (i, value) in out.iter_mut().enumerate() {
+            *value = self.data[0][i] * rhs.get_x()
+                + self.data[1][i] * rhs.get_y()
+                + self.data[2][i] * rhs.get_z()
+                + self.data[3][i] * rhs.get_w();
         }
         V::from_tuple((out[0], out[1], out[2], out[3]))
     }
 }
 
-impl<T: MatTraits> Mul for Mat4<T> {
-    type Output = Mat4<T>;
+impl Mat3<f64> {
+    /// 2D affine translation, points are `(x, y, 1)`.
+    pub fn translation_2d(x: f64, y: f64) -> Mat3<f64> {
+        Mat3::from_rows([[1.0, 0.0, x], [0.0, 1.0, y], [0.0, 0.0, 1.0]])
+    }
+
+    pub fn scaling_2d(x: f64, y: f64) -> Mat3<f64> {
+        Mat3::from_rows([[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Counter clockwise rotation when y points up.
+    pub fn rotation_2d(r: f64) -> Mat3<f64> {
+        let (sin, cos) = r.sin_cos();
+        Mat3::from_rows([[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    pub fn transform_point_2d(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            self.data[0][0] * x + self.data[1][0] * y + self.data[2][0],
+            self.data[0][1] * x + self.data[1][1] * y + self.data[2][1],
+        )
+    }
+
+    pub fn mul_v3d(&self, rhs: &V3D) -> V3D {
+        let row =
+            |i: usize| self.data[0][i] * rhs.x + self.data[1][i] * rhs.y + self.data[2][i] * rhs.z;
+        V3D::new(row(0), row(1), row(2))
+    }
+}
+
+impl<T: MatTraits, const N: usize> Mul for Mat<T, N> {
+    type Output = Mat<T, N>;
 
-    fn mul(self, rhs: Mat4<T>) -> Mat4<T> {
+    fn mul(self, rhs: Mat<T, N>) -> Mat<T, N> {
         self.mul_ref(&rhs)
     }
 }
 
+/// `submatrix` returns a matrix one size smaller, which const generics can't
+/// express yet, so it is written out for every size we use.
+macro_rules! submatrix {
+    ($n:literal) => {
+        impl<T: MatTraits> Mat<T, $n> {
+            /// The matrix without `row` and `col`.
+            pub fn submatrix(&self, row: usize, col: usize) -> Mat<T, { $n - 1 }> {
+                let mut mat = Mat::new();
+                for (to, from) in (0..$n).filter(|c| *c != col).enumerate() {
+                    let rows = (0..$n).filter(|r| *r != row);
+                    for (value, r) in mat.data[to].iter_mut().zip(rows) {
+                        *value = self.data[from][r];
+                    }
+                }
+                mat
+            }
+        }
+    };
+}
+
+submatrix!(2);
+submatrix!(3);
+submatrix!(4);
+
 impl HasOne for f32 {
     fn one() -> Self {
         1.0
@@ -234,6 +523,10 @@ impl Trigonometric for f32 {
     fn cos(&self) -> Self {
         f32::cos(*self)
     }
+
+    fn acos(&self) -> Self {
+        f32::acos(*self)
+    }
 }
 impl Trigonometric for f64 {
     fn sin(&self) -> Self {
@@ -243,6 +536,22 @@ impl Trigonometric for f64 {
     fn cos(&self) -> Self {
         f64::cos(*self)
     }
+
+    fn acos(&self) -> Self {
+        f64::acos(*self)
+    }
+}
+
+impl SquareRoot for f32 {
+    fn sqrt(&self) -> Self {
+        f32::sqrt(*self)
+    }
+}
+
+impl SquareRoot for f64 {
+    fn sqrt(&self) -> Self {
+        f64::sqrt(*self)
+    }
 }
 
 impl MatTraits for f32 {}
@@ -257,14 +566,15 @@ impl<T: TupleLike> Mul<T> for Mat4<f64> {
 
 #[cfg(test)]
 mod test {
-    use super::super::matrices::Mat4;
+    use super::super::matrices::{Mat2, Mat3, Mat4};
+    use super::super::point_vec::{Point, TupleLike, V3D};
 
     #[test]
     fn mul_test() {
         let m = Mat4::<f32>::identity();
         let mut m2 = m.clone();
-        m2.data[0] = 2.0;
-        m2.data[1] = 3.0;
+        m2.data[0][0] = 2.0;
+        m2.data[0][1] = 3.0;
         let m = m.clone() * m2.clone();
         assert_eq!(m, m2);
     }
@@ -286,10 +596,143 @@ mod test {
     #[test]
     fn small_ints_inverse() {
         let mut m = Mat4::<f32>::identity();
-        m.data[0] = 5.0;
-        m.data[1] = 2.0;
-        m.data[2] = 3.0;
+        m.data[0][0] = 5.0;
+        m.data[0][1] = 2.0;
+        m.data[0][2] = 3.0;
         let identity = m.inverse().map(|inv| inv * m);
         assert_eq!(Some(Mat4::<f32>::identity()), identity);
     }
+
+    #[test]
+    fn rotation_y_quarter_turn() {
+        let m = Mat4::rotation_y(std::f64::consts::FRAC_PI_2);
+        let p = m * Point::new(0.0, 0.0, 1.0);
+        assert_eq!(p, Point::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn default_view_transform_is_identity() {
+        let m = Mat4::view_transform(
+            &Point::new(0.0, 0.0, 0.0),
+            &Point::new(0.0, 0.0, -1.0),
+            &V3D::new(0.0, 1.0, 0.0),
+        );
+        assert_eq!(m, Mat4::identity());
+    }
+
+    #[test]
+    fn view_transform_moves_the_world() {
+        let m = Mat4::view_transform(
+            &Point::new(0.0, 0.0, 8.0),
+            &Point::new(0.0, 0.0, 0.0),
+            &V3D::new(0.0, 1.0, 0.0),
+        );
+        let p = m * Point::new(0.0, 0.0, 0.0);
+        assert_eq!(p, Point::new(0.0, 0.0, -8.0));
+    }
+
+    #[test]
+    fn determinant_2x2() {
+        let m = Mat2::from_rows([[1.0, 5.0], [-3.0, 2.0]]);
+        assert_eq!(m.determinant(), 17.0);
+    }
+
+    #[test]
+    fn submatrices() {
+        let m = Mat3::from_rows([[1.0, 5.0, 0.0], [-3.0, 2.0, 7.0], [0.0, 6.0, -3.0]]);
+        assert_eq!(
+            m.submatrix(0, 2),
+            Mat2::from_rows([[-3.0, 2.0], [0.0, 6.0]])
+        );
+        let m = Mat4::from_rows([
+            [-6.0, 1.0, 1.0, 6.0],
+            [-8.0, 5.0, 8.0, 6.0],
+            [-1.0, 0.0, 8.0, 2.0],
+            [-7.0, 1.0, -1.0, 1.0],
+        ]);
+        assert_eq!(
+            m.submatrix(2, 1),
+            Mat3::from_rows([[-6.0, 1.0, 6.0], [-8.0, 8.0, 6.0], [-7.0, -1.0, 1.0]])
+        );
+    }
+
+    #[test]
+    fn minors_and_cofactors() {
+        let m = Mat3::from_rows([[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]]);
+        assert_eq!(m.minor(1, 0), m.submatrix(1, 0).determinant());
+        assert_eq!(m.minor(0, 0), -12.0);
+        assert_eq!(m.cofactor(0, 0), -12.0);
+        assert_eq!(m.minor(1, 0), 25.0);
+        assert_eq!(m.cofactor(1, 0), -25.0);
+    }
+
+    #[test]
+    fn larger_determinants() {
+        let m = Mat3::from_rows([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+        assert_eq!(m.cofactor(0, 2), -46.0);
+        assert!((m.determinant() - -196.0f64).abs() < 1e-9);
+        let m = Mat4::from_rows([
+            [-2.0, -8.0, 3.0, 5.0],
+            [-3.0, 1.0, 7.0, 3.0],
+            [1.0, 2.0, -9.0, 6.0],
+            [-6.0, 7.0, 7.0, -9.0],
+        ]);
+        assert!((m.cofactor(0, 3) - 51.0f64).abs() < 1e-9);
+        assert!((m.determinant() - -4071.0f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn small_inverses() {
+        let m = Mat2::<f64>::from_rows([[4.0, 7.0], [2.0, 6.0]]);
+        let inverse = m.inverse().unwrap();
+        let expected = Mat2::from_rows([[0.6, -0.7], [-0.2, 0.4]]);
+        for (row, col) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            assert!((inverse.get(row, col) - expected.get(row, col)).abs() < 1e-9);
+        }
+        let m = Mat3::from_rows([[2.0, 0.0, 0.0], [0.0, 4.0, 0.0], [1.0, 0.0, 1.0]]);
+        assert_eq!(m.inverse().unwrap() * m, Mat3::identity());
+        assert_eq!(Mat3::<f64>::from_rows([[1.0; 3]; 3]).inverse(), None);
+    }
+
+    #[test]
+    fn normal_matrix_keeps_normals_perpendicular() {
+        let m = Mat4::scaling(1.0, 0.5, 1.0) * Mat4::rotation_z(0.3);
+        let (a, b) = (V3D::new(1.0, 1.0, 0.0), V3D::new(-1.0, 1.0, 0.0));
+        let normal = m.normal_matrix().unwrap().mul_v3d(&a);
+        let surface = m.clone() * b;
+        assert!(normal.dot(&surface).abs() < 1e-9);
+        let inverse_transpose = m.inverse().unwrap().transpose() * a;
+        assert_eq!(normal, inverse_transpose);
+    }
+
+    #[test]
+    fn affine_2d() {
+        let m = Mat3::translation_2d(1.0, 2.0)
+            * Mat3::rotation_2d(std::f64::consts::FRAC_PI_2)
+            * Mat3::scaling_2d(2.0, 2.0);
+        let (x, y) = m.transform_point_2d((1.0, 0.0));
+        assert!((x - 1.0).abs() < 1e-9 && (y - 4.0).abs() < 1e-9);
+        let (x, y) = m.inverse().unwrap().transform_point_2d((x, y));
+        assert!((x - 1.0).abs() < 1e-9 && y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn projections_map_the_frustum_to_the_unit_cube() {
+        let ortho = Mat4::orthographic((-2.0, 2.0), (-1.0, 1.0), (1.0, 11.0));
+        assert_eq!(
+            ortho.project(&Point::new(2.0, -1.0, -1.0)),
+            V3D::new(1.0, -1.0, -1.0)
+        );
+        assert_eq!(
+            ortho.project(&Point::new(0.0, 0.0, -11.0)),
+            V3D::new(0.0, 0.0, 1.0)
+        );
+        let perspective = Mat4::perspective(std::f64::consts::FRAC_PI_2, 2.0, 1.0, 10.0);
+        assert_eq!(
+            perspective.project(&Point::new(2.0, 1.0, -1.0)),
+            V3D::new(1.0, 1.0, -1.0)
+        );
+        let far = perspective.project(&Point::new(-20.0, 0.0, -10.0));
+        assert!((far.x + 1.0).abs() < 1e-9 && (far.z - 1.0).abs() < 1e-9);
+    }
 }
@@ -1,3 +1,5 @@
+pub mod color;
 pub mod matrices;
 pub mod point_vec;
+pub mod quaternion;
 pub mod transformable;
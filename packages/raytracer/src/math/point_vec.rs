@@ -1,4 +1,4 @@
-use std::ops::{Add, Mul, Sub};
+use std::ops::{Add, Mul, Neg, Sub};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Point {
@@ -195,6 +195,10 @@ impl V3D {
     pub fn new(x: f64, y: f64, z: f64) -> Self {
         V3D { x, y, z }
     }
+
+    pub fn reflect(&self, normal: &V3D) -> V3D {
+        *self - *normal * (2.0 * self.dot(normal))
+    }
 }
 
 impl From<Point> for V3D {
@@ -210,7 +214,7 @@ impl From<Point> for V3D {
 impl From<&[f64]> for Point {
     fn from(value: &[f64]) -> Self {
         Point {
-            x: *value.get(0).unwrap_or(&0.0),
+            x: *value.first().unwrap_or(&0.0),
             y: *value.get(1).unwrap_or(&0.0),
             z: *value.get(2).unwrap_or(&0.0),
             w: value.get(3).unwrap_or(&1.0).to_owned(),
@@ -232,6 +236,13 @@ impl Sub for V3D {
     }
 }
 
+impl Neg for V3D {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        V3D::new(-self.x, -self.y, -self.z)
+    }
+}
+
 impl Mul<f64> for V3D {
     type Output = Self;
     fn mul(self, rhs: f64) -> Self::Output {
@@ -312,6 +323,13 @@ mod test {
         assert_eq!(result, V3D::new(0.0, 0.0, 1.0));
     }
 
+    #[test]
+    fn reflect_off_slanted_surface() {
+        let v = V3D::new(0.0, -1.0, 0.0);
+        let n = V3D::new(2f64.sqrt() / 2.0, 2f64.sqrt() / 2.0, 0.0);
+        assert_eq!(v.reflect(&n), V3D::new(1.0, 0.0, 0.0));
+    }
+
     #[test]
     fn cross_product() {
         let a = V3D::new(1.0, 2.0, 3.0);
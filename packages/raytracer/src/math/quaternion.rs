@@ -0,0 +1,251 @@
+use super::{
+    matrices::{Mat4, MatTraits},
+    point_vec::V3D,
+};
+use std::ops::{Add, Mul, Neg};
+
+/// Rotation stored as a quaternion, `w` is the scalar part.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat<T: MatTraits> {
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+fn two<T: MatTraits>() -> T {
+    T::one() + T::one()
+}
+
+impl<T: MatTraits> Quat<T> {
+    pub fn new(w: T, x: T, y: T, z: T) -> Quat<T> {
+        Quat { w, x, y, z }
+    }
+
+    pub fn identity() -> Quat<T> {
+        Quat::new(T::one(), T::default(), T::default(), T::default())
+    }
+
+    /// Rotation of `angle` around `axis` (which doesn't need to be normalized).
+    /// The direction of the rotation is the same as [`Mat4::rotation_x`] and
+    /// friends, so `from_axis_angle([1, 0, 0], r).to_mat4()` is `rotation_x(r)`.
+    pub fn from_axis_angle(axis: [T; 3], angle: T) -> Quat<T> {
+        let [x, y, z] = axis;
+        let length = (x * x + y * y + z * z).sqrt();
+        let half = -angle / two();
+        let s = half.sin() / length;
+        Quat::new(half.cos(), x * s, y * s, z * s)
+    }
+
+    pub fn dot(&self, other: &Quat<T>) -> T {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn norm(&self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Quat<T> {
+        self.scale(T::one() / self.norm())
+    }
+
+    pub fn conjugate(&self) -> Quat<T> {
+        Quat::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn inverse(&self) -> Option<Quat<T>> {
+        let norm_squared = self.dot(self);
+        if norm_squared.near_zero() {
+            return None;
+        }
+        Some(self.conjugate().scale(T::one() / norm_squared))
+    }
+
+    pub fn scale(&self, s: T) -> Quat<T> {
+        Quat::new(self.w * s, self.x * s, self.y * s, self.z * s)
+    }
+
+    /// Hamilton product, the rotation `rhs` followed by `self`, like `Mat4` products.
+    pub fn mul_ref(&self, rhs: &Quat<T>) -> Quat<T> {
+        let (a, b) = (self, rhs);
+        Quat::new(
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        )
+    }
+
+    pub fn to_mat4(&self) -> Mat4<T> {
+        let q = self.normalize();
+        let (w, x, y, z) = (q.w, q.x, q.y, q.z);
+        let one = T::one();
+        let two = two::<T>();
+        let zero = T::default();
+        Mat4::from_rows([
+            [
+                one - two * (y * y + z * z),
+                two * (x * y - w * z),
+                two * (x * z + w * y),
+                zero,
+            ],
+            [
+                two * (x * y + w * z),
+                one - two * (x * x + z * z),
+                two * (y * z - w * x),
+                zero,
+            ],
+            [
+                two * (x * z - w * y),
+                two * (y * z + w * x),
+                one - two * (x * x + y * y),
+                zero,
+            ],
+            [zero, zero, zero, one],
+        ])
+    }
+
+    /// Spherical interpolation, `t = 0` gives `self` and `t = 1` gives `other`.
+    /// It always goes the short way around, so the rotation speed is constant
+    /// and never flips.
+    pub fn slerp(&self, other: &Quat<T>, t: T) -> Quat<T> {
+        let start = self.normalize();
+        let mut end = other.normalize();
+        let mut dot = start.dot(&end);
+        if dot < T::default() {
+            end = -end;
+            dot = -dot;
+        }
+        //almost the same rotation, sin(theta) would be ~0 so lerp is good enough
+        if (T::one() - dot).near_zero() {
+            return (start.scale(T::one() - t) + end.scale(t)).normalize();
+        }
+        let theta = dot.acos();
+        let sin_theta = theta.sin();
+        let a = ((T::one() - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        start.scale(a) + end.scale(b)
+    }
+}
+
+impl Quat<f64> {
+    pub fn rotate(&self, v: &V3D) -> V3D {
+        self.to_mat4().mul_tuple(v)
+    }
+}
+
+impl<T: MatTraits> Default for Quat<T> {
+    fn default() -> Self {
+        Quat::identity()
+    }
+}
+
+impl<T: MatTraits> Mul for Quat<T> {
+    type Output = Quat<T>;
+    fn mul(self, rhs: Quat<T>) -> Quat<T> {
+        self.mul_ref(&rhs)
+    }
+}
+
+impl<T: MatTraits> Add for Quat<T> {
+    type Output = Quat<T>;
+    fn add(self, rhs: Quat<T>) -> Quat<T> {
+        Quat::new(
+            self.w + rhs.w,
+            self.x + rhs.x,
+            self.y + rhs.y,
+            self.z + rhs.z,
+        )
+    }
+}
+
+impl<T: MatTraits> Neg for Quat<T> {
+    type Output = Quat<T>;
+    fn neg(self) -> Quat<T> {
+        Quat::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    fn assert_mat_near(a: &Mat4<f64>, b: &Mat4<f64>) {
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!(
+                    (a.get(row, col) - b.get(row, col)).abs() < 1e-9,
+                    "{:?} != {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    fn assert_quat_near(a: &Quat<f64>, b: &Quat<f64>) {
+        assert!((a.dot(b).abs() - 1.0).abs() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn matches_matrix_rotations() {
+        let r = 0.7;
+        let x = Quat::from_axis_angle([1.0, 0.0, 0.0], r);
+        let y = Quat::from_axis_angle([0.0, 2.0, 0.0], r);
+        let z = Quat::from_axis_angle([0.0, 0.0, 1.0], r);
+        assert_mat_near(&x.to_mat4(), &Mat4::rotation_x(r));
+        assert_mat_near(&y.to_mat4(), &Mat4::rotation_y(r));
+        assert_mat_near(&z.to_mat4(), &Mat4::rotation_z(r));
+    }
+
+    #[test]
+    fn composition_matches_matrix_product() {
+        let a = Quat::from_axis_angle([1.0, 0.0, 0.0], FRAC_PI_4);
+        let b = Quat::from_axis_angle([0.0, 1.0, 1.0], 1.2);
+        assert_mat_near(&(a * b).to_mat4(), &(a.to_mat4() * b.to_mat4()));
+    }
+
+    #[test]
+    fn inverse_undoes_rotation() {
+        let q = Quat::from_axis_angle([1.0, 2.0, 3.0], 0.4).scale(2.0);
+        let identity = q * q.inverse().unwrap();
+        assert_quat_near(&identity, &Quat::identity());
+        assert_eq!(Quat::new(0.0, 0.0, 0.0, 0.0).inverse(), None);
+    }
+
+    #[test]
+    fn rotates_vectors() {
+        let q = Quat::from_axis_angle([0.0, 1.0, 0.0], FRAC_PI_2);
+        let v = q.rotate(&V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(v, Mat4::rotation_y(FRAC_PI_2) * V3D::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn slerp_halfway() {
+        let start = Quat::identity();
+        let end = Quat::from_axis_angle([0.0, 1.0, 0.0], FRAC_PI_2);
+        assert_quat_near(&start.slerp(&end, 0.0), &start);
+        assert_quat_near(&start.slerp(&end, 1.0), &end);
+        let half = start.slerp(&end, 0.5);
+        assert_quat_near(&half, &Quat::from_axis_angle([0.0, 1.0, 0.0], FRAC_PI_4));
+        assert!((half.norm() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn slerp_takes_the_short_way() {
+        let start = Quat::from_axis_angle([0.0, 0.0, 1.0], 0.1);
+        let end = -Quat::from_axis_angle([0.0, 0.0, 1.0], 0.3);
+        let half = start.slerp(&end, 0.5);
+        assert_quat_near(&half, &Quat::from_axis_angle([0.0, 0.0, 1.0], 0.2));
+    }
+
+    #[test]
+    fn slerp_is_stable_for_close_rotations() {
+        let start = Quat::from_axis_angle([1.0, 0.0, 0.0], PI / 3.0);
+        let end = Quat::from_axis_angle([1.0, 0.0, 0.0], PI / 3.0 + 1e-9);
+        let mid = start.slerp(&end, 0.5);
+        assert!(mid.w.is_finite());
+        assert_quat_near(&mid, &start);
+    }
+}
@@ -0,0 +1,151 @@
+use super::TriangleRaster;
+use crate::math::point_vec::V3D;
+
+/// Depth of the closest surface drawn so far at each pixel, in normalized
+/// device coordinates (smaller is closer).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthBuffer {
+    pub width: usize,
+    pub height: usize,
+    depth: Vec<f64>,
+}
+
+impl DepthBuffer {
+    pub fn new(width: usize, height: usize) -> DepthBuffer {
+        DepthBuffer {
+            width,
+            height,
+            depth: vec![f64::INFINITY; width * height],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.depth.fill(f64::INFINITY);
+    }
+
+    /// Depth at a pixel, infinity when nothing was drawn there or the pixel
+    /// is outside the buffer.
+    pub fn get(&self, x: i64, y: i64) -> f64 {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return f64::INFINITY;
+        }
+        self.depth[y as usize * self.width + x as usize]
+    }
+
+    /// Stores `depth` when it is closer than what the pixel holds and
+    /// tells if it was.
+    pub fn test_and_set(&mut self, x: usize, y: usize, depth: f64) -> bool {
+        let stored = &mut self.depth[y * self.width + x];
+        if depth < *stored {
+            *stored = depth;
+            return true;
+        }
+        false
+    }
+}
+
+/// Pixel coordinates of a point in normalized device coordinates, with y
+/// pointing down. Depth is kept in `z`.
+pub fn viewport(ndc: &V3D, width: usize, height: usize) -> V3D {
+    V3D::new(
+        (ndc.x + 1.0) * 0.5 * width as f64,
+        (1.0 - ndc.y) * 0.5 * height as f64,
+        ndc.z,
+    )
+}
+
+fn edge(a: &V3D, b: &V3D, x: f64, y: f64) -> f64 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// Calls `fragment` with the pixel and barycentric weights of every pixel
+/// center inside the triangle, for both windings.
+pub(crate) fn for_each_fragment(
+    [a, b, c]: &[V3D; 3],
+    width: usize,
+    height: usize,
+    mut fragment: impl FnMut(usize, usize, [f64; 3]),
+) {
+    let area = edge(a, b, c.x, c.y);
+    if area == 0.0 || width == 0 {
+        return;
+    }
+    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+    let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(width);
+    let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(height);
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let w0 = edge(b, c, px, py) / area;
+            let w1 = edge(c, a, px, py) / area;
+            let w2 = 1.0 - w0 - w1;
+            if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+                fragment(x, y, [w0, w1, w2]);
+            }
+        }
+    }
+}
+
+impl TriangleRaster {
+    /// Writes the depth of the triangle (`z` of its screen space corners)
+    /// wherever it is closer than what the buffer holds.
+    pub fn rasterize_depth(&self, triangle: &[V3D; 3], depth: &mut DepthBuffer) {
+        let (width, height) = (depth.width, depth.height);
+        for_each_fragment(triangle, width, height, |x, y, [w0, w1, w2]| {
+            let z = triangle[0].z * w0 + triangle[1].z * w1 + triangle[2].z * w2;
+            depth.test_and_set(x, y, z);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn square(z: f64) -> [[V3D; 3]; 2] {
+        let corners =
+            [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)].map(|(x, y)| V3D::new(x, y, z));
+        [
+            [corners[0], corners[1], corners[2]],
+            [corners[0], corners[2], corners[3]],
+        ]
+    }
+
+    #[test]
+    fn closest_surface_wins_in_any_order() {
+        let raster = TriangleRaster::new();
+        for order in [[0.5, -0.5], [-0.5, 0.5]] {
+            let mut depth = DepthBuffer::new(4, 4);
+            for z in order {
+                for triangle in square(z) {
+                    raster.rasterize_depth(&triangle, &mut depth);
+                }
+            }
+            assert_eq!(depth.get(1, 1), -0.5);
+            assert_eq!(depth.get(3, 0), -0.5);
+        }
+    }
+
+    #[test]
+    fn depth_is_interpolated() {
+        let mut depth = DepthBuffer::new(4, 1);
+        let triangle = [
+            V3D::new(0.0, -4.0, 0.0),
+            V3D::new(4.0, 0.5, 1.0),
+            V3D::new(0.0, 4.0, 0.0),
+        ];
+        TriangleRaster::new().rasterize_depth(&triangle, &mut depth);
+        assert_eq!(depth.get(0, 0), 0.125);
+        assert_eq!(depth.get(3, 0), 0.875);
+        assert_eq!(depth.get(4, 0), f64::INFINITY);
+    }
+
+    #[test]
+    fn viewport_flips_y() {
+        let screen = viewport(&V3D::new(-1.0, 1.0, 0.25), 8, 4);
+        assert_eq!(screen, V3D::new(0.0, 0.0, 0.25));
+        let screen = viewport(&V3D::new(0.0, -1.0, 0.0), 8, 4);
+        assert_eq!(screen, V3D::new(4.0, 4.0, 0.0));
+    }
+}
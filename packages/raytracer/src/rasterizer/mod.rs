@@ -1,2 +1,14 @@
+mod depth;
+mod polygon;
+mod shading;
+mod shadow;
+mod text;
 mod triangle_rasterizer;
+mod truetype;
+pub use depth::*;
+pub use polygon::*;
+pub use shading::*;
+pub use shadow::*;
+pub use text::*;
 pub use triangle_rasterizer::*;
+pub use truetype::*;
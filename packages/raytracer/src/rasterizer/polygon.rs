@@ -0,0 +1,447 @@
+use super::TriangleRaster;
+use crate::math::{
+    color::Color,
+    point_vec::{TupleLike, V3D},
+};
+
+/// How overlapping parts of a path decide whether they are inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Inside when a ray to infinity crosses the outline an odd number of times.
+    EvenOdd,
+    /// Inside when the outline winds around the point at all.
+    #[default]
+    NonZero,
+}
+
+/// Largest distance (in pixels) between a curve and its flattened segments.
+pub const FLATTEN_TOLERANCE: f64 = 0.1;
+
+/// Vertical samples per pixel row for anti-aliased fills.
+const SUBSCANLINES: usize = 5;
+
+/// 2d outline made of one or more contours, curves are flattened into line
+/// segments as they are added. Every contour is implicitly closed. Only `x`
+/// and `y` of the points are used.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    contours: Vec<Vec<V3D>>,
+}
+
+impl Path {
+    pub fn new() -> Path {
+        Path::default()
+    }
+
+    /// Path with a single contour through `points`.
+    pub fn polygon(points: &[V3D]) -> Path {
+        Path {
+            contours: vec![points.to_vec()],
+        }
+    }
+
+    /// Starts a new contour.
+    pub fn move_to(mut self, x: f64, y: f64) -> Path {
+        self.contours.push(vec![V3D::new(x, y, 0.0)]);
+        self
+    }
+
+    pub fn line_to(mut self, x: f64, y: f64) -> Path {
+        self.current().push(V3D::new(x, y, 0.0));
+        self
+    }
+
+    pub fn quad_to(mut self, cx: f64, cy: f64, x: f64, y: f64) -> Path {
+        let start = self.last_point();
+        let points = flatten_quadratic(
+            &start,
+            &V3D::new(cx, cy, 0.0),
+            &V3D::new(x, y, 0.0),
+            FLATTEN_TOLERANCE,
+        );
+        self.current().extend(points);
+        self
+    }
+
+    pub fn cubic_to(mut self, c1x: f64, c1y: f64, c2x: f64, c2y: f64, x: f64, y: f64) -> Path {
+        let start = self.last_point();
+        let points = flatten_cubic(
+            &start,
+            &V3D::new(c1x, c1y, 0.0),
+            &V3D::new(c2x, c2y, 0.0),
+            &V3D::new(x, y, 0.0),
+            FLATTEN_TOLERANCE,
+        );
+        self.current().extend(points);
+        self
+    }
+
+    /// Ends the current contour, the next segment has to start with `move_to`.
+    pub fn close(mut self) -> Path {
+        self.contours.push(vec![]);
+        self
+    }
+
+    /// Flattened contours, empty ones left by `close` are skipped.
+    pub fn contours(&self) -> impl Iterator<Item = &[V3D]> {
+        self.contours
+            .iter()
+            .filter(|contour| contour.len() > 1)
+            .map(|contour| contour.as_slice())
+    }
+
+    /// Adds the contours of `other` to this path.
+    pub fn append(&mut self, other: Path) {
+        self.contours.extend(other.contours);
+    }
+
+    /// Applies `f` to every point, for scaling and placing glyphs and shapes.
+    pub fn map_points(mut self, f: impl Fn(&V3D) -> V3D) -> Path {
+        for point in self.contours.iter_mut().flatten() {
+            *point = f(point);
+        }
+        self
+    }
+
+    fn current(&mut self) -> &mut Vec<V3D> {
+        if self.contours.is_empty() {
+            self.contours.push(vec![]);
+        }
+        self.contours.last_mut().unwrap()
+    }
+
+    fn last_point(&self) -> V3D {
+        self.contours
+            .last()
+            .and_then(|contour| contour.last())
+            .copied()
+            .unwrap_or(V3D::new(0.0, 0.0, 0.0))
+    }
+
+    fn edges(&self) -> Vec<Edge> {
+        self.contours()
+            .flat_map(|contour| {
+                let next = contour.iter().cycle().skip(1);
+                contour
+                    .iter()
+                    .zip(next)
+                    .filter_map(|(a, b)| Edge::new(a, b))
+            })
+            .collect()
+    }
+}
+
+/// Points after `p0` along a quadratic Bézier, `p0` itself is left out.
+pub fn flatten_quadratic(p0: &V3D, p1: &V3D, p2: &V3D, tolerance: f64) -> Vec<V3D> {
+    //uniform steps are off by at most |p0 - 2p1 + p2| / (8n²)
+    let dd = (*p0 - *p1 * 2.0 + *p2).magnitude();
+    let n = ((dd / (8.0 * tolerance)).sqrt().ceil() as usize).max(1);
+    (1..=n)
+        .map(|i| {
+            let t = i as f64 / n as f64;
+            let mt = 1.0 - t;
+            *p0 * (mt * mt) + *p1 * (2.0 * mt * t) + *p2 * (t * t)
+        })
+        .collect()
+}
+
+/// Points after `p0` along a cubic Bézier, `p0` itself is left out.
+pub fn flatten_cubic(p0: &V3D, p1: &V3D, p2: &V3D, p3: &V3D, tolerance: f64) -> Vec<V3D> {
+    let dd = (*p0 - *p1 * 2.0 + *p2)
+        .magnitude()
+        .max((*p1 - *p2 * 2.0 + *p3).magnitude());
+    let n = ((0.75 * dd / tolerance).sqrt().ceil() as usize).max(1);
+    (1..=n)
+        .map(|i| {
+            let t = i as f64 / n as f64;
+            let mt = 1.0 - t;
+            *p0 * (mt * mt * mt)
+                + *p1 * (3.0 * mt * mt * t)
+                + *p2 * (3.0 * mt * t * t)
+                + *p3 * (t * t * t)
+        })
+        .collect()
+}
+
+/// Non horizontal segment, stored top to bottom with the direction it had.
+struct Edge {
+    top: V3D,
+    bottom: V3D,
+    winding: i32,
+}
+
+impl Edge {
+    fn new(a: &V3D, b: &V3D) -> Option<Edge> {
+        if a.y == b.y {
+            return None;
+        }
+        let (top, bottom, winding) = if a.y < b.y { (*a, *b, 1) } else { (*b, *a, -1) };
+        Some(Edge {
+            top,
+            bottom,
+            winding,
+        })
+    }
+
+    /// X where the edge crosses the scanline, if it does. Edges include
+    /// their top and exclude their bottom so shared vertices count once.
+    fn crossing(&self, y: f64) -> Option<f64> {
+        if y < self.top.y || y >= self.bottom.y {
+            return None;
+        }
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        Some(self.top.x + (self.bottom.x - self.top.x) * t)
+    }
+}
+
+/// Inside spans `[start, end)` of a scanline.
+fn spans(
+    edges: &[Edge],
+    y: f64,
+    rule: FillRule,
+    crossings: &mut Vec<(f64, i32)>,
+) -> Vec<(f64, f64)> {
+    crossings.clear();
+    crossings.extend(
+        edges
+            .iter()
+            .filter_map(|edge| edge.crossing(y).map(|x| (x, edge.winding))),
+    );
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut spans = vec![];
+    let mut winding = 0;
+    for pair in crossings.windows(2) {
+        let (x, direction) = pair[0];
+        winding += direction;
+        let inside = match rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        };
+        if inside && pair[1].0 > x {
+            spans.push((x, pair[1].0));
+        }
+    }
+    spans
+}
+
+impl TriangleRaster {
+    /// Fills every pixel whose center is inside the path.
+    pub fn fill_path(
+        &self,
+        path: &Path,
+        rule: FillRule,
+        canvas: &mut [u32],
+        width: usize,
+        paint_color: u32,
+    ) {
+        let height = canvas.len() / width.max(1);
+        let edges = path.edges();
+        let mut crossings = vec![];
+        for y in 0..height {
+            for (start, end) in spans(&edges, y as f64 + 0.5, rule, &mut crossings) {
+                let first = (start - 0.5).ceil().max(0.0) as usize;
+                let last = ((end - 0.5).ceil().max(0.0) as usize).min(width);
+                if first < last {
+                    canvas[y * width + first..y * width + last].fill(paint_color);
+                }
+            }
+        }
+    }
+
+    /// Fraction of every pixel covered by the path, `width * height` values
+    /// in `0..=1`. Rows are sampled with a few sub-scanlines and the spans
+    /// are measured exactly along x.
+    pub fn path_coverage(
+        &self,
+        path: &Path,
+        rule: FillRule,
+        width: usize,
+        height: usize,
+    ) -> Vec<f32> {
+        let mut coverage = vec![0.0; width * height];
+        let edges = path.edges();
+        let mut crossings = vec![];
+        let weight = 1.0 / SUBSCANLINES as f64;
+        for y in 0..height {
+            let row = &mut coverage[y * width..(y + 1) * width];
+            for sub in 0..SUBSCANLINES {
+                let sy = y as f64 + (sub as f64 + 0.5) * weight;
+                for (start, end) in spans(&edges, sy, rule, &mut crossings) {
+                    let start = start.clamp(0.0, width as f64);
+                    let end = end.clamp(0.0, width as f64);
+                    let mut x = start;
+                    while x < end {
+                        let pixel = x as usize;
+                        let next = (pixel as f64 + 1.0).min(end);
+                        row[pixel] += ((next - x) * weight) as f32;
+                        x = next;
+                    }
+                }
+            }
+        }
+        coverage.iter_mut().for_each(|c| *c = c.min(1.0));
+        coverage
+    }
+
+    /// Anti-aliased fill, blending `color` over the canvas by coverage.
+    pub fn fill_path_antialiased(
+        &self,
+        path: &Path,
+        rule: FillRule,
+        canvas: &mut [u32],
+        width: usize,
+        color: Color,
+    ) {
+        let height = canvas.len() / width.max(1);
+        let coverage = self.path_coverage(path, rule, width, height);
+        blend_coverage(canvas, &coverage, color);
+    }
+}
+
+/// Blends `color` over `canvas` using `coverage` as its alpha.
+pub(crate) fn blend_coverage(canvas: &mut [u32], coverage: &[f32], color: Color) {
+    for (pixel, alpha) in canvas.iter_mut().zip(coverage) {
+        if *alpha <= 0.0 {
+            continue;
+        }
+        let alpha = *alpha as f64;
+        let below = Color::from_u32(*pixel);
+        *pixel = (below * (1.0 - alpha) + color * alpha).to_u32();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PAINT: u32 = 0xffffffff;
+
+    fn fill(path: &Path, rule: FillRule, size: usize) -> Vec<u32> {
+        let mut canvas = vec![0; size * size];
+        TriangleRaster::new().fill_path(path, rule, &mut canvas, size, PAINT);
+        canvas
+    }
+
+    fn count(canvas: &[u32]) -> usize {
+        canvas.iter().filter(|p| **p == PAINT).count()
+    }
+
+    #[test]
+    fn fills_a_square() {
+        let square = Path::new()
+            .move_to(2.0, 2.0)
+            .line_to(6.0, 2.0)
+            .line_to(6.0, 6.0)
+            .line_to(2.0, 6.0);
+        let canvas = fill(&square, FillRule::NonZero, 8);
+        assert_eq!(count(&canvas), 16);
+        assert_eq!(canvas[2 * 8 + 2], PAINT);
+        assert_eq!(canvas[6 * 8 + 6], 0);
+    }
+
+    #[test]
+    fn fills_concave_shapes() {
+        //a U shape, the notch in the middle stays empty
+        let u = Path::polygon(&[
+            V3D::new(0.0, 0.0, 0.0),
+            V3D::new(2.0, 0.0, 0.0),
+            V3D::new(2.0, 4.0, 0.0),
+            V3D::new(4.0, 4.0, 0.0),
+            V3D::new(4.0, 0.0, 0.0),
+            V3D::new(6.0, 0.0, 0.0),
+            V3D::new(6.0, 6.0, 0.0),
+            V3D::new(0.0, 6.0, 0.0),
+        ]);
+        let canvas = fill(&u, FillRule::EvenOdd, 6);
+        assert_eq!(canvas[3], 0);
+        assert_eq!(canvas[5 * 6 + 3], PAINT);
+        assert_eq!(count(&canvas), 36 - 8);
+    }
+
+    #[test]
+    fn winding_rules_differ_on_overlaps() {
+        //two squares wound the same way, one inside the other
+        let nested = Path::new()
+            .move_to(0.0, 0.0)
+            .line_to(8.0, 0.0)
+            .line_to(8.0, 8.0)
+            .line_to(0.0, 8.0)
+            .close()
+            .move_to(2.0, 2.0)
+            .line_to(6.0, 2.0)
+            .line_to(6.0, 6.0)
+            .line_to(2.0, 6.0);
+        assert_eq!(count(&fill(&nested, FillRule::NonZero, 8)), 64);
+        assert_eq!(count(&fill(&nested, FillRule::EvenOdd, 8)), 64 - 16);
+    }
+
+    #[test]
+    fn self_intersecting_star() {
+        let star: Vec<V3D> = (0..5)
+            .map(|i| {
+                let angle = i as f64 * 4.0 * std::f64::consts::PI / 5.0;
+                V3D::new(10.0 + 9.0 * angle.sin(), 10.0 - 9.0 * angle.cos(), 0.0)
+            })
+            .collect();
+        let star = Path::polygon(&star);
+        let center = 10 * 20 + 10;
+        assert_eq!(fill(&star, FillRule::NonZero, 20)[center], PAINT);
+        assert_eq!(fill(&star, FillRule::EvenOdd, 20)[center], 0);
+    }
+
+    #[test]
+    fn flattened_curves_stay_close() {
+        let (p0, p1, p2) = (
+            V3D::new(0.0, 0.0, 0.0),
+            V3D::new(50.0, 100.0, 0.0),
+            V3D::new(100.0, 0.0, 0.0),
+        );
+        let points = flatten_quadratic(&p0, &p1, &p2, 0.1);
+        assert!(points.len() > 4);
+        assert_eq!(*points.last().unwrap(), p2);
+        //the peak of the curve is at t = 0.5
+        let peak = points.iter().map(|p| p.y).fold(0.0, f64::max);
+        assert!((peak - 50.0).abs() < 0.1);
+        let cubic = flatten_cubic(&p0, &p1, &p1, &p2, 0.1);
+        assert_eq!(*cubic.last().unwrap(), p2);
+        assert!(!flatten_cubic(&p0, &p0, &p2, &p2, 0.1).is_empty());
+    }
+
+    #[test]
+    fn curved_path_covers_a_circle() {
+        //a circle of radius 10 from four cubic arcs
+        let k = 10.0 * 0.552_284_75;
+        let circle = Path::new()
+            .move_to(20.0, 10.0)
+            .cubic_to(20.0, 10.0 + k, 10.0 + k, 20.0, 10.0, 20.0)
+            .cubic_to(10.0 - k, 20.0, 0.0, 10.0 + k, 0.0, 10.0)
+            .cubic_to(0.0, 10.0 - k, 10.0 - k, 0.0, 10.0, 0.0)
+            .cubic_to(10.0 + k, 0.0, 20.0, 10.0 - k, 20.0, 10.0);
+        let coverage = TriangleRaster::new().path_coverage(&circle, FillRule::NonZero, 20, 20);
+        let area: f32 = coverage.iter().sum();
+        //chords cut a sliver off every arc, about 1% of the area at this size
+        assert!((area - 314.159).abs() < 5.0, "{}", area);
+        assert_eq!(coverage[10 * 20 + 10], 1.0);
+        assert_eq!(coverage[0], 0.0);
+    }
+
+    #[test]
+    fn antialiased_edges_blend() {
+        let half = Path::polygon(&[
+            V3D::new(0.0, 0.0, 0.0),
+            V3D::new(1.5, 0.0, 0.0),
+            V3D::new(1.5, 2.0, 0.0),
+            V3D::new(0.0, 2.0, 0.0),
+        ]);
+        let mut canvas = vec![Color::black().to_u32(); 4];
+        TriangleRaster::new().fill_path_antialiased(
+            &half,
+            FillRule::NonZero,
+            &mut canvas,
+            2,
+            Color::white(),
+        );
+        assert_eq!(canvas[0], Color::white().to_u32());
+        assert_eq!(canvas[1], (Color::white() * 0.5).to_u32());
+    }
+}
@@ -0,0 +1,149 @@
+use super::{depth::for_each_fragment, TriangleRaster};
+use crate::{
+    math::{
+        color::Color,
+        point_vec::{Point, TupleLike, V3D},
+    },
+    tracer::{material::Material, normal_map::SurfaceFrame},
+};
+
+/// Corner of a smooth shaded triangle. `screen` is in pixels, the rest is in
+/// object space and gets interpolated across the face.
+#[derive(Debug, Clone, Copy)]
+pub struct ShadedVertex {
+    pub screen: V3D,
+    pub position: Point,
+    pub normal: V3D,
+    pub tangent: V3D,
+    pub uv: (f64, f64),
+}
+
+/// Light coming from far away, the only kind the rasterizer shades with.
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    /// Direction the light travels in.
+    pub direction: V3D,
+    pub intensity: Color,
+}
+
+impl TriangleRaster {
+    /// Fills the triangle with Lambert shading of the interpolated normal,
+    /// perturbed per pixel by the material normal map or bump function.
+    /// Pixels are sampled at their centers and both windings are filled.
+    pub fn rasterize_shaded(
+        &self,
+        vertices: &[ShadedVertex; 3],
+        canvas: &mut [u32],
+        width: usize,
+        material: &Material,
+        light: &DirectionalLight,
+    ) {
+        let height = canvas.len().checked_div(width).unwrap_or(0);
+        let screen = vertices.map(|v| v.screen);
+        for_each_fragment(&screen, width, height, |x, y, weights| {
+            let frame = interpolate(vertices, weights);
+            canvas[y * width + x] = shade(&frame, material, light, 1.0).to_u32();
+        });
+    }
+}
+
+/// Lambert shading where only `visibility` of the light reaches the
+/// surface, the ambient term is always there.
+pub(crate) fn shade(
+    frame: &SurfaceFrame,
+    material: &Material,
+    light: &DirectionalLight,
+    visibility: f64,
+) -> Color {
+    let normal = material.shading_normal(frame);
+    let lambert = normal.dot(&-light.direction.normalize()).max(0.0);
+    material.color * light.intensity * (material.ambient + material.diffuse * lambert * visibility)
+}
+
+pub(crate) fn interpolate(vertices: &[ShadedVertex; 3], weights: [f64; 3]) -> SurfaceFrame {
+    let mut position = V3D::new(0.0, 0.0, 0.0);
+    let mut normal = V3D::new(0.0, 0.0, 0.0);
+    let mut tangent = V3D::new(0.0, 0.0, 0.0);
+    let mut uv = (0.0, 0.0);
+    for (vertex, weight) in vertices.iter().zip(weights) {
+        position = position + V3D::from(vertex.position) * weight;
+        normal = normal + vertex.normal * weight;
+        tangent = tangent + vertex.tangent * weight;
+        uv.0 += vertex.uv.0 * weight;
+        uv.1 += vertex.uv.1 * weight;
+    }
+    SurfaceFrame {
+        point: Point::new(position.x, position.y, position.z),
+        normal: normal.normalize(),
+        tangent,
+        uv,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tracer::{normal_map::NormalPerturbation, texture::Texture};
+
+    const WIDTH: usize = 8;
+
+    fn quad_half(normals: [V3D; 3]) -> [ShadedVertex; 3] {
+        let screen = [
+            V3D::new(0.0, 0.0, 0.0),
+            V3D::new(8.0, 0.0, 0.0),
+            V3D::new(0.0, 8.0, 0.0),
+        ];
+        [0, 1, 2].map(|i| ShadedVertex {
+            screen: screen[i],
+            position: Point::new(screen[i].x, screen[i].y, 0.0),
+            normal: normals[i],
+            tangent: V3D::new(1.0, 0.0, 0.0),
+            uv: (screen[i].x / 8.0, screen[i].y / 8.0),
+        })
+    }
+
+    fn light() -> DirectionalLight {
+        DirectionalLight {
+            direction: V3D::new(0.0, 0.0, -1.0),
+            intensity: Color::white(),
+        }
+    }
+
+    fn render(vertices: &[ShadedVertex; 3], material: &Material) -> Vec<u32> {
+        let mut canvas = vec![0; WIDTH * WIDTH];
+        TriangleRaster::new().rasterize_shaded(vertices, &mut canvas, WIDTH, material, &light());
+        canvas
+    }
+
+    #[test]
+    fn fills_only_the_triangle() {
+        let facing = V3D::new(0.0, 0.0, 1.0);
+        let canvas = render(&quad_half([facing; 3]), &Material::default());
+        assert_eq!(canvas[0], Color::white().to_u32());
+        assert_eq!(canvas[WIDTH * WIDTH - 1], 0);
+    }
+
+    #[test]
+    fn normals_are_interpolated() {
+        let facing = V3D::new(0.0, 0.0, 1.0);
+        let away = V3D::new(1.0, 0.0, 0.0);
+        let canvas = render(&quad_half([facing, away, facing]), &Material::default());
+        let left = Color::from_u32(canvas[0]);
+        let right = Color::from_u32(canvas[6]);
+        assert!(left.r > right.r);
+    }
+
+    #[test]
+    fn honors_normal_maps() {
+        let facing = V3D::new(0.0, 0.0, 1.0);
+        let vertices = quad_half([facing; 3]);
+        let tilted = Texture::solid(Color::new(1.0, 0.5, 0.5));
+        let material = Material {
+            normal_perturbation: Some(NormalPerturbation::normal_map(tilted)),
+            ..Material::default()
+        };
+        let canvas = render(&vertices, &material);
+        //the map turns the normal sideways, leaving only the ambient term
+        assert_eq!(canvas[0], Color::new(0.1, 0.1, 0.1).to_u32());
+    }
+}
@@ -0,0 +1,189 @@
+use super::{
+    depth::{for_each_fragment, viewport, DepthBuffer},
+    shading::{interpolate, shade},
+    DirectionalLight, ShadedVertex, TriangleRaster,
+};
+use crate::{
+    math::{
+        matrices::Mat4,
+        point_vec::{Point, TupleLike, V3D},
+    },
+    tracer::material::Material,
+};
+
+/// Depth of the scene seen from a directional light, through an orthographic
+/// projection covering a sphere around the scene.
+#[derive(Debug, Clone)]
+pub struct ShadowMap {
+    light: DirectionalLight,
+    view_projection: Mat4<f64>,
+    depth: DepthBuffer,
+    /// Offset (in normalized depth) that keeps surfaces from shadowing
+    /// themselves.
+    pub bias: f64,
+    /// Percentage-closer filtering looks at `(2 * radius + 1)²` texels.
+    pub pcf_radius: usize,
+}
+
+impl ShadowMap {
+    /// A `size`x`size` map for `light` that covers everything within
+    /// `radius` of `center`.
+    pub fn new(light: DirectionalLight, center: Point, radius: f64, size: usize) -> ShadowMap {
+        let direction = light.direction.normalize();
+        let eye = center - direction * (2.0 * radius);
+        //any up works as long as it isn't parallel to the light
+        let up = if direction.x.abs() < 0.9 {
+            V3D::new(1.0, 0.0, 0.0)
+        } else {
+            V3D::new(0.0, 1.0, 0.0)
+        };
+        let view = Mat4::view_transform(&eye, &center, &up);
+        let projection =
+            Mat4::orthographic((-radius, radius), (-radius, radius), (radius, 3.0 * radius));
+        ShadowMap {
+            light,
+            view_projection: projection * view,
+            depth: DepthBuffer::new(size, size),
+            bias: 0.005,
+            pcf_radius: 1,
+        }
+    }
+
+    pub fn light(&self) -> &DirectionalLight {
+        &self.light
+    }
+
+    pub fn depth(&self) -> &DepthBuffer {
+        &self.depth
+    }
+
+    /// Position of a world point in the map, in texels, with its depth.
+    fn to_map(&self, point: &Point) -> V3D {
+        let ndc = self.view_projection.project(point);
+        viewport(&ndc, self.depth.width, self.depth.height)
+    }
+
+    /// Replaces the map contents with the depth of the given world space
+    /// triangles.
+    pub fn render(&mut self, raster: &TriangleRaster, triangles: &[[Point; 3]]) {
+        self.depth.clear();
+        for triangle in triangles {
+            let screen = triangle.map(|p| self.to_map(&p));
+            raster.rasterize_depth(&screen, &mut self.depth);
+        }
+    }
+
+    /// How much of the light reaches `point`, from 0 (fully shadowed) to 1,
+    /// as the fraction of nearby texels that see it.
+    pub fn visibility(&self, point: &Point) -> f64 {
+        let p = self.to_map(point);
+        let (x, y) = (p.x.floor() as i64, p.y.floor() as i64);
+        let r = self.pcf_radius as i64;
+        let mut lit = 0;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if p.z - self.bias <= self.depth.get(x + dx, y + dy) {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f64 / ((2 * r + 1) * (2 * r + 1)) as f64
+    }
+}
+
+impl TriangleRaster {
+    /// Like [`TriangleRaster::rasterize_shaded`], but depth tested against
+    /// `depth` and lit by the light of `shadow` where it isn't in shadow.
+    /// Vertex positions must be in the space the shadow map was rendered in.
+    pub fn rasterize_shadowed(
+        &self,
+        vertices: &[ShadedVertex; 3],
+        canvas: &mut [u32],
+        depth: &mut DepthBuffer,
+        material: &Material,
+        shadow: &ShadowMap,
+    ) {
+        let width = depth.width;
+        let screen = vertices.map(|v| v.screen);
+        for_each_fragment(&screen, width, depth.height, |x, y, weights| {
+            let z = (0..3).map(|i| screen[i].z * weights[i]).sum();
+            if !depth.test_and_set(x, y, z) {
+                return;
+            }
+            let frame = interpolate(vertices, weights);
+            let visibility = shadow.visibility(&frame.point);
+            canvas[y * width + x] = shade(&frame, material, &shadow.light, visibility).to_u32();
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::color::Color;
+
+    /// Two triangles of a horizontal square at height `y`.
+    fn square(half: f64, y: f64) -> [[Point; 3]; 2] {
+        let [a, b, c, d] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, z)| Point::new(x * half, y, z * half));
+        [[a, b, c], [a, c, d]]
+    }
+
+    fn shadow_map() -> ShadowMap {
+        let light = DirectionalLight {
+            direction: V3D::new(0.0, -1.0, 0.0),
+            intensity: Color::white(),
+        };
+        let mut map = ShadowMap::new(light, Point::new(0.0, 0.0, 0.0), 8.0, 64);
+        let mut triangles = square(5.0, 0.0).to_vec();
+        triangles.extend(square(1.0, 2.0));
+        map.render(&TriangleRaster::new(), &triangles);
+        map
+    }
+
+    #[test]
+    fn occluders_cast_shadows() {
+        let map = shadow_map();
+        assert_eq!(map.visibility(&Point::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(map.visibility(&Point::new(3.0, 0.0, -3.0)), 1.0);
+        //the occluder doesn't shadow itself
+        assert_eq!(map.visibility(&Point::new(0.5, 2.0, 0.5)), 1.0);
+    }
+
+    #[test]
+    fn filtering_softens_the_edges() {
+        let mut map = shadow_map();
+        let edge = Point::new(1.0, 0.0, 0.0);
+        let soft = map.visibility(&edge);
+        assert!(soft > 0.0 && soft < 1.0, "{}", soft);
+        map.pcf_radius = 0;
+        assert!([0.0, 1.0].contains(&map.visibility(&edge)));
+    }
+
+    #[test]
+    fn shadowed_fragments_only_get_ambient_light() {
+        let map = shadow_map();
+        //looking straight down at the floor, 10 units on a 10 pixel canvas
+        let mut canvas = vec![0; 100];
+        let mut depth = DepthBuffer::new(10, 10);
+        let raster = TriangleRaster::new();
+        for triangle in square(5.0, 0.0) {
+            let vertices = triangle.map(|p| ShadedVertex {
+                screen: V3D::new(p.x + 5.0, p.z + 5.0, 0.0),
+                position: p,
+                normal: V3D::new(0.0, 1.0, 0.0),
+                tangent: V3D::new(1.0, 0.0, 0.0),
+                uv: (0.0, 0.0),
+            });
+            raster.rasterize_shadowed(
+                &vertices,
+                &mut canvas,
+                &mut depth,
+                &Material::default(),
+                &map,
+            );
+        }
+        assert_eq!(canvas[0], Color::white().to_u32());
+        assert_eq!(canvas[55], Color::new(0.1, 0.1, 0.1).to_u32());
+    }
+}
@@ -0,0 +1,343 @@
+use super::{
+    polygon::{blend_coverage, FillRule, Path},
+    truetype::TrueTypeFont,
+    TriangleRaster,
+};
+use crate::math::{color::Color, point_vec::V3D};
+
+/// Where the anchor `x` sits relative to each line of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    pub color: Color,
+    pub align: TextAlign,
+    /// Line height in pixels. The bitmap font only draws at whole multiples
+    /// of its 8 pixel line, rounding down (but never below 1).
+    pub size: f64,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            color: Color::white(),
+            align: TextAlign::Left,
+            size: 8.0,
+        }
+    }
+}
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+/// Glyph plus one pixel of spacing on the right and below.
+const CELL_WIDTH: usize = GLYPH_WIDTH + 1;
+const CELL_HEIGHT: usize = GLYPH_HEIGHT + 1;
+
+/// Classic 5x7 font for printable ascii (`' '..='~'`), one byte per
+/// column with bit 0 at the top.
+const FONT_5X7: [[u8; GLYPH_WIDTH]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x5f, 0x00, 0x00],
+    [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7f, 0x14, 0x7f, 0x14],
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12],
+    [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50],
+    [0x00, 0x05, 0x03, 0x00, 0x00],
+    [0x00, 0x1c, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1c, 0x00],
+    [0x08, 0x2a, 0x1c, 0x2a, 0x08],
+    [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00],
+    [0x08, 0x08, 0x08, 0x08, 0x08],
+    [0x00, 0x60, 0x60, 0x00, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3e, 0x51, 0x49, 0x45, 0x3e],
+    [0x00, 0x42, 0x7f, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46],
+    [0x21, 0x41, 0x45, 0x4b, 0x31],
+    [0x18, 0x14, 0x12, 0x7f, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39],
+    [0x3c, 0x4a, 0x49, 0x49, 0x30],
+    [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36],
+    [0x06, 0x49, 0x49, 0x29, 0x1e],
+    [0x00, 0x36, 0x36, 0x00, 0x00],
+    [0x00, 0x56, 0x36, 0x00, 0x00],
+    [0x08, 0x14, 0x22, 0x41, 0x00],
+    [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08],
+    [0x02, 0x01, 0x51, 0x09, 0x06],
+    [0x32, 0x49, 0x79, 0x41, 0x3e],
+    [0x7e, 0x11, 0x11, 0x11, 0x7e],
+    [0x7f, 0x49, 0x49, 0x49, 0x36],
+    [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c],
+    [0x7f, 0x49, 0x49, 0x49, 0x41],
+    [0x7f, 0x09, 0x09, 0x09, 0x01],
+    [0x3e, 0x41, 0x49, 0x49, 0x7a],
+    [0x7f, 0x08, 0x08, 0x08, 0x7f],
+    [0x00, 0x41, 0x7f, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3f, 0x01],
+    [0x7f, 0x08, 0x14, 0x22, 0x41],
+    [0x7f, 0x40, 0x40, 0x40, 0x40],
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f],
+    [0x7f, 0x04, 0x08, 0x10, 0x7f],
+    [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06],
+    [0x3e, 0x41, 0x51, 0x21, 0x5e],
+    [0x7f, 0x09, 0x19, 0x29, 0x46],
+    [0x46, 0x49, 0x49, 0x49, 0x31],
+    [0x01, 0x01, 0x7f, 0x01, 0x01],
+    [0x3f, 0x40, 0x40, 0x40, 0x3f],
+    [0x1f, 0x20, 0x40, 0x20, 0x1f],
+    [0x3f, 0x40, 0x38, 0x40, 0x3f],
+    [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x07, 0x08, 0x70, 0x08, 0x07],
+    [0x61, 0x51, 0x49, 0x45, 0x43],
+    [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20],
+    [0x00, 0x41, 0x41, 0x7f, 0x00],
+    [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x01, 0x02, 0x04, 0x00],
+    [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7f, 0x48, 0x44, 0x44, 0x38],
+    [0x38, 0x44, 0x44, 0x44, 0x20],
+    [0x38, 0x44, 0x44, 0x48, 0x7f],
+    [0x38, 0x54, 0x54, 0x54, 0x18],
+    [0x08, 0x7e, 0x09, 0x01, 0x02],
+    [0x0c, 0x52, 0x52, 0x52, 0x3e],
+    [0x7f, 0x08, 0x04, 0x04, 0x78],
+    [0x00, 0x44, 0x7d, 0x40, 0x00],
+    [0x20, 0x40, 0x44, 0x3d, 0x00],
+    [0x7f, 0x10, 0x28, 0x44, 0x00],
+    [0x00, 0x41, 0x7f, 0x40, 0x00],
+    [0x7c, 0x04, 0x18, 0x04, 0x78],
+    [0x7c, 0x08, 0x04, 0x04, 0x78],
+    [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0x7c, 0x14, 0x14, 0x14, 0x08],
+    [0x08, 0x14, 0x14, 0x18, 0x7c],
+    [0x7c, 0x08, 0x04, 0x04, 0x08],
+    [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20],
+    [0x3c, 0x40, 0x40, 0x20, 0x7c],
+    [0x1c, 0x20, 0x40, 0x20, 0x1c],
+    [0x3c, 0x40, 0x30, 0x40, 0x3c],
+    [0x44, 0x28, 0x10, 0x28, 0x44],
+    [0x0c, 0x50, 0x50, 0x50, 0x3c],
+    [0x44, 0x64, 0x54, 0x4c, 0x44],
+    [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x7f, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00],
+    [0x08, 0x04, 0x08, 0x10, 0x08],
+];
+
+/// Columns of the glyph for `c`, characters outside printable ascii are
+/// drawn as `?`.
+fn glyph(c: char) -> &'static [u8; GLYPH_WIDTH] {
+    let index = match c {
+        ' '..='~' => c as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    };
+    &FONT_5X7[index]
+}
+
+/// Left edge of a line of `width` pixels anchored at `x`.
+fn aligned_start(x: f64, width: f64, align: TextAlign) -> f64 {
+    match align {
+        TextAlign::Left => x,
+        TextAlign::Center => x - width / 2.0,
+        TextAlign::Right => x - width,
+    }
+}
+
+impl TriangleRaster {
+    /// Draws `text` with the embedded bitmap font at `(x, y)`, where `y` is
+    /// the top of the first line and `\n` starts a new one.
+    pub fn draw_text(
+        &self,
+        text: &str,
+        (x, y): (f64, f64),
+        style: &TextStyle,
+        canvas: &mut [u32],
+        width: usize,
+    ) {
+        let height = canvas.len() / width.max(1);
+        let scale = ((style.size / CELL_HEIGHT as f64) as usize).max(1);
+        let color = style.color.to_u32();
+        for (line_index, line) in text.split('\n').enumerate() {
+            let line_width = (line.chars().count() * CELL_WIDTH * scale) as f64;
+            let left = aligned_start(x, line_width, style.align).round() as i64;
+            let top = y.round() as i64 + (line_index * CELL_HEIGHT * scale) as i64;
+            for (i, c) in line.chars().enumerate() {
+                let glyph_left = left + (i * CELL_WIDTH * scale) as i64;
+                for (column, bits) in glyph(c).iter().enumerate() {
+                    for row in (0..GLYPH_HEIGHT).filter(|row| bits >> row & 1 == 1) {
+                        let px = glyph_left + (column * scale) as i64;
+                        let py = top + (row * scale) as i64;
+                        fill_block(canvas, width, height, px, py, scale, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws anti-aliased `text` with a TrueType font, `y` is the top of
+    /// the first line and lines are `style.size` pixels apart.
+    pub fn draw_text_with(
+        &self,
+        font: &TrueTypeFont,
+        text: &str,
+        (x, y): (f64, f64),
+        style: &TextStyle,
+        canvas: &mut [u32],
+        width: usize,
+    ) {
+        let height = canvas.len() / width.max(1);
+        let mut path = Path::new();
+        for (line_index, line) in text.split('\n').enumerate() {
+            let line_width: f64 = line.chars().map(|c| font.advance(c, style.size)).sum();
+            let mut pen = aligned_start(x, line_width, style.align);
+            let baseline = y + font.ascent(style.size) + line_index as f64 * style.size;
+            for c in line.chars() {
+                let glyph = font
+                    .glyph_path(c, style.size)
+                    .map_points(|p| V3D::new(p.x + pen, p.y + baseline, 0.0));
+                path.append(glyph);
+                pen += font.advance(c, style.size);
+            }
+        }
+        let coverage = self.path_coverage(&path, FillRule::NonZero, width, height);
+        blend_coverage(canvas, &coverage, style.color);
+    }
+}
+
+fn fill_block(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    x: i64,
+    y: i64,
+    size: usize,
+    color: u32,
+) {
+    let clamp = |v: i64, max: usize| v.clamp(0, max as i64) as usize;
+    let (x0, x1) = (clamp(x, width), clamp(x + size as i64, width));
+    for py in clamp(y, height)..clamp(y + size as i64, height) {
+        canvas[py * width + x0..py * width + x1].fill(color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WIDTH: usize = 20;
+
+    fn lit(canvas: &[u32]) -> Vec<(usize, usize)> {
+        (0..canvas.len())
+            .filter(|i| canvas[*i] != 0)
+            .map(|i| (i % WIDTH, i / WIDTH))
+            .collect()
+    }
+
+    #[test]
+    fn draws_glyph_bits() {
+        let mut canvas = vec![0; WIDTH * 10];
+        TriangleRaster::new().draw_text("!", (0.0, 0.0), &TextStyle::default(), &mut canvas, WIDTH);
+        //the exclamation mark is a column of 5 pixels, a gap and a dot
+        let expected: Vec<_> = [0, 1, 2, 3, 4, 6].iter().map(|y| (2, *y)).collect();
+        assert_eq!(lit(&canvas), expected);
+    }
+
+    #[test]
+    fn alignment_moves_the_line() {
+        let style = |align| TextStyle {
+            align,
+            ..TextStyle::default()
+        };
+        let raster = TriangleRaster::new();
+        let mut left = vec![0; WIDTH * 10];
+        raster.draw_text("--", (0.0, 0.0), &style(TextAlign::Left), &mut left, WIDTH);
+        let mut right = vec![0; WIDTH * 10];
+        raster.draw_text(
+            "--",
+            (12.0, 0.0),
+            &style(TextAlign::Right),
+            &mut right,
+            WIDTH,
+        );
+        let mut center = vec![0; WIDTH * 10];
+        raster.draw_text(
+            "--",
+            (6.0, 0.0),
+            &style(TextAlign::Center),
+            &mut center,
+            WIDTH,
+        );
+        assert_eq!(lit(&left), lit(&right));
+        assert_eq!(lit(&left), lit(&center));
+    }
+
+    #[test]
+    fn scales_colors_and_clips() {
+        let style = TextStyle {
+            color: Color::new(1.0, 0.0, 0.0),
+            size: 16.0,
+            ..TextStyle::default()
+        };
+        let mut canvas = vec![0; WIDTH * 10];
+        TriangleRaster::new().draw_text(".\n.", (15.0, -2.0), &style, &mut canvas, WIDTH);
+        //the first dot is cut by the right and bottom edges, the second line is off canvas
+        let red = Color::new(1.0, 0.0, 0.0).to_u32();
+        let lit = lit(&canvas);
+        assert!(lit.iter().all(|(x, y)| *x >= 17 && *y >= 8));
+        assert!(lit.iter().all(|(x, y)| canvas[y * WIDTH + x] == red));
+        assert_eq!(lit.len(), 6);
+    }
+
+    #[test]
+    fn truetype_text_is_aligned_and_colored() {
+        let font = TrueTypeFont::parse(&crate::rasterizer::truetype::test::square_font()).unwrap();
+        let style = |align| TextStyle {
+            color: Color::new(0.0, 1.0, 0.0),
+            align,
+            size: 10.0,
+        };
+        let raster = TriangleRaster::new();
+        let draw = |x, align| {
+            let mut canvas = vec![0; WIDTH * 10];
+            raster.draw_text_with(&font, "A", (x, 0.0), &style(align), &mut canvas, WIDTH);
+            lit(&canvas)
+        };
+        //'A' is a 4x4 square, 1 pixel into its 6 pixel advance and on the baseline 8 pixels down
+        let left = draw(0.0, TextAlign::Left);
+        let expected: Vec<_> = (4..8).flat_map(|y| (1..5).map(move |x| (x, y))).collect();
+        assert_eq!(left, expected);
+        let shifted = |dx| left.iter().map(|(x, y)| (x + dx, *y)).collect::<Vec<_>>();
+        assert_eq!(draw(12.0, TextAlign::Right), shifted(6));
+        assert_eq!(draw(6.0, TextAlign::Center), shifted(3));
+        let mut canvas = vec![0; WIDTH * 10];
+        raster.draw_text_with(
+            &font,
+            "A",
+            (0.0, 0.0),
+            &style(TextAlign::Left),
+            &mut canvas,
+            WIDTH,
+        );
+        assert_eq!(canvas[5 * WIDTH + 2], Color::new(0.0, 1.0, 0.0).to_u32());
+    }
+
+    #[test]
+    fn unknown_characters_use_a_question_mark() {
+        assert_eq!(glyph('é'), glyph('?'));
+    }
+}
@@ -25,6 +25,7 @@ impl Boundable for &[V3D] {
     }
 }
 
+#[derive(Default)]
 pub struct TriangleRaster {}
 
 impl TriangleRaster {
@@ -50,7 +51,7 @@ impl TriangleRaster {
             }
             let mut x = min.x;
             canvas[index_start..index_end].iter_mut().for_each(|color| {
-                let p = V3D::new(x as f64, y as f64, 0.0);
+                let p = V3D::new(x, y as f64, 0.0);
                 if normal_triangle.is_inside(&p) {
                     *color = paint_color;
                 }
@@ -87,22 +88,19 @@ impl TriangleRaster {
             let mut chunks = canvas[index_start..index_end].chunks_exact_mut(4);
             chunks.borrow_mut().for_each(|chunk| {
                 let mask = simd_triangle.is_inside(vx, vy);
-                vx = vx + add_four;
+                vx += add_four;
                 let painted = mask.select(paint_values, not_paint_values);
                 painted.copy_to_slice(chunk);
             });
 
             //then we paint the remainder
             let remainder = chunks.into_remainder();
-            if remainder.len() > 0 {
+            if !remainder.is_empty() {
                 let mask = simd_triangle.is_inside(vx, vy);
                 let painted = mask.select(paint_values, not_paint_values).to_array();
-                remainder
-                    .iter_mut()
-                    .zip(painted.into_iter())
-                    .for_each(|(c, p)| {
-                        *c = p;
-                    });
+                remainder.iter_mut().zip(painted).for_each(|(c, p)| {
+                    *c = p;
+                });
             }
         }
     }
@@ -132,8 +130,7 @@ impl SimdTriangle {
         let sign0 = cross0.simd_ge(zeros);
         let sign1 = cross1.simd_ge(zeros);
         let sign2 = cross2.simd_ge(zeros);
-        let mask = sign0 & sign1 & sign2;
-        mask
+        sign0 & sign1 & sign2
     }
 
     fn from_triangle(triangle: &Triangle) -> SimdTriangle {
@@ -166,6 +163,31 @@ impl SimdTriangle {
     }
 }
 
+struct NormalTriangle {
+    abc: [V3D; 3],
+    v012: [V3D; 3],
+}
+
+impl NormalTriangle {
+    fn is_inside(&self, p: &V3D) -> bool {
+        let cross0 = (*p - self.abc[0]).cross_z(&self.v012[0]);
+        let cross1 = (*p - self.abc[1]).cross_z(&self.v012[1]);
+        let cross2 = (*p - self.abc[2]).cross_z(&self.v012[2]);
+        cross0.is_sign_positive() && cross1.is_sign_positive() && cross2.is_sign_positive()
+    }
+
+    fn from_triangle(triangle: &Triangle) -> NormalTriangle {
+        let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+        let v0 = b - a;
+        let v1 = c - b;
+        let v2 = a - c;
+        NormalTriangle {
+            abc: [a, b, c],
+            v012: [v0, v1, v2],
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::simd::{self};
@@ -179,9 +201,9 @@ mod test {
         ];
         let normal_triangle = super::NormalTriangle::from_triangle(&triangle);
         let inside = super::V3D::new(10.0, 50.0, 0.0);
-        assert_eq!(normal_triangle.is_inside(&inside), true);
+        assert!(normal_triangle.is_inside(&inside));
         let outside = super::V3D::new(100.0, 0.0, 0.0);
-        assert_eq!(normal_triangle.is_inside(&outside), false);
+        assert!(!normal_triangle.is_inside(&outside));
     }
 
     #[test]
@@ -195,41 +217,16 @@ mod test {
         let x = simd::f32x4::from_array([10.0, 10.0, 10.0, 10.0]);
         let y = simd::f32x4::from_array([50.0, 50.0, 50.0, 50.0]);
         let mask = simd_triangle.is_inside(x, y);
-        assert_eq!(mask.test(0), true);
-        assert_eq!(mask.test(1), true);
-        assert_eq!(mask.test(2), true);
-        assert_eq!(mask.test(3), true);
+        assert!(mask.test(0));
+        assert!(mask.test(1));
+        assert!(mask.test(2));
+        assert!(mask.test(3));
         let x = simd::f32x4::from_array([100.0, 100.0, 100.0, 100.0]);
         let y = simd::f32x4::from_array([0.0, 0.0, 0.0, 0.0]);
         let mask = simd_triangle.is_inside(x, y);
-        assert_eq!(mask.test(0), false);
-        assert_eq!(mask.test(1), false);
-        assert_eq!(mask.test(2), false);
-        assert_eq!(mask.test(3), false);
-    }
-}
-
-struct NormalTriangle {
-    abc: [V3D; 3],
-    v012: [V3D; 3],
-}
-
-impl NormalTriangle {
-    fn is_inside(&self, p: &V3D) -> bool {
-        let cross0 = (*p - self.abc[0]).cross_z(&self.v012[0]);
-        let cross1 = (*p - self.abc[1]).cross_z(&self.v012[1]);
-        let cross2 = (*p - self.abc[2]).cross_z(&self.v012[2]);
-        cross0.is_sign_positive() && cross1.is_sign_positive() && cross2.is_sign_positive()
-    }
-
-    fn from_triangle(triangle: &Triangle) -> NormalTriangle {
-        let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
-        let v0 = b - a;
-        let v1 = c - b;
-        let v2 = a - c;
-        NormalTriangle {
-            abc: [a, b, c],
-            v012: [v0, v1, v2],
-        }
+        assert!(!mask.test(0));
+        assert!(!mask.test(1));
+        assert!(!mask.test(2));
+        assert!(!mask.test(3));
     }
 }
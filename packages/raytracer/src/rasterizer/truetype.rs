@@ -0,0 +1,626 @@
+use super::polygon::Path;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontError {
+    /// A table every TrueType font needs isn't there.
+    MissingTable(&'static str),
+    /// Offsets or lengths point outside of the file.
+    Truncated,
+    /// No unicode character map in a format we can read (4 or 12).
+    UnsupportedCmap,
+    /// CFF outlines (`.otf`) instead of quadratic TrueType ones.
+    UnsupportedOutlines,
+}
+
+impl Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FontError::MissingTable(tag) => write!(f, "font has no '{}' table", tag),
+            FontError::Truncated => write!(f, "font data is truncated"),
+            FontError::UnsupportedCmap => write!(f, "font has no supported unicode cmap"),
+            FontError::UnsupportedOutlines => write!(f, "only TrueType outlines are supported"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// Big endian reads that fail instead of panicking on short data.
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8], FontError> {
+        self.data
+            .get(offset..offset.checked_add(len).ok_or(FontError::Truncated)?)
+            .ok_or(FontError::Truncated)
+    }
+
+    fn u8(&self, offset: usize) -> Result<u8, FontError> {
+        Ok(self.bytes(offset, 1)?[0])
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, FontError> {
+        let b = self.bytes(offset, 2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&self, offset: usize) -> Result<i16, FontError> {
+        Ok(self.u16(offset)? as i16)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, FontError> {
+        let b = self.bytes(offset, 4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn sub(&self, offset: usize, len: usize) -> Result<Reader<'a>, FontError> {
+        Ok(Reader {
+            data: self.bytes(offset, len)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum CharMap {
+    /// Format 4 subtable, kept whole since glyph ids may be read relative to it.
+    Segments(Vec<u8>),
+    /// Format 12 `(first char, last char, first glyph)` groups.
+    Groups(Vec<(u32, u32, u32)>),
+}
+
+/// Outline point in font units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OutlinePoint {
+    x: f64,
+    y: f64,
+    on_curve: bool,
+}
+
+/// Parsed TrueType font, enough of it to turn characters into outlines:
+/// character map, glyph outlines (simple and composite) and horizontal
+/// metrics. Hinting and kerning are ignored.
+#[derive(Debug, Clone)]
+pub struct TrueTypeFont {
+    glyf: Vec<u8>,
+    offsets: Vec<usize>,
+    advances: Vec<u16>,
+    cmap: CharMap,
+    units_per_em: f64,
+    ascender: f64,
+    descender: f64,
+}
+
+/// Composite glyphs can nest, but never this deep in a sane font.
+const MAX_COMPOSITE_DEPTH: usize = 8;
+
+impl TrueTypeFont {
+    pub fn parse(data: &[u8]) -> Result<TrueTypeFont, FontError> {
+        let file = Reader { data };
+        if file.u32(0)? == u32::from_be_bytes(*b"OTTO") {
+            return Err(FontError::UnsupportedOutlines);
+        }
+        let num_tables = file.u16(4)? as usize;
+        let table = |tag: &'static str| -> Result<Reader<'_>, FontError> {
+            for i in 0..num_tables {
+                let record = 12 + i * 16;
+                if file.bytes(record, 4)? == tag.as_bytes() {
+                    let offset = file.u32(record + 8)? as usize;
+                    let length = file.u32(record + 12)? as usize;
+                    return file.sub(offset, length);
+                }
+            }
+            Err(FontError::MissingTable(tag))
+        };
+
+        let head = table("head")?;
+        let units_per_em = head.u16(18)? as f64;
+        let long_offsets = head.i16(50)? != 0;
+        let num_glyphs = table("maxp")?.u16(4)? as usize;
+        let hhea = table("hhea")?;
+        let ascender = hhea.i16(4)? as f64;
+        let descender = hhea.i16(6)? as f64;
+        let num_metrics = hhea.u16(34)? as usize;
+
+        let hmtx = table("hmtx")?;
+        let advances = (0..num_metrics)
+            .map(|i| hmtx.u16(i * 4))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let loca = table("loca")?;
+        let offsets = (0..=num_glyphs)
+            .map(|i| match long_offsets {
+                true => loca.u32(i * 4).map(|o| o as usize),
+                false => loca.u16(i * 2).map(|o| o as usize * 2),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TrueTypeFont {
+            glyf: table("glyf")?.data.to_vec(),
+            offsets,
+            advances,
+            cmap: parse_cmap(table("cmap")?)?,
+            units_per_em,
+            ascender,
+            descender,
+        })
+    }
+
+    fn scale(&self, size: f64) -> f64 {
+        size / self.units_per_em
+    }
+
+    /// Distance from the top of a line to the baseline, for an em of `size` pixels.
+    pub fn ascent(&self, size: f64) -> f64 {
+        self.ascender * self.scale(size)
+    }
+
+    /// Distance from the baseline to the bottom of a line (positive).
+    pub fn descent(&self, size: f64) -> f64 {
+        -self.descender * self.scale(size)
+    }
+
+    /// Glyph id for `c`, 0 (the "missing" glyph) when the font lacks it or
+    /// maps it past its last glyph.
+    pub fn glyph_id(&self, c: char) -> u16 {
+        let c = c as u32;
+        let id = match &self.cmap {
+            CharMap::Groups(groups) => groups
+                .iter()
+                .find(|(start, end, _)| (*start..=*end).contains(&c))
+                .and_then(|(start, _, glyph)| glyph.checked_add(c - start))
+                .and_then(|id| u16::try_from(id).ok()),
+            CharMap::Segments(table) => format4_lookup(Reader { data: table }, c).ok(),
+        };
+        //loca has one more offset than there are glyphs
+        id.filter(|id| (*id as usize) < self.offsets.len() - 1)
+            .unwrap_or(0)
+    }
+
+    /// Horizontal advance of `c` in pixels, for an em of `size` pixels.
+    pub fn advance(&self, c: char, size: f64) -> f64 {
+        let id = self.glyph_id(c) as usize;
+        let units = self
+            .advances
+            .get(id)
+            .or(self.advances.last())
+            .copied()
+            .unwrap_or(0);
+        units as f64 * self.scale(size)
+    }
+
+    /// Outline of `c` for an em of `size` pixels, with the origin on the
+    /// baseline and y growing downwards like the canvas.
+    pub fn glyph_path(&self, c: char, size: f64) -> Path {
+        let scale = self.scale(size);
+        let contours = self.outline(self.glyph_id(c), 0).unwrap_or_default();
+        contours.iter().fold(Path::new(), |path, contour| {
+            contour_path(path, contour, |p| (p.x * scale, -p.y * scale))
+        })
+    }
+
+    fn outline(&self, id: u16, depth: usize) -> Result<Vec<Vec<OutlinePoint>>, FontError> {
+        let id = id as usize;
+        let (Some(start), Some(end)) = (self.offsets.get(id), self.offsets.get(id + 1)) else {
+            return Ok(vec![]);
+        };
+        if end <= start {
+            return Ok(vec![]);
+        }
+        let glyph = Reader { data: &self.glyf }.sub(*start, end - start)?;
+        let num_contours = glyph.i16(0)?;
+        if num_contours >= 0 {
+            simple_outline(glyph, num_contours as usize)
+        } else if depth < MAX_COMPOSITE_DEPTH {
+            self.composite_outline(glyph, depth)
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    fn composite_outline(
+        &self,
+        glyph: Reader,
+        depth: usize,
+    ) -> Result<Vec<Vec<OutlinePoint>>, FontError> {
+        const ARGS_ARE_WORDS: u16 = 0x1;
+        const ARGS_ARE_XY: u16 = 0x2;
+        const HAS_SCALE: u16 = 0x8;
+        const MORE_COMPONENTS: u16 = 0x20;
+        const HAS_XY_SCALE: u16 = 0x40;
+        const HAS_2X2: u16 = 0x80;
+        let f2dot14 = |offset| glyph.i16(offset).map(|v| v as f64 / 16384.0);
+
+        let mut contours = vec![];
+        let mut offset = 10;
+        loop {
+            let flags = glyph.u16(offset)?;
+            let component = glyph.u16(offset + 2)?;
+            offset += 4;
+            let (dx, dy) = if flags & ARGS_ARE_WORDS != 0 {
+                offset += 4;
+                (glyph.i16(offset - 4)? as f64, glyph.i16(offset - 2)? as f64)
+            } else {
+                offset += 2;
+                (
+                    glyph.u8(offset - 2)? as i8 as f64,
+                    glyph.u8(offset - 1)? as i8 as f64,
+                )
+            };
+            //matching points instead of offsets is rare enough to place those at the origin
+            let (dx, dy) = if flags & ARGS_ARE_XY != 0 {
+                (dx, dy)
+            } else {
+                (0.0, 0.0)
+            };
+            let [a, b, c, d] = if flags & HAS_SCALE != 0 {
+                offset += 2;
+                let s = f2dot14(offset - 2)?;
+                [s, 0.0, 0.0, s]
+            } else if flags & HAS_XY_SCALE != 0 {
+                offset += 4;
+                [f2dot14(offset - 4)?, 0.0, 0.0, f2dot14(offset - 2)?]
+            } else if flags & HAS_2X2 != 0 {
+                offset += 8;
+                [
+                    f2dot14(offset - 8)?,
+                    f2dot14(offset - 6)?,
+                    f2dot14(offset - 4)?,
+                    f2dot14(offset - 2)?,
+                ]
+            } else {
+                [1.0, 0.0, 0.0, 1.0]
+            };
+            for contour in self.outline(component, depth + 1)? {
+                contours.push(
+                    contour
+                        .into_iter()
+                        .map(|p| OutlinePoint {
+                            x: a * p.x + c * p.y + dx,
+                            y: b * p.x + d * p.y + dy,
+                            on_curve: p.on_curve,
+                        })
+                        .collect(),
+                );
+            }
+            if flags & MORE_COMPONENTS == 0 {
+                return Ok(contours);
+            }
+        }
+    }
+}
+
+fn parse_cmap(cmap: Reader) -> Result<CharMap, FontError> {
+    let mut format4 = None;
+    for i in 0..cmap.u16(2)? as usize {
+        let record = 4 + i * 8;
+        let platform = cmap.u16(record)?;
+        let encoding = cmap.u16(record + 2)?;
+        let offset = cmap.u32(record + 4)? as usize;
+        let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+        if !unicode {
+            continue;
+        }
+        match cmap.u16(offset)? {
+            12 => {
+                let count = cmap.u32(offset + 12)? as usize;
+                let groups = (0..count)
+                    .map(|g| {
+                        let group = offset + 16 + g * 12;
+                        Ok((cmap.u32(group)?, cmap.u32(group + 4)?, cmap.u32(group + 8)?))
+                    })
+                    .collect::<Result<Vec<_>, FontError>>()?;
+                return Ok(CharMap::Groups(groups));
+            }
+            4 => {
+                let length = cmap.u16(offset + 2)? as usize;
+                format4 = Some(cmap.bytes(offset, length)?.to_vec());
+            }
+            _ => {}
+        }
+    }
+    format4
+        .map(CharMap::Segments)
+        .ok_or(FontError::UnsupportedCmap)
+}
+
+fn format4_lookup(table: Reader, c: u32) -> Result<u16, FontError> {
+    if c > 0xffff {
+        return Ok(0);
+    }
+    let segments = table.u16(6)? as usize / 2;
+    let ends = 14;
+    let starts = ends + segments * 2 + 2;
+    let deltas = starts + segments * 2;
+    let range_offsets = deltas + segments * 2;
+    for i in 0..segments {
+        if table.u16(ends + i * 2)? as u32 >= c {
+            let start = table.u16(starts + i * 2)? as u32;
+            if start > c {
+                return Ok(0);
+            }
+            let delta = table.u16(deltas + i * 2)?;
+            let range_offset = table.u16(range_offsets + i * 2)? as usize;
+            if range_offset == 0 {
+                return Ok((c as u16).wrapping_add(delta));
+            }
+            //the offset is relative to where it is stored
+            let address = range_offsets + i * 2 + range_offset + (c - start) as usize * 2;
+            let glyph = table.u16(address)?;
+            return Ok(if glyph == 0 {
+                0
+            } else {
+                glyph.wrapping_add(delta)
+            });
+        }
+    }
+    Ok(0)
+}
+
+fn simple_outline(glyph: Reader, num_contours: usize) -> Result<Vec<Vec<OutlinePoint>>, FontError> {
+    const ON_CURVE: u8 = 0x1;
+    const X_SHORT: u8 = 0x2;
+    const Y_SHORT: u8 = 0x4;
+    const REPEAT: u8 = 0x8;
+    const X_SAME_OR_POSITIVE: u8 = 0x10;
+    const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+    let ends = (0..num_contours)
+        .map(|i| glyph.u16(10 + i * 2).map(|e| e as usize))
+        .collect::<Result<Vec<_>, _>>()?;
+    let num_points = ends.last().map_or(0, |last| last + 1);
+    let instructions = glyph.u16(10 + num_contours * 2)? as usize;
+    let mut offset = 12 + num_contours * 2 + instructions;
+
+    let mut flags = Vec::with_capacity(num_points);
+    while flags.len() < num_points {
+        let flag = glyph.u8(offset)?;
+        offset += 1;
+        flags.push(flag);
+        if flag & REPEAT != 0 {
+            let count = glyph.u8(offset)?;
+            offset += 1;
+            flags.extend(std::iter::repeat_n(flag, count as usize));
+        }
+    }
+    flags.truncate(num_points);
+
+    let mut read_coordinates = |short: u8, same_or_positive: u8| {
+        let mut value = 0i32;
+        flags
+            .iter()
+            .map(|flag| {
+                if flag & short != 0 {
+                    let delta = glyph.u8(offset)? as i32;
+                    offset += 1;
+                    value += if flag & same_or_positive != 0 {
+                        delta
+                    } else {
+                        -delta
+                    };
+                } else if flag & same_or_positive == 0 {
+                    value += glyph.i16(offset)? as i32;
+                    offset += 2;
+                }
+                Ok(value as f64)
+            })
+            .collect::<Result<Vec<_>, FontError>>()
+    };
+    let xs = read_coordinates(X_SHORT, X_SAME_OR_POSITIVE)?;
+    let ys = read_coordinates(Y_SHORT, Y_SAME_OR_POSITIVE)?;
+
+    let mut start = 0;
+    Ok(ends
+        .iter()
+        .map(|end| {
+            let contour = (start..=*end)
+                .filter(|i| *i < num_points)
+                .map(|i| OutlinePoint {
+                    x: xs[i],
+                    y: ys[i],
+                    on_curve: flags[i] & ON_CURVE != 0,
+                })
+                .collect();
+            start = end + 1;
+            contour
+        })
+        .collect())
+}
+
+/// Appends a TrueType contour (quadratic, with implied on-curve points
+/// between two off-curve ones) to `path`.
+fn contour_path(
+    path: Path,
+    contour: &[OutlinePoint],
+    to_pixels: impl Fn(&OutlinePoint) -> (f64, f64),
+) -> Path {
+    if contour.len() < 2 {
+        return path;
+    }
+    let midpoint = |a: &OutlinePoint, b: &OutlinePoint| OutlinePoint {
+        x: (a.x + b.x) / 2.0,
+        y: (a.y + b.y) / 2.0,
+        on_curve: true,
+    };
+    //start on a point of the curve, making one up when every point is a control point
+    let first_on = contour.iter().position(|p| p.on_curve);
+    let (start, rotated): (OutlinePoint, Vec<&OutlinePoint>) = match first_on {
+        Some(i) => (
+            contour[i],
+            contour[i + 1..].iter().chain(&contour[..=i]).collect(),
+        ),
+        None => (
+            midpoint(&contour[0], &contour[1]),
+            contour[1..].iter().chain(&contour[..=1]).collect(),
+        ),
+    };
+    let (x, y) = to_pixels(&start);
+    let mut path = path.move_to(x, y);
+    let mut control: Option<OutlinePoint> = None;
+    for point in rotated {
+        match (control, point.on_curve) {
+            (None, true) => {
+                let (x, y) = to_pixels(point);
+                path = path.line_to(x, y);
+            }
+            (None, false) => control = Some(*point),
+            (Some(c), true) => {
+                let ((cx, cy), (x, y)) = (to_pixels(&c), to_pixels(point));
+                path = path.quad_to(cx, cy, x, y);
+                control = None;
+            }
+            (Some(c), false) => {
+                let ((cx, cy), (x, y)) = (to_pixels(&c), to_pixels(&midpoint(&c, point)));
+                path = path.quad_to(cx, cy, x, y);
+                control = Some(*point);
+            }
+        }
+    }
+    path.close()
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::rasterizer::{FillRule, TriangleRaster};
+
+    fn table(tag: &str, data: Vec<u8>) -> (String, Vec<u8>) {
+        (tag.to_string(), data)
+    }
+
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    /// Tiny font with 1000 units per em: glyph 1 is a square for 'A'
+    /// (covering x 100..500, y 0..400) and glyph 2, for 'B', is glyph 1
+    /// moved 500 units right through a composite.
+    pub(crate) fn square_font() -> Vec<u8> {
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let maxp = u16s(&[0, 0x5000, 3]);
+        let mut hhea = vec![0; 36];
+        hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+        hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+        hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+        let hmtx = u16s(&[500, 0, 600, 0, 1100, 0]);
+
+        //one contour of four on-curve points, coordinates as words
+        let mut square = u16s(&[1, 100, 0, 500, 400, 3, 0]);
+        square.extend([0x01; 4]);
+        square.extend(u16s(&[100, 400, 0, (-400i16) as u16]));
+        square.extend(u16s(&[0, 0, 400, 0]));
+        //composite: args are xy words, no more components
+        let mut composite = u16s(&[(-1i16) as u16, 600, 0, 1000, 400, 0x0003, 1, 500, 0]);
+        composite.resize(composite.len().next_multiple_of(2), 0);
+        let glyf = [square.clone(), composite.clone()].concat();
+        let loca = u16s(&[
+            0,
+            0,
+            square.len() as u16 / 2,
+            (square.len() + composite.len()) as u16 / 2,
+        ]);
+
+        //format 4 with 'A'..'B' -> 1..2 and the final 0xffff segment
+        let mut subtable = u16s(&[4, 0, 0, 4, 4, 1, 0]);
+        subtable.extend(u16s(&[0x42, 0xffff, 0, 0x41, 0xffff]));
+        subtable.extend(u16s(&[(1i32 - 0x41) as u16, 1, 0, 0]));
+        let length = subtable.len() as u16;
+        subtable[2..4].copy_from_slice(&length.to_be_bytes());
+        let mut cmap = u16s(&[0, 1, 3, 1, 0, 12]);
+        cmap.extend(subtable);
+
+        let tables = [
+            table("cmap", cmap),
+            table("glyf", glyf),
+            table("head", head),
+            table("hhea", hhea),
+            table("hmtx", hmtx),
+            table("loca", loca),
+            table("maxp", maxp),
+        ];
+        let mut font = vec![0, 1, 0, 0];
+        font.extend(u16s(&[tables.len() as u16, 0, 0, 0]));
+        let mut offset = 12 + tables.len() * 16;
+        let mut body: Vec<u8> = vec![];
+        for (tag, data) in &tables {
+            font.extend(tag.as_bytes());
+            font.extend([0; 4]);
+            font.extend((offset as u32).to_be_bytes());
+            font.extend((data.len() as u32).to_be_bytes());
+            body.extend(data);
+            offset += data.len();
+        }
+        font.extend(body);
+        font
+    }
+
+    #[test]
+    fn maps_characters_to_glyphs() {
+        let font = TrueTypeFont::parse(&square_font()).unwrap();
+        assert_eq!(font.glyph_id('A'), 1);
+        assert_eq!(font.glyph_id('B'), 2);
+        assert_eq!(font.glyph_id('C'), 0);
+        assert_eq!(font.advance('A', 10.0), 6.0);
+        assert_eq!(font.ascent(10.0), 8.0);
+        assert_eq!(font.descent(10.0), 2.0);
+    }
+
+    #[test]
+    fn out_of_range_groups_map_to_the_missing_glyph() {
+        let mut font = TrueTypeFont::parse(&square_font()).unwrap();
+        font.cmap = CharMap::Groups(vec![
+            (0x41, 0x42, u32::MAX),
+            (0x43, 0x44, 1),
+            (0x45, 0x45, 70000),
+        ]);
+        assert_eq!(font.glyph_id('A'), 0);
+        assert_eq!(font.glyph_id('B'), 0);
+        assert_eq!(font.glyph_id('C'), 1);
+        assert_eq!(font.glyph_id('D'), 2);
+        assert_eq!(font.glyph_id('E'), 0);
+    }
+
+    #[test]
+    fn square_glyph_fills_its_box() {
+        let font = TrueTypeFont::parse(&square_font()).unwrap();
+        //at 10px per em the square is 4x4 pixels, from x 1 and up to 4 pixels above the baseline
+        let path = font
+            .glyph_path('A', 10.0)
+            .map_points(|p| crate::math::point_vec::V3D::new(p.x, p.y + 5.0, 0.0));
+        let coverage = TriangleRaster::new().path_coverage(&path, FillRule::NonZero, 6, 6);
+        let area: f32 = coverage.iter().sum();
+        assert!((area - 16.0).abs() < 1e-3);
+        assert_eq!(coverage[2 * 6 + 2], 1.0);
+        assert_eq!(coverage[0], 0.0);
+    }
+
+    #[test]
+    fn composite_glyphs_are_offset() {
+        let font = TrueTypeFont::parse(&square_font()).unwrap();
+        let a = font.glyph_path('A', 10.0);
+        let b = font.glyph_path('B', 10.0);
+        let shifted = a.map_points(|p| crate::math::point_vec::V3D::new(p.x + 5.0, p.y, 0.0));
+        assert_eq!(b, shifted);
+    }
+
+    #[test]
+    fn rejects_broken_fonts() {
+        let font = square_font();
+        assert_eq!(
+            TrueTypeFont::parse(&font[..100]).unwrap_err(),
+            FontError::Truncated
+        );
+        assert_eq!(
+            TrueTypeFont::parse(b"OTTO\0\0\0\0").unwrap_err(),
+            FontError::UnsupportedOutlines
+        );
+        assert_eq!(
+            TrueTypeFont::parse(&[0, 1, 0, 0, 0, 0]).unwrap_err(),
+            FontError::MissingTable("head")
+        );
+    }
+}
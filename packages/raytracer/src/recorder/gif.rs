@@ -0,0 +1,226 @@
+use super::quantize::{median_cut, PaletteMapper, MAX_COLORS};
+use std::collections::HashMap;
+
+/// Palette indices are always 8 bits, the color table is padded to 256.
+const MIN_CODE_SIZE: u8 = 8;
+const MAX_CODE: u16 = 4095;
+
+/// Packs variable width codes least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Code table state of the LZW encoder.
+struct Lzw {
+    out: BitWriter,
+    width: u8,
+    /// Latest code in the table, the one the decoder will define next.
+    next: u16,
+    /// Code at which the width grows by one bit.
+    overflow: u16,
+    table: HashMap<(u16, u8), u16>,
+}
+
+const CLEAR: u16 = 1 << MIN_CODE_SIZE;
+const END: u16 = CLEAR + 1;
+
+impl Lzw {
+    fn new() -> Lzw {
+        let mut lzw = Lzw {
+            out: BitWriter {
+                bytes: vec![],
+                buffer: 0,
+                bits: 0,
+            },
+            width: 0,
+            next: 0,
+            overflow: 0,
+            table: HashMap::new(),
+        };
+        lzw.reset();
+        lzw
+    }
+
+    fn reset(&mut self) {
+        self.out.write(CLEAR, self.width.max(MIN_CODE_SIZE + 1));
+        self.width = MIN_CODE_SIZE + 1;
+        self.next = END;
+        self.overflow = 1 << self.width;
+        self.table.clear();
+    }
+
+    /// Moves on to the next code, false when that filled the table and
+    /// it had to be reset.
+    fn grow(&mut self) -> bool {
+        self.next += 1;
+        if self.next == self.overflow {
+            self.width += 1;
+            self.overflow <<= 1;
+        }
+        if self.next == MAX_CODE {
+            self.reset();
+            return false;
+        }
+        true
+    }
+}
+
+/// GIF flavored LZW: a clear code first, code widths from 9 up to 12 bits
+/// and a reset (with a new clear code) whenever the table fills up.
+pub fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    let mut lzw = Lzw::new();
+    let Some((&first, rest)) = indices.split_first() else {
+        lzw.out.write(END, lzw.width);
+        return lzw.out.finish();
+    };
+    let mut code = first as u16;
+    for &index in rest {
+        if let Some(&known) = lzw.table.get(&(code, index)) {
+            code = known;
+            continue;
+        }
+        lzw.out.write(code, lzw.width);
+        let key = (code, index);
+        code = index as u16;
+        if lzw.grow() {
+            lzw.table.insert(key, lzw.next);
+        }
+    }
+    lzw.out.write(code, lzw.width);
+    lzw.grow();
+    lzw.out.write(END, lzw.width);
+    lzw.out.finish()
+}
+
+/// Animated, endlessly looping GIF of `frames` (canvas pixels, see
+/// `Color::to_u32`) sharing one median cut palette.
+pub fn encode(width: usize, height: usize, frames: &[Vec<u32>], delay_cs: u16) -> Vec<u8> {
+    let palette = median_cut(frames.iter().flatten(), MAX_COLORS);
+    let mut mapper = PaletteMapper::new(&palette);
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend((width as u16).to_le_bytes());
+    gif.extend((height as u16).to_le_bytes());
+    //global table of 2^(7+1) colors, 8 bits per channel
+    gif.extend([0xf7, 0, 0]);
+    for i in 0..MAX_COLORS {
+        gif.extend(palette.get(i).unwrap_or(&[0, 0, 0]));
+    }
+    gif.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+    for frame in frames {
+        gif.extend([0x21, 0xf9, 0x04, 0x00]);
+        gif.extend(delay_cs.to_le_bytes());
+        gif.extend([0x00, 0x00]);
+        gif.push(0x2c);
+        gif.extend([0, 0, 0, 0]);
+        gif.extend((width as u16).to_le_bytes());
+        gif.extend((height as u16).to_le_bytes());
+        gif.push(0);
+        gif.push(MIN_CODE_SIZE);
+        let indices: Vec<u8> = frame.iter().map(|p| mapper.index_of(*p)).collect();
+        for block in lzw_encode(&indices).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend(block);
+        }
+        gif.push(0);
+    }
+    gif.push(0x3b);
+    gif
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Straightforward GIF LZW decoder to check the encoder against.
+    fn lzw_decode(data: &[u8]) -> Vec<u8> {
+        let (mut bits, mut buffer, mut position) = (0u8, 0u32, 0);
+        let mut width = MIN_CODE_SIZE + 1;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = vec![];
+        loop {
+            while bits < width {
+                buffer |= (data[position] as u32) << bits;
+                position += 1;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << width) - 1)) as u16;
+            buffer >>= width;
+            bits -= width;
+            if code == CLEAR {
+                table = (0..=END).map(|i| vec![i as u8]).collect();
+                width = MIN_CODE_SIZE + 1;
+                previous = None;
+                continue;
+            }
+            if code == END {
+                return out;
+            }
+            let entry = match (table.get(code as usize), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => [prev.clone(), vec![prev[0]]].concat(),
+                (None, None) => panic!("bad code"),
+            };
+            out.extend(&entry);
+            if let Some(prev) = previous {
+                table.push([prev, vec![entry[0]]].concat());
+            }
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trips() {
+        assert_eq!(lzw_decode(&lzw_encode(&[])), Vec::<u8>::new());
+        let repeated = vec![7; 1000];
+        assert_eq!(lzw_decode(&lzw_encode(&repeated)), repeated);
+        //pseudo random data fills the table and forces resets
+        let mut state = 1u32;
+        let noisy: Vec<u8> = (0..50_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8 % 40
+            })
+            .collect();
+        assert_eq!(lzw_decode(&lzw_encode(&noisy)), noisy);
+    }
+
+    #[test]
+    fn writes_every_frame() {
+        let red = u32::from_le_bytes([255, 0, 0, 255]);
+        let blue = u32::from_le_bytes([0, 0, 255, 255]);
+        let frames = vec![vec![red; 4], vec![blue; 4], vec![red, blue, red, blue]];
+        let gif = encode(2, 2, &frames, 5);
+        assert!(gif.starts_with(b"GIF89a\x02\x00\x02\x00"));
+        assert_eq!(gif.last(), Some(&0x3b));
+        let descriptors = gif.windows(2).filter(|w| w == &[0x21, 0xf9]).count();
+        assert_eq!(descriptors, 3);
+        //the palette holds both colors exactly
+        let table = &gif[13..13 + 768];
+        assert_eq!(&table[..6], &[0, 0, 255, 255, 0, 0]);
+    }
+}
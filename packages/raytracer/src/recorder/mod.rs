@@ -0,0 +1,163 @@
+pub mod gif;
+pub mod png;
+pub mod quantize;
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Formats for numbered image sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    /// Binary (P6) portable pixmap.
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+        }
+    }
+
+    pub fn encode(&self, width: usize, height: usize, pixels: &[u32]) -> Vec<u8> {
+        match self {
+            ImageFormat::Png => png::encode(width, height, pixels),
+            ImageFormat::Ppm => {
+                let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+                ppm.extend(pixels.iter().flat_map(|p| quantize::rgb(*p)));
+                ppm
+            }
+        }
+    }
+}
+
+/// Collects successive canvas frames (as produced by the rasterizer or
+/// `Canvas`) to write them out as an animated GIF or an image sequence.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    width: usize,
+    height: usize,
+    frames: Vec<Vec<u32>>,
+    /// Time each GIF frame is shown, in hundredths of a second.
+    frame_delay: u16,
+}
+
+impl Recorder {
+    pub fn new(width: usize, height: usize) -> Recorder {
+        Recorder {
+            width,
+            height,
+            frames: vec![],
+            frame_delay: 4,
+        }
+    }
+
+    /// Sets the GIF frame rate, rounded to the hundredths of a second the
+    /// format counts in.
+    pub fn with_fps(mut self, fps: f64) -> Recorder {
+        self.frame_delay = (100.0 / fps).round().clamp(1.0, u16::MAX as f64) as u16;
+        self
+    }
+
+    pub fn capture(&mut self, pixels: &[u32]) {
+        assert_eq!(
+            pixels.len(),
+            self.width * self.height,
+            "frame doesn't match the recorder size"
+        );
+        self.frames.push(pixels.to_vec());
+    }
+
+    pub fn frames(&self) -> &[Vec<u32>] {
+        &self.frames
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn encode_gif(&self) -> Vec<u8> {
+        gif::encode(self.width, self.height, &self.frames, self.frame_delay)
+    }
+
+    pub fn write_gif(&self, mut out: impl Write) -> io::Result<()> {
+        out.write_all(&self.encode_gif())
+    }
+
+    /// Writes every frame to `dir` as `{prefix}_0000.{extension}`,
+    /// `{prefix}_0001.{extension}`, ... and returns the paths written.
+    pub fn write_sequence(
+        &self,
+        dir: impl AsRef<Path>,
+        prefix: &str,
+        format: ImageFormat,
+    ) -> io::Result<Vec<PathBuf>> {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                let path = dir
+                    .as_ref()
+                    .join(format!("{}_{:04}.{}", prefix, i, format.extension()));
+                let mut file = BufWriter::new(File::create(&path)?);
+                file.write_all(&format.encode(self.width, self.height, frame))?;
+                file.flush()?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn recorder() -> Recorder {
+        let mut recorder = Recorder::new(2, 1).with_fps(25.0);
+        recorder.capture(&[0xff00_00ff, 0xff00_ff00]);
+        recorder.capture(&[0xff00_ff00, 0xff00_00ff]);
+        recorder
+    }
+
+    #[test]
+    fn ppm_header_and_pixels() {
+        let ppm = ImageFormat::Ppm.encode(2, 1, &recorder().frames()[0]);
+        assert_eq!(ppm, b"P6\n2 1\n255\n\xff\x00\x00\x00\xff\x00");
+    }
+
+    #[test]
+    fn gif_uses_the_frame_rate() {
+        let gif = recorder().encode_gif();
+        let gce = gif.windows(2).position(|w| w == [0x21, 0xf9]).unwrap();
+        assert_eq!(&gif[gce + 4..gce + 6], &[4, 0]);
+    }
+
+    #[test]
+    fn writes_numbered_files() {
+        let dir = std::env::temp_dir().join(format!("recorder_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = recorder()
+            .write_sequence(&dir, "turntable", ImageFormat::Png)
+            .unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[1].ends_with("turntable_0001.png"));
+        assert!(std::fs::read(&paths[0]).unwrap().starts_with(b"\x89PNG"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_frames_of_the_wrong_size() {
+        Recorder::new(2, 2).capture(&[0; 3]);
+    }
+}
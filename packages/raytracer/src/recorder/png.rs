@@ -0,0 +1,120 @@
+use super::quantize::rgb;
+
+/// Stored deflate blocks hold at most this many bytes.
+const MAX_STORED: usize = 65535;
+
+fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+    table
+}
+
+pub fn crc32(table: &[u32; 256], bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        table[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+/// Zlib stream made of uncompressed deflate blocks. Frames are written once
+/// and viewed elsewhere, size matters less than not pulling in a compressor.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+/// 8 bit RGB PNG of canvas pixels (see `Color::to_u32`), alpha is dropped.
+pub fn encode(width: usize, height: usize, pixels: &[u32]) -> Vec<u8> {
+    assert_eq!(
+        pixels.len(),
+        width * height,
+        "pixel count doesn't match size"
+    );
+    let table = crc_table();
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut chunk = |kind: &[u8; 4], data: &[u8]| {
+        png.extend((data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend(kind);
+        png.extend(data);
+        let crc = crc32(&table, &png[start..]);
+        png.extend(crc.to_be_bytes());
+    };
+    let mut header = vec![];
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    //bit depth 8, truecolor, default compression, filtering and no interlace
+    header.extend([8, 2, 0, 0, 0]);
+    chunk(b"IHDR", &header);
+    let mut scanlines = Vec::with_capacity(height * (width * 3 + 1));
+    for row in pixels.chunks(width.max(1)) {
+        scanlines.push(0);
+        scanlines.extend(row.iter().flat_map(|p| rgb(*p)));
+    }
+    chunk(b"IDAT", &zlib_stored(&scanlines));
+    chunk(b"IEND", &[]);
+    png
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(&crc_table(), b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn stored_blocks_split_large_data() {
+        let data = vec![3u8; MAX_STORED + 10];
+        let z = zlib_stored(&data);
+        //header, two block headers, data and adler
+        assert_eq!(z.len(), 2 + 5 + 5 + data.len() + 4);
+        assert_eq!(&z[2..7], &[0, 0xff, 0xff, 0, 0]);
+        assert_eq!(&z[7 + MAX_STORED..12 + MAX_STORED], &[1, 10, 0, 0xf5, 0xff]);
+    }
+
+    #[test]
+    fn writes_chunks() {
+        let png = encode(2, 1, &[0xff00_00ff, 0xffff_0000]);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+        let idat = png.windows(4).position(|w| w == b"IDAT").unwrap();
+        //zlib header, final stored block of 7 bytes: filter, red, blue
+        assert_eq!(
+            &png[idat + 4..idat + 16],
+            &[0x78, 0x01, 1, 7, 0, 0xf8, 0xff, 0, 255, 0, 0, 0]
+        );
+    }
+}
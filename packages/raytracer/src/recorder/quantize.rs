@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+/// At most 256 colors, enough for a GIF color table.
+pub const MAX_COLORS: usize = 256;
+
+/// Channels of a canvas pixel (`0xAABBGGRR`, see `Color::to_u32`).
+pub fn rgb(pixel: u32) -> [u8; 3] {
+    let [r, g, b, _] = pixel.to_le_bytes();
+    [r, g, b]
+}
+
+/// Colors and how many pixels use them, in a box of the color space.
+struct ColorBox {
+    colors: Vec<([u8; 3], u32)>,
+}
+
+impl ColorBox {
+    /// Channel with the widest range and that range.
+    fn widest_channel(&self) -> (usize, u8) {
+        (0..3)
+            .map(|channel| {
+                let values = self.colors.iter().map(|(color, _)| color[channel]);
+                let min = values.clone().min().unwrap_or(0);
+                let max = values.max().unwrap_or(0);
+                (channel, max - min)
+            })
+            .max_by_key(|(_, range)| *range)
+            .unwrap_or((0, 0))
+    }
+
+    /// Splits at the pixel weighted median of the widest channel.
+    fn split(mut self) -> (ColorBox, ColorBox) {
+        let (channel, _) = self.widest_channel();
+        self.colors.sort_by_key(|(color, _)| color[channel]);
+        let total: u64 = self.colors.iter().map(|(_, count)| *count as u64).sum();
+        let mut seen = 0;
+        let mut at = 1;
+        for (i, (_, count)) in self.colors.iter().enumerate() {
+            seen += *count as u64;
+            if seen * 2 >= total {
+                at = i + 1;
+                break;
+            }
+        }
+        let at = at.clamp(1, self.colors.len() - 1);
+        let rest = self.colors.split_off(at);
+        (self, ColorBox { colors: rest })
+    }
+
+    fn average(&self) -> [u8; 3] {
+        let total: u64 = self.colors.iter().map(|(_, count)| *count as u64).sum();
+        let mut sum = [0u64; 3];
+        for (color, count) in &self.colors {
+            for channel in 0..3 {
+                sum[channel] += color[channel] as u64 * *count as u64;
+            }
+        }
+        sum.map(|s| ((s + total / 2) / total.max(1)) as u8)
+    }
+}
+
+/// Palette of at most `max_colors` colors for the given pixels, found with
+/// median cut. When there are few enough distinct colors they are used as
+/// they are.
+pub fn median_cut<'a>(pixels: impl Iterator<Item = &'a u32>, max_colors: usize) -> Vec<[u8; 3]> {
+    let mut histogram: HashMap<[u8; 3], u32> = HashMap::new();
+    for pixel in pixels {
+        *histogram.entry(rgb(*pixel)).or_default() += 1;
+    }
+    let mut colors: Vec<_> = histogram.into_iter().collect();
+    //hash map order is random, keep palettes reproducible
+    colors.sort_unstable();
+    if colors.len() <= max_colors {
+        return colors.into_iter().map(|(color, _)| color).collect();
+    }
+    let mut boxes = vec![ColorBox { colors }];
+    while boxes.len() < max_colors {
+        let Some((index, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.colors.len() > 1)
+            .max_by_key(|(_, b)| b.widest_channel().1)
+        else {
+            break;
+        };
+        let (a, b) = boxes.swap_remove(index).split();
+        boxes.push(a);
+        boxes.push(b);
+    }
+    boxes.iter().map(ColorBox::average).collect()
+}
+
+/// Maps pixels to the index of the closest palette color, remembering the
+/// answers since animation frames repeat most of their colors.
+pub struct PaletteMapper<'a> {
+    palette: &'a [[u8; 3]],
+    cache: HashMap<[u8; 3], u8>,
+}
+
+impl<'a> PaletteMapper<'a> {
+    pub fn new(palette: &'a [[u8; 3]]) -> PaletteMapper<'a> {
+        PaletteMapper {
+            palette,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn index_of(&mut self, pixel: u32) -> u8 {
+        let color = rgb(pixel);
+        let palette = self.palette;
+        *self.cache.entry(color).or_insert_with(|| {
+            let distance = |p: &[u8; 3]| -> i32 {
+                (0..3).map(|c| (p[c] as i32 - color[c] as i32).pow(2)).sum()
+            };
+            (0..palette.len())
+                .min_by_key(|i| distance(&palette[*i]))
+                .unwrap_or(0) as u8
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pixel([r, g, b]: [u8; 3]) -> u32 {
+        u32::from_le_bytes([r, g, b, 255])
+    }
+
+    #[test]
+    fn keeps_small_palettes_exact() {
+        let pixels = [pixel([1, 2, 3]), pixel([4, 5, 6]), pixel([1, 2, 3])];
+        let palette = median_cut(pixels.iter(), MAX_COLORS);
+        assert_eq!(palette, vec![[1, 2, 3], [4, 5, 6]]);
+        let mut mapper = PaletteMapper::new(&palette);
+        assert_eq!(mapper.index_of(pixels[1]), 1);
+        assert_eq!(mapper.index_of(pixel([5, 5, 5])), 1);
+    }
+
+    #[test]
+    fn reduces_gradients() {
+        let pixels: Vec<u32> = (0..=255u8)
+            .flat_map(|r| [0u8, 128, 255].map(|g| pixel([r, g, 0])))
+            .collect();
+        let palette = median_cut(pixels.iter(), 16);
+        assert_eq!(palette.len(), 16);
+        let mut mapper = PaletteMapper::new(&palette);
+        //pixels land on close colors on average
+        let mut total = 0;
+        for p in &pixels {
+            let [r, g, b] = palette[mapper.index_of(*p) as usize];
+            let [pr, pg, pb] = rgb(*p);
+            total += (r as i32 - pr as i32).abs()
+                + (g as i32 - pg as i32).abs()
+                + (b as i32 - pb as i32).abs();
+        }
+        let average = total / pixels.len() as i32;
+        assert!(average < 40, "{}", average);
+    }
+}
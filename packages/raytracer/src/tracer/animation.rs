@@ -0,0 +1,128 @@
+use crate::math::{matrices::Mat4, point_vec::Point, quaternion::Quat};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    pub time: f64,
+    pub position: Point,
+    pub rotation: Quat<f64>,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, position: Point, rotation: Quat<f64>) -> Keyframe {
+        Keyframe {
+            time,
+            position,
+            rotation,
+        }
+    }
+}
+
+/// Keyframed motion of a camera or an object. Positions are interpolated
+/// linearly and rotations with slerp.
+#[derive(Debug, Clone, Default)]
+pub struct Track {
+    keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    pub fn new(mut keyframes: Vec<Keyframe>) -> Track {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Track { keyframes }
+    }
+
+    pub fn push(&mut self, keyframe: Keyframe) {
+        let index = self
+            .keyframes
+            .partition_point(|other| other.time <= keyframe.time);
+        self.keyframes.insert(index, keyframe);
+    }
+
+    /// Position and rotation at `time`, clamped to the first and last keyframes.
+    pub fn sample(&self, time: f64) -> Option<(Point, Quat<f64>)> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if time <= first.time {
+            return Some((first.position, first.rotation));
+        }
+        if time >= last.time {
+            return Some((last.position, last.rotation));
+        }
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = (time - a.time) / (b.time - a.time);
+        let position = a.position + (b.position - a.position) * t;
+        Some((position, a.rotation.slerp(&b.rotation, t)))
+    }
+
+    /// Object to world transform at `time`.
+    pub fn pose_at(&self, time: f64) -> Option<Mat4<f64>> {
+        let (position, rotation) = self.sample(time)?;
+        Some(Mat4::translation(position.x, position.y, position.z) * rotation.to_mat4())
+    }
+
+    /// World to camera transform, what `Camera::with_transform` expects when
+    /// the track describes where the camera is and where it faces.
+    pub fn camera_transform_at(&self, time: f64) -> Option<Mat4<f64>> {
+        self.pose_at(time)?.inverse()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{math::point_vec::V3D, tracer::camera::Camera};
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn track() -> Track {
+        let turn = Quat::from_axis_angle([0.0, 1.0, 0.0], FRAC_PI_2);
+        Track::new(vec![
+            Keyframe::new(2.0, Point::new(10.0, 0.0, 0.0), turn),
+            Keyframe::new(0.0, Point::new(0.0, 0.0, 0.0), Quat::identity()),
+        ])
+    }
+
+    #[test]
+    fn clamps_outside_the_keyframes() {
+        let track = track();
+        assert_eq!(track.sample(-1.0).unwrap().0, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(track.sample(5.0).unwrap().0, Point::new(10.0, 0.0, 0.0));
+        assert!(Track::default().sample(0.0).is_none());
+    }
+
+    #[test]
+    fn interpolates_between_keyframes() {
+        let (position, rotation) = track().sample(1.0).unwrap();
+        assert_eq!(position, Point::new(5.0, 0.0, 0.0));
+        let expected = Quat::from_axis_angle([0.0, 1.0, 0.0], FRAC_PI_4);
+        assert!((rotation.dot(&expected).abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn push_keeps_keyframes_sorted() {
+        let mut track = track();
+        track.push(Keyframe::new(
+            1.0,
+            Point::new(0.0, 4.0, 0.0),
+            Quat::identity(),
+        ));
+        assert_eq!(track.sample(1.0).unwrap().0, Point::new(0.0, 4.0, 0.0));
+        assert_eq!(track.sample(0.5).unwrap().0, Point::new(0.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn camera_follows_the_track() {
+        let transform = track().camera_transform_at(0.0).unwrap();
+        let camera = Camera::new(11, 11, FRAC_PI_2).with_transform(transform);
+        let ray = camera.ray_for_pixel(5, 5);
+        assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(ray.direction, V3D::new(0.0, 0.0, -1.0));
+        let transform = track().camera_transform_at(2.0).unwrap();
+        let camera = Camera::new(11, 11, FRAC_PI_2).with_transform(transform);
+        let ray = camera.ray_for_pixel(5, 5);
+        assert_eq!(ray.origin, Point::new(10.0, 0.0, 0.0));
+        assert_eq!(
+            ray.direction,
+            Mat4::rotation_y(FRAC_PI_2) * V3D::new(0.0, 0.0, -1.0)
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use super::{intersection::EPSILON, ray::Ray};
+use crate::math::{matrices::Mat4, point_vec::Point, transformable::Transformable};
+
+/// Axis aligned bounding box, used to skip whole groups of objects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Default for BoundingBox {
+    fn default() -> Self {
+        BoundingBox::empty()
+    }
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    pub fn empty() -> BoundingBox {
+        BoundingBox::new(
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+
+    pub fn infinite() -> BoundingBox {
+        BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [
+            self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z,
+        ]
+        .iter()
+        .all(|v| v.is_finite())
+    }
+
+    pub fn add_point(&mut self, p: &Point) {
+        self.min = Point::new(
+            self.min.x.min(p.x),
+            self.min.y.min(p.y),
+            self.min.z.min(p.z),
+        );
+        self.max = Point::new(
+            self.max.x.max(p.x),
+            self.max.y.max(p.y),
+            self.max.z.max(p.z),
+        );
+    }
+
+    pub fn merge(&self, other: &BoundingBox) -> BoundingBox {
+        let mut merged = *self;
+        if !other.is_empty() {
+            merged.add_point(&other.min);
+            merged.add_point(&other.max);
+        }
+        merged
+    }
+
+    pub fn contains_point(&self, p: &Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn corners(&self) -> [Point; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Point::new(a.x, a.y, a.z),
+            Point::new(a.x, a.y, b.z),
+            Point::new(a.x, b.y, a.z),
+            Point::new(a.x, b.y, b.z),
+            Point::new(b.x, a.y, a.z),
+            Point::new(b.x, a.y, b.z),
+            Point::new(b.x, b.y, a.z),
+            Point::new(b.x, b.y, b.z),
+        ]
+    }
+
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+        tmin <= tmax && tmax >= 0.0
+    }
+}
+
+impl Transformable for BoundingBox {
+    fn transform(&self, m: &Mat4<f64>) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        //multiplying an infinite extent by a zero entry of the matrix gives NaN
+        if !self.is_finite() {
+            return BoundingBox::infinite();
+        }
+        let mut transformed = BoundingBox::empty();
+        for corner in self.corners() {
+            transformed.add_point(&m.mul_tuple(&corner));
+        }
+        transformed
+    }
+}
+
+/// Slab test for one axis, returns where the ray enters and leaves `min..max`.
+pub(crate) fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    let tmin_numerator = min - origin;
+    let tmax_numerator = max - origin;
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::point_vec::V3D;
+
+    fn unit_box() -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn merge_boxes() {
+        let a = BoundingBox::new(Point::new(-5.0, -2.0, 0.0), Point::new(7.0, 4.0, 4.0));
+        let b = BoundingBox::new(Point::new(8.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+        let merged = a.merge(&b);
+        assert_eq!(merged.min, Point::new(-5.0, -7.0, -2.0));
+        assert_eq!(merged.max, Point::new(14.0, 4.0, 8.0));
+        assert_eq!(BoundingBox::empty().merge(&a), a);
+    }
+
+    #[test]
+    fn transformed_box_contains_rotated_corners() {
+        let rotated = unit_box().transform(
+            &(Mat4::rotation_x(std::f64::consts::FRAC_PI_4)
+                * Mat4::rotation_y(std::f64::consts::FRAC_PI_4)),
+        );
+        assert!((rotated.max.x - std::f64::consts::SQRT_2).abs() < 1e-9);
+        assert!((rotated.max.y - (1.0 + std::f64::consts::FRAC_1_SQRT_2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ray_against_box() {
+        let b = unit_box();
+        let hit = Ray::new(Point::new(5.0, 0.5, 0.0), V3D::new(-1.0, 0.0, 0.0));
+        let miss = Ray::new(Point::new(-2.0, 0.0, 2.0), V3D::new(0.0, 0.0, -1.0));
+        let behind = Ray::new(Point::new(5.0, 0.0, 0.0), V3D::new(1.0, 0.0, 0.0));
+        assert!(b.intersects(&hit));
+        assert!(!b.intersects(&miss));
+        assert!(!b.intersects(&behind));
+        assert!(!BoundingBox::empty().intersects(&hit));
+    }
+
+    #[test]
+    fn infinite_box_stays_infinite() {
+        let b = BoundingBox::infinite().transform(&Mat4::rotation_x(1.0));
+        assert!(!b.is_finite());
+        assert!(b.contains_point(&Point::new(1e9, -1e9, 0.0)));
+    }
+}
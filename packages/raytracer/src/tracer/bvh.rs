@@ -0,0 +1,361 @@
+use super::{mesh::Mesh, ray::Ray};
+use crate::math::point_vec::Point;
+
+/// Hits closer than this are the surface a ray starts from.
+pub const EPSILON: f32 = 1e-4;
+/// Leaves hold at most this many primitives.
+const MAX_LEAF: usize = 4;
+
+pub(crate) type Vec3 = [f32; 3];
+
+pub(crate) fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn vec3(p: &Point) -> Vec3 {
+    [p.x as f32, p.y as f32, p.z as f32]
+}
+
+/// World space geometry the BVH is built over, in single precision so
+/// packets can test 4 or 8 rays per instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Primitive {
+    Sphere { center: Vec3, radius: f32 },
+    Triangle { p1: Vec3, e1: Vec3, e2: Vec3 },
+}
+
+impl Primitive {
+    pub fn sphere(center: &Point, radius: f64) -> Primitive {
+        Primitive::Sphere {
+            center: vec3(center),
+            radius: radius as f32,
+        }
+    }
+
+    pub fn triangle(p1: &Point, p2: &Point, p3: &Point) -> Primitive {
+        let p1 = vec3(p1);
+        Primitive::Triangle {
+            p1,
+            e1: sub(vec3(p2), p1),
+            e2: sub(vec3(p3), p1),
+        }
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        match *self {
+            Primitive::Sphere { center, radius } => {
+                (center.map(|c| c - radius), center.map(|c| c + radius))
+            }
+            Primitive::Triangle { p1, e1, e2 } => {
+                let mut min = p1;
+                let mut max = p1;
+                for edge in [e1, e2] {
+                    for axis in 0..3 {
+                        min[axis] = min[axis].min(p1[axis] + edge[axis]);
+                        max[axis] = max[axis].max(p1[axis] + edge[axis]);
+                    }
+                }
+                (min, max)
+            }
+        }
+    }
+
+    fn centroid(&self) -> Vec3 {
+        let (min, max) = self.bounds();
+        [0, 1, 2].map(|axis| (min[axis] + max[axis]) * 0.5)
+    }
+
+    /// Distance to the closest hit beyond [`EPSILON`].
+    pub fn intersect(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        match *self {
+            Primitive::Sphere { center, radius } => {
+                let oc = sub(origin, center);
+                let a = dot(direction, direction);
+                let b = dot(oc, direction);
+                let c = dot(oc, oc) - radius * radius;
+                let discriminant = b * b - a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                [(-b - root) / a, (-b + root) / a]
+                    .into_iter()
+                    .find(|t| *t > EPSILON)
+            }
+            Primitive::Triangle { p1, e1, e2 } => {
+                let dir_cross_e2 = cross(direction, e2);
+                let det = dot(e1, dir_cross_e2);
+                if det.abs() < 1e-8 {
+                    return None;
+                }
+                let f = 1.0 / det;
+                let p1_to_origin = sub(origin, p1);
+                let u = f * dot(p1_to_origin, dir_cross_e2);
+                let origin_cross_e1 = cross(p1_to_origin, e1);
+                let v = f * dot(direction, origin_cross_e1);
+                let t = f * dot(e2, origin_cross_e1);
+                (u >= 0.0 && v >= 0.0 && u + v <= 1.0 && t > EPSILON).then_some(t)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub t: f32,
+    /// Index of the primitive in the list the BVH was built from.
+    pub primitive: usize,
+}
+
+/// Node of the flattened tree. Inner nodes have their left child right after
+/// them and `offset` points at the right one; leaves hold `count` primitives
+/// starting at `offset`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Node {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub offset: u32,
+    pub count: u32,
+}
+
+impl Node {
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+/// Bounding volume hierarchy over spheres and triangles, split at the median
+/// of the widest axis. Built once and traversed by single rays
+/// ([`Bvh::intersect`]) or ray packets ([`Bvh::intersect_packet`]).
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    pub(crate) nodes: Vec<Node>,
+    /// Primitives in leaf order, with their index in the original list.
+    pub(crate) primitives: Vec<(Primitive, usize)>,
+}
+
+impl Bvh {
+    pub fn new(primitives: &[Primitive]) -> Bvh {
+        let mut bvh = Bvh {
+            nodes: vec![],
+            primitives: primitives.iter().copied().zip(0..).collect(),
+        };
+        if !primitives.is_empty() {
+            bvh.build(0, primitives.len());
+        }
+        bvh
+    }
+
+    pub fn from_mesh(mesh: &Mesh) -> Bvh {
+        let triangles: Vec<Primitive> = mesh
+            .triangle_indices()
+            .map(|[a, b, c]| {
+                Primitive::triangle(
+                    &mesh.positions[a.position],
+                    &mesh.positions[b.position],
+                    &mesh.positions[c.position],
+                )
+            })
+            .collect();
+        Bvh::new(&triangles)
+    }
+
+    pub fn len(&self) -> usize {
+        self.primitives.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.primitives.is_empty()
+    }
+
+    /// Adds the node for `primitives[start..end]` and its subtree, returns
+    /// its index.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        let mut centroid_min = [f32::INFINITY; 3];
+        let mut centroid_max = [f32::NEG_INFINITY; 3];
+        for (primitive, _) in &self.primitives[start..end] {
+            let (low, high) = primitive.bounds();
+            let centroid = primitive.centroid();
+            for axis in 0..3 {
+                min[axis] = min[axis].min(low[axis]);
+                max[axis] = max[axis].max(high[axis]);
+                centroid_min[axis] = centroid_min[axis].min(centroid[axis]);
+                centroid_max[axis] = centroid_max[axis].max(centroid[axis]);
+            }
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            offset: start as u32,
+            count: (end - start) as u32,
+        });
+        if end - start <= MAX_LEAF {
+            return index;
+        }
+        let axis = (0..3)
+            .max_by(|a, b| {
+                (centroid_max[*a] - centroid_min[*a])
+                    .total_cmp(&(centroid_max[*b] - centroid_min[*b]))
+            })
+            .unwrap_or(0);
+        let middle = (start + end) / 2;
+        self.primitives[start..end].select_nth_unstable_by(middle - start, |a, b| {
+            a.0.centroid()[axis].total_cmp(&b.0.centroid()[axis])
+        });
+        self.build(start, middle);
+        let right = self.build(middle, end);
+        self.nodes[index].offset = right as u32;
+        self.nodes[index].count = 0;
+        index
+    }
+
+    /// Closest hit along a single ray.
+    pub fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let origin = vec3(&ray.origin);
+        let direction = [ray.direction.x, ray.direction.y, ray.direction.z].map(|v| v as f32);
+        let inverse = direction.map(|d| 1.0 / d);
+        let mut closest: Option<Hit> = None;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                continue;
+            };
+            let limit = closest.map_or(f32::INFINITY, |hit| hit.t);
+            let (mut near, mut far) = (0.0f32, limit);
+            for axis in 0..3 {
+                let t1 = (node.min[axis] - origin[axis]) * inverse[axis];
+                let t2 = (node.max[axis] - origin[axis]) * inverse[axis];
+                near = near.max(t1.min(t2));
+                far = far.min(t1.max(t2));
+            }
+            if near > far {
+                continue;
+            }
+            if !node.is_leaf() {
+                stack.push(node.offset as usize);
+                stack.push(index + 1);
+                continue;
+            }
+            let leaf = node.offset as usize..(node.offset + node.count) as usize;
+            for (primitive, id) in &self.primitives[leaf] {
+                if let Some(t) = primitive.intersect(origin, direction) {
+                    if closest.is_none_or(|hit| t < hit.t) {
+                        closest = Some(Hit { t, primitive: *id });
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{
+        math::point_vec::{TupleLike, V3D},
+        tracer::sampling::Rng,
+    };
+
+    /// Icosphere with a few spheres floating around it.
+    pub(crate) fn scene() -> Vec<Primitive> {
+        let mesh = Mesh::icosphere(2);
+        let mut primitives: Vec<Primitive> = mesh
+            .triangle_indices()
+            .map(|[a, b, c]| {
+                Primitive::triangle(
+                    &mesh.positions[a.position],
+                    &mesh.positions[b.position],
+                    &mesh.positions[c.position],
+                )
+            })
+            .collect();
+        for i in 0..8 {
+            let angle = i as f64 * std::f64::consts::PI / 4.0;
+            primitives.push(Primitive::sphere(
+                &Point::new(angle.cos() * 2.0, angle.sin() * 2.0, 0.5),
+                0.3,
+            ));
+        }
+        primitives
+    }
+
+    pub(crate) fn random_rays(count: usize) -> Vec<Ray> {
+        let mut rng = Rng::new(7);
+        (0..count)
+            .map(|_| {
+                let target = V3D::new(
+                    rng.next_f64() * 5.0 - 2.5,
+                    rng.next_f64() * 5.0 - 2.5,
+                    rng.next_f64() * 2.0 - 1.0,
+                );
+                let origin = Point::new(0.0, 0.0, -6.0);
+                Ray::new(origin, (target - V3D::from(origin)).normalize())
+            })
+            .collect()
+    }
+
+    fn brute_force(primitives: &[Primitive], ray: &Ray) -> Option<Hit> {
+        let origin = vec3(&ray.origin);
+        let direction = [ray.direction.x, ray.direction.y, ray.direction.z].map(|v| v as f32);
+        primitives
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| {
+                p.intersect(origin, direction)
+                    .map(|t| Hit { t, primitive: i })
+            })
+            .min_by(|a, b| a.t.total_cmp(&b.t))
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let primitives = scene();
+        let bvh = Bvh::new(&primitives);
+        assert_eq!(bvh.len(), primitives.len());
+        let mut hits = 0;
+        for ray in random_rays(500) {
+            let expected = brute_force(&primitives, &ray);
+            assert_eq!(bvh.intersect(&ray), expected);
+            hits += expected.is_some() as usize;
+        }
+        assert!(hits > 100 && hits < 500);
+    }
+
+    #[test]
+    fn leaves_are_small() {
+        let bvh = Bvh::new(&scene());
+        assert!(bvh
+            .nodes
+            .iter()
+            .filter(|n| n.is_leaf())
+            .all(|n| n.count as usize <= MAX_LEAF));
+        assert!(Bvh::new(&[]).intersect(&random_rays(1)[0]).is_none());
+    }
+
+    #[test]
+    fn spheres_are_hit_from_inside() {
+        let sphere = Primitive::sphere(&Point::new(0.0, 0.0, 0.0), 1.0);
+        assert_eq!(sphere.intersect([0.0; 3], [0.0, 0.0, 1.0]), Some(1.0));
+        assert_eq!(
+            sphere.intersect([0.0, 0.0, -5.0], [0.0, 0.0, 1.0]),
+            Some(4.0)
+        );
+        assert_eq!(sphere.intersect([0.0, 2.0, -5.0], [0.0, 0.0, 1.0]), None);
+    }
+}
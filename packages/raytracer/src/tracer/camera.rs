@@ -0,0 +1,199 @@
+use super::{ray::Ray, sampling::concentric_disk};
+use crate::math::{
+    matrices::Mat4,
+    point_vec::{Point, TupleLike},
+};
+
+/// Thin lens for depth of field. Points at `focal_distance` (measured along
+/// the view direction) are sharp, everything else blurs more the wider the
+/// aperture is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lens {
+    /// Diameter of the lens in world units, 0 is a pinhole.
+    pub aperture: f64,
+    pub focal_distance: f64,
+    /// Seeds the lens samples, the same seed gives the same blur.
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub hsize: usize,
+    pub vsize: usize,
+    pub field_of_view: f64,
+    transform: Mat4<f64>,
+    inverse: Mat4<f64>,
+    half_width: f64,
+    half_height: f64,
+    pixel_size: f64,
+    lens: Option<Lens>,
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Camera {
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f64 / vsize as f64;
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+        Camera {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Mat4::identity(),
+            inverse: Mat4::identity(),
+            half_width,
+            half_height,
+            pixel_size: half_width * 2.0 / hsize as f64,
+            lens: None,
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Mat4<f64>) -> Camera {
+        self.inverse = transform
+            .inverse()
+            .expect("camera transforms must be invertible");
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_lens(mut self, lens: Lens) -> Camera {
+        self.lens = (lens.aperture > 0.0).then_some(lens);
+        self
+    }
+
+    /// The thin lens, `None` for a pinhole camera.
+    pub fn lens(&self) -> Option<&Lens> {
+        self.lens.as_ref()
+    }
+
+    pub fn transform_matrix(&self) -> &Mat4<f64> {
+        &self.transform
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        self.pixel_size
+    }
+
+    /// Ray through the center of the pixel.
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
+        self.ray_through(x as f64 + 0.5, y as f64 + 0.5)
+    }
+
+    /// Ray through a point of the canvas given in (fractional) pixels.
+    pub fn ray_through(&self, px: f64, py: f64) -> Ray {
+        let world_x = self.half_width - px * self.pixel_size;
+        let world_y = self.half_height - py * self.pixel_size;
+        let pixel = self.inverse.mul_tuple(&Point::new(world_x, world_y, -1.0));
+        let origin = self.inverse.mul_tuple(&Point::new(0.0, 0.0, 0.0));
+        Ray::new(origin, (pixel - origin).normalize())
+    }
+
+    /// Like [`Camera::ray_through`] but leaving from the point of the lens
+    /// picked by `u1, u2` (uniform in `0..1`), towards the spot of the focal
+    /// plane that the pinhole ray would hit. Pinhole cameras ignore `u1, u2`.
+    pub fn ray_through_lens(&self, px: f64, py: f64, u1: f64, u2: f64) -> Ray {
+        let Some(lens) = &self.lens else {
+            return self.ray_through(px, py);
+        };
+        let world_x = self.half_width - px * self.pixel_size;
+        let world_y = self.half_height - py * self.pixel_size;
+        //the canvas sits at z = -1, so scaling it by the distance lands on the focal plane
+        let d = lens.focal_distance;
+        let focus = self
+            .inverse
+            .mul_tuple(&Point::new(world_x * d, world_y * d, -d));
+        let (lx, ly) = concentric_disk(u1, u2);
+        let radius = lens.aperture / 2.0;
+        let origin = self
+            .inverse
+            .mul_tuple(&Point::new(lx * radius, ly * radius, 0.0));
+        Ray::new(origin, (focus - origin).normalize())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::point_vec::V3D;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    #[test]
+    fn pixel_size_for_horizontal_canvas() {
+        let camera = Camera::new(200, 125, FRAC_PI_2);
+        assert!((camera.pixel_size() - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ray_through_center() {
+        let camera = Camera::new(201, 101, FRAC_PI_2);
+        let ray = camera.ray_for_pixel(100, 50);
+        assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(ray.direction, V3D::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn ray_through_corner() {
+        let camera = Camera::new(201, 101, FRAC_PI_2);
+        let ray = camera.ray_for_pixel(0, 0);
+        let expected = V3D::new(0.66519, 0.33259, -0.66851);
+        assert!((ray.direction - expected).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn ray_with_transformed_camera() {
+        let camera = Camera::new(201, 101, FRAC_PI_2)
+            .with_transform(Mat4::rotation_y(-FRAC_PI_4) * Mat4::translation(0.0, -2.0, 5.0));
+        let ray = camera.ray_for_pixel(100, 50);
+        let half = 2f64.sqrt() / 2.0;
+        assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
+        assert_eq!(ray.direction, V3D::new(half, 0.0, -half));
+    }
+
+    fn lens() -> Lens {
+        Lens {
+            aperture: 0.5,
+            focal_distance: 4.0,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn lens_rays_meet_on_the_focal_plane() {
+        let camera = Camera::new(201, 101, FRAC_PI_2)
+            .with_transform(Mat4::translation(0.0, 0.0, -5.0))
+            .with_lens(lens());
+        let pinhole = camera.ray_through(30.0, 20.0);
+        let t = 4.0 / -pinhole.direction.z;
+        let focus = pinhole.position(t);
+        for (u1, u2) in [(0.1, 0.2), (0.9, 0.5), (0.5, 0.99)] {
+            let ray = camera.ray_through_lens(30.0, 20.0, u1, u2);
+            assert!(ray.origin != pinhole.origin);
+            let t = (focus.z - ray.origin.z) / ray.direction.z;
+            assert_eq!(ray.position(t), focus);
+        }
+    }
+
+    #[test]
+    fn lens_samples_stay_on_the_aperture() {
+        let camera = Camera::new(11, 11, FRAC_PI_2).with_lens(lens());
+        let ray = camera.ray_through_lens(5.5, 5.5, 1.0, 0.5);
+        assert_eq!(ray.origin, Point::new(0.25, 0.0, 0.0));
+        assert_eq!(ray.origin.z, 0.0);
+    }
+
+    #[test]
+    fn closed_aperture_is_a_pinhole() {
+        let camera = Camera::new(11, 11, FRAC_PI_2).with_lens(Lens {
+            aperture: 0.0,
+            ..lens()
+        });
+        assert!(camera.lens().is_none());
+        let ray = camera.ray_through_lens(2.0, 3.0, 0.9, 0.1);
+        let pinhole = camera.ray_through(2.0, 3.0);
+        assert_eq!(ray.origin, pinhole.origin);
+        assert_eq!(ray.direction, pinhole.direction);
+    }
+}
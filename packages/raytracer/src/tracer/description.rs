@@ -0,0 +1,752 @@
+use super::{
+    camera::{Camera, Lens},
+    environment::Environment,
+    light::PointLight,
+    material::Material,
+    normal_map::NormalPerturbation,
+    path::PathSettings,
+    render::RenderSettings,
+    scene::{RenderMode, Scene},
+    shapes::{CsgOperation, Object, Triangle},
+    texture::Texture,
+    tone_mapping::{ToneMapping, ToneOperator},
+    world::World,
+};
+use crate::math::{
+    color::Color,
+    matrices::Mat4,
+    point_vec::{Point, TupleLike, V3D},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Why a description can't be turned into a scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneError {
+    /// An object transform can't be inverted, e.g. a scale by 0.
+    SingularTransform,
+    /// The camera looks at its own position or `up` is along the view.
+    SingularCamera,
+    /// The aperture is negative or the focal distance isn't positive.
+    BadLens,
+    /// A texture is empty or its pixels don't fill `width` x `height`.
+    TextureSize { width: usize, height: usize },
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::SingularTransform => write!(f, "object transform is not invertible"),
+            SceneError::SingularCamera => write!(f, "camera transform is not invertible"),
+            SceneError::BadLens => write!(f, "camera lens has a bad aperture or focal distance"),
+            SceneError::TextureSize { width, height } => {
+                write!(f, "texture pixels don't fit a {}x{} texture", width, height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// Plain data description of a scene, this is what js hands to the wasm api.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneInfo {
+    pub width: usize,
+    pub height: usize,
+    #[serde(default = "default_samples")]
+    pub samples: u32,
+    pub camera: CameraInfo,
+    #[serde(default)]
+    pub lights: Vec<LightInfo>,
+    #[serde(default)]
+    pub objects: Vec<ObjectInfo>,
+    #[serde(default)]
+    pub mode: ModeInfo,
+    /// Defaults to clamping for Whitted renders and to ACES for path tracing.
+    #[serde(default)]
+    pub tone_mapping: Option<ToneOperator>,
+    #[serde(default = "default_exposure")]
+    pub exposure: f64,
+    #[serde(default)]
+    pub environment: Option<EnvironmentInfo>,
+}
+
+/// HDR images are given as linear rgb floats, three per pixel, row by row
+/// from the top.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EnvironmentInfo {
+    Color {
+        color: [f64; 3],
+    },
+    Sky {
+        zenith: Option<[f64; 3]>,
+        horizon: Option<[f64; 3]>,
+        ground: Option<[f64; 3]>,
+    },
+    Equirectangular {
+        width: usize,
+        height: usize,
+        pixels: Vec<f64>,
+    },
+    CubeMap {
+        size: usize,
+        /// `+x, -x, +y, -y, +z, -z`
+        faces: [Vec<f64>; 6],
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModeInfo {
+    #[default]
+    Whitted,
+    Path {
+        max_bounces: Option<usize>,
+        seed: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraInfo {
+    pub from: [f64; 3],
+    pub to: [f64; 3],
+    #[serde(default = "default_up")]
+    pub up: [f64; 3],
+    /// Field of view in radians.
+    pub fov: f64,
+    /// Lens diameter, leaving it out (or 0) gives a pinhole camera.
+    #[serde(default)]
+    pub aperture: f64,
+    /// Distance that is in focus, defaults to the distance from `from` to `to`.
+    #[serde(default)]
+    pub focal_distance: Option<f64>,
+    #[serde(default)]
+    pub lens_seed: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightInfo {
+    pub position: [f64; 3],
+    #[serde(default = "default_intensity")]
+    pub intensity: [f64; 3],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectInfo {
+    #[serde(flatten)]
+    pub shape: ShapeInfo,
+    /// Applied in order, the first transform of the list is applied first.
+    #[serde(default)]
+    pub transform: Vec<TransformInfo>,
+    #[serde(default)]
+    pub material: MaterialInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum ShapeInfo {
+    Sphere,
+    Plane,
+    Cube,
+    Cylinder {
+        #[serde(default = "default_minimum")]
+        minimum: f64,
+        #[serde(default = "default_maximum")]
+        maximum: f64,
+        #[serde(default)]
+        closed: bool,
+    },
+    Triangle {
+        points: [[f64; 3]; 3],
+        #[serde(default)]
+        normals: Option<[[f64; 3]; 3]>,
+        #[serde(default)]
+        uvs: Option<[[f64; 2]; 3]>,
+    },
+    Group {
+        children: Vec<ObjectInfo>,
+    },
+    Csg {
+        operation: CsgOperation,
+        left: Box<ObjectInfo>,
+        right: Box<ObjectInfo>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformInfo {
+    Translate([f64; 3]),
+    Scale([f64; 3]),
+    RotateX(f64),
+    RotateY(f64),
+    RotateZ(f64),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaterialInfo {
+    pub color: Option<[f64; 3]>,
+    pub ambient: Option<f64>,
+    pub diffuse: Option<f64>,
+    pub specular: Option<f64>,
+    pub shininess: Option<f64>,
+    pub reflective: Option<f64>,
+    pub transparency: Option<f64>,
+    pub refractive_index: Option<f64>,
+    pub emissive: Option<[f64; 3]>,
+    pub normal_map: Option<NormalMapInfo>,
+    pub bump: Option<BumpInfo>,
+}
+
+/// Normal map pixels, in the same `u32` layout as the canvas buffers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalMapInfo {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+    #[serde(default = "default_strength")]
+    pub strength: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BumpInfo {
+    pub pattern: BumpPattern,
+    #[serde(default = "default_frequency")]
+    pub frequency: f64,
+    #[serde(default = "default_bump_scale")]
+    pub scale: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BumpPattern {
+    /// Parallel waves running along x.
+    Waves,
+    /// Concentric rings around the y axis.
+    Ripples,
+}
+
+fn default_samples() -> u32 {
+    1
+}
+
+fn default_minimum() -> f64 {
+    f64::NEG_INFINITY
+}
+
+fn default_maximum() -> f64 {
+    f64::INFINITY
+}
+
+fn default_strength() -> f64 {
+    1.0
+}
+
+fn default_frequency() -> f64 {
+    10.0
+}
+
+fn default_bump_scale() -> f64 {
+    0.1
+}
+
+fn default_exposure() -> f64 {
+    1.0
+}
+
+fn default_up() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_intensity() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn color(value: &[f64; 3]) -> Color {
+    Color::new(value[0], value[1], value[2])
+}
+
+impl SceneInfo {
+    pub fn to_scene(&self) -> Result<Scene, SceneError> {
+        let mut world = World::new();
+        for light in &self.lights {
+            world.add_light(PointLight::new(
+                Point::from(&light.position[..]),
+                color(&light.intensity),
+            ));
+        }
+        for object in &self.objects {
+            world.add(object.to_object()?);
+        }
+        if let Some(environment) = &self.environment {
+            world.environment = environment.to_environment()?;
+        }
+        let camera = self.camera.to_camera(self.width, self.height)?;
+        Ok(Scene::new(world, camera).with_mode(self.render_mode()))
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        match self.mode {
+            ModeInfo::Whitted => RenderMode::Whitted,
+            ModeInfo::Path { max_bounces, seed } => {
+                let default = PathSettings::default();
+                RenderMode::Path(PathSettings {
+                    max_bounces: max_bounces.unwrap_or(default.max_bounces),
+                    seed: seed.map(u64::from).unwrap_or(default.seed),
+                    ..default
+                })
+            }
+        }
+    }
+
+    pub fn render_settings(&self) -> RenderSettings {
+        //path traced radiance is linear, whitted colors are used as they are
+        let (default_operator, gamma) = match self.mode {
+            ModeInfo::Whitted => (ToneOperator::Clamp, 1.0),
+            ModeInfo::Path { .. } => (ToneOperator::Aces, 2.2),
+        };
+        RenderSettings {
+            max_samples: self.samples.max(1),
+            tone_mapping: ToneMapping {
+                operator: self.tone_mapping.unwrap_or(default_operator),
+                exposure: self.exposure,
+                gamma,
+            },
+            ..RenderSettings::new(self.width, self.height)
+        }
+    }
+}
+
+impl EnvironmentInfo {
+    pub fn to_environment(&self) -> Result<Environment, SceneError> {
+        Ok(match self {
+            EnvironmentInfo::Color { color: c } => Environment::Uniform(color(c)),
+            EnvironmentInfo::Sky {
+                zenith,
+                horizon,
+                ground,
+            } => {
+                let Environment::Sky {
+                    zenith: default_zenith,
+                    horizon: default_horizon,
+                    ground: default_ground,
+                } = Environment::sky()
+                else {
+                    unreachable!("the default sky is a sky")
+                };
+                Environment::Sky {
+                    zenith: zenith.as_ref().map(color).unwrap_or(default_zenith),
+                    horizon: horizon.as_ref().map(color).unwrap_or(default_horizon),
+                    ground: ground.as_ref().map(color).unwrap_or(default_ground),
+                }
+            }
+            EnvironmentInfo::Equirectangular {
+                width,
+                height,
+                pixels,
+            } => Environment::Equirectangular(hdr_texture(*width, *height, pixels)?.into()),
+            EnvironmentInfo::CubeMap { size, faces } => {
+                let [px, nx, py, ny, pz, nz] =
+                    faces.each_ref().map(|face| hdr_texture(*size, *size, face));
+                Environment::CubeMap([px?, nx?, py?, ny?, pz?, nz?].into())
+            }
+        })
+    }
+}
+
+fn hdr_texture(width: usize, height: usize, pixels: &[f64]) -> Result<Texture, SceneError> {
+    check_texture(width, height, pixels.len(), 3)?;
+    let colors = pixels
+        .chunks_exact(3)
+        .map(|rgb| Color::new(rgb[0], rgb[1], rgb[2]))
+        .collect();
+    Ok(Texture::new(width, height, colors))
+}
+
+impl CameraInfo {
+    pub fn to_camera(&self, width: usize, height: usize) -> Result<Camera, SceneError> {
+        let up = V3D::new(self.up[0], self.up[1], self.up[2]);
+        let from = Point::from(&self.from[..]);
+        let to = Point::from(&self.to[..]);
+        let view = invertible(
+            Mat4::view_transform(&from, &to, &up),
+            SceneError::SingularCamera,
+        )?;
+        let focal_distance = self
+            .focal_distance
+            .unwrap_or_else(|| (to - from).magnitude());
+        if !(self.aperture.is_finite() && self.aperture >= 0.0)
+            || !(focal_distance.is_finite() && focal_distance > 0.0)
+        {
+            return Err(SceneError::BadLens);
+        }
+        Ok(Camera::new(width, height, self.fov)
+            .with_transform(view)
+            .with_lens(Lens {
+                aperture: self.aperture,
+                focal_distance,
+                seed: u64::from(self.lens_seed),
+            }))
+    }
+}
+
+impl ObjectInfo {
+    pub fn to_object(&self) -> Result<Object, SceneError> {
+        let transform = invertible(transform_of(&self.transform), SceneError::SingularTransform)?;
+        let object = match &self.shape {
+            ShapeInfo::Sphere => Object::sphere(),
+            ShapeInfo::Plane => Object::plane(),
+            ShapeInfo::Cube => Object::cube(),
+            ShapeInfo::Cylinder {
+                minimum,
+                maximum,
+                closed,
+            } => Object::cylinder(*minimum, *maximum, *closed),
+            ShapeInfo::Triangle {
+                points,
+                normals,
+                uvs,
+            } => {
+                let [p1, p2, p3] = points.map(|p| Point::from(&p[..]));
+                let mut triangle = Triangle::new(p1, p2, p3);
+                if let Some(normals) = normals {
+                    triangle = triangle.with_normals(normals.map(|[x, y, z]| V3D::new(x, y, z)));
+                }
+                if let Some(uvs) = uvs {
+                    triangle = triangle.with_uvs(uvs.map(|[u, v]| (u, v)));
+                }
+                Object::new(triangle)
+            }
+            //children keep their own materials, so only primitives take one here
+            ShapeInfo::Group { children } => {
+                let children = children
+                    .iter()
+                    .map(ObjectInfo::to_object)
+                    .collect::<Result<_, _>>()?;
+                return Ok(Object::group(children).with_transform(transform));
+            }
+            ShapeInfo::Csg {
+                operation,
+                left,
+                right,
+            } => {
+                let shape = Object::csg(*operation, left.to_object()?, right.to_object()?);
+                return Ok(shape.with_transform(transform));
+            }
+        };
+        Ok(object
+            .with_material(self.material.to_material()?)
+            .with_transform(transform))
+    }
+}
+
+/// `matrix` when it has an inverse, `error` otherwise (NaNs included, a
+/// camera looking at its own position gives those).
+fn invertible(matrix: Mat4<f64>, error: SceneError) -> Result<Mat4<f64>, SceneError> {
+    if matrix.determinant().is_normal() {
+        Ok(matrix)
+    } else {
+        Err(error)
+    }
+}
+
+pub fn transform_of(transforms: &[TransformInfo]) -> Mat4<f64> {
+    transforms.iter().fold(Mat4::identity(), |acc, transform| {
+        let m = match *transform {
+            TransformInfo::Translate([x, y, z]) => Mat4::translation(x, y, z),
+            TransformInfo::Scale([x, y, z]) => Mat4::scaling(x, y, z),
+            TransformInfo::RotateX(r) => Mat4::rotation_x(r),
+            TransformInfo::RotateY(r) => Mat4::rotation_y(r),
+            TransformInfo::RotateZ(r) => Mat4::rotation_z(r),
+        };
+        m * acc
+    })
+}
+
+impl MaterialInfo {
+    pub fn to_material(&self) -> Result<Material, SceneError> {
+        let default = Material::default();
+        Ok(Material {
+            color: self.color.as_ref().map(color).unwrap_or(default.color),
+            ambient: self.ambient.unwrap_or(default.ambient),
+            diffuse: self.diffuse.unwrap_or(default.diffuse),
+            specular: self.specular.unwrap_or(default.specular),
+            shininess: self.shininess.unwrap_or(default.shininess),
+            reflective: self.reflective.unwrap_or(default.reflective),
+            transparency: self.transparency.unwrap_or(default.transparency),
+            refractive_index: self.refractive_index.unwrap_or(default.refractive_index),
+            emissive: self
+                .emissive
+                .as_ref()
+                .map(color)
+                .unwrap_or(default.emissive),
+            //a normal map wins over a bump function when both are given
+            normal_perturbation: match (&self.normal_map, &self.bump) {
+                (Some(map), _) => Some(map.to_perturbation()?),
+                (None, Some(bump)) => Some(bump.to_perturbation()),
+                (None, None) => None,
+            },
+        })
+    }
+}
+
+impl NormalMapInfo {
+    pub fn to_perturbation(&self) -> Result<NormalPerturbation, SceneError> {
+        check_texture(self.width, self.height, self.pixels.len(), 1)?;
+        Ok(NormalPerturbation::NormalMap {
+            texture: Texture::from_u32(self.width, self.height, &self.pixels).into(),
+            strength: self.strength,
+        })
+    }
+}
+
+/// `values` must be `per_pixel` values for every pixel of a non empty
+/// `width` x `height` texture.
+fn check_texture(
+    width: usize,
+    height: usize,
+    values: usize,
+    per_pixel: usize,
+) -> Result<(), SceneError> {
+    let expected = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(per_pixel));
+    if width == 0 || height == 0 || expected != Some(values) {
+        return Err(SceneError::TextureSize { width, height });
+    }
+    Ok(())
+}
+
+impl BumpInfo {
+    pub fn to_perturbation(&self) -> NormalPerturbation {
+        let frequency = self.frequency;
+        let amplitude = 1.0 / frequency;
+        match self.pattern {
+            BumpPattern::Waves => NormalPerturbation::bump(
+                move |p: &Point| (p.x * frequency).sin() * amplitude,
+                self.scale,
+            ),
+            BumpPattern::Ripples => NormalPerturbation::bump(
+                move |p: &Point| {
+                    let distance = (p.x * p.x + p.z * p.z).sqrt();
+                    (distance * frequency).sin() * amplitude
+                },
+                self.scale,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tracer::render::{ProgressiveRender, RenderState};
+
+    const SCENE: &str = r#"{
+        "width": 20,
+        "height": 10,
+        "camera": { "from": [0, 0, -5], "to": [0, 0, 0], "fov": 1.0 },
+        "lights": [{ "position": [-10, 10, -10] }],
+        "objects": [
+            { "shape": "sphere", "material": { "color": [1, 0, 0] } },
+            { "shape": "plane", "transform": [{ "translate": [0, -1, 0] }] }
+        ]
+    }"#;
+
+    const SCENE_CAMERA: &str = r#"{ "from": [0, 0, -5], "to": [0, 0, 0], "fov": 1.0 }"#;
+
+    #[test]
+    fn parses_the_js_shape() {
+        let info: SceneInfo = serde_json::from_str(SCENE).unwrap();
+        assert_eq!(info.samples, 1);
+        assert_eq!(info.lights[0].intensity, [1.0, 1.0, 1.0]);
+        assert!(matches!(info.objects[1].shape, ShapeInfo::Plane));
+        let scene = info.to_scene().unwrap();
+        assert_eq!(scene.world.objects.len(), 2);
+        assert_eq!(
+            scene.world.objects[0].material.color,
+            Color::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn parses_nested_shapes() {
+        let json = r#"{
+            "shape": "group",
+            "transform": [{ "translate": [0, 1, 0] }],
+            "children": [{
+                "shape": "csg",
+                "operation": "difference",
+                "left": { "shape": "sphere", "material": { "color": [0, 0, 1] } },
+                "right": { "shape": "cylinder", "minimum": -2, "maximum": 2, "closed": true }
+            }]
+        }"#;
+        let info: ObjectInfo = serde_json::from_str(json).unwrap();
+        let object = info.to_object().unwrap();
+        let (left, right) = object.children()[0].operands().unwrap();
+        assert_eq!(left.material.color, Color::new(0.0, 0.0, 1.0));
+        assert_eq!(right.bounds().max, Point::new(1.0, 3.0, 1.0));
+    }
+
+    #[test]
+    fn path_mode_defaults() {
+        let mut info: SceneInfo = serde_json::from_str(SCENE).unwrap();
+        assert_eq!(info.render_mode(), RenderMode::Whitted);
+        assert_eq!(info.render_settings().tone_mapping, ToneMapping::default());
+        info.mode = serde_json::from_str(r#"{ "type": "path", "seed": 3 }"#).unwrap();
+        let RenderMode::Path(settings) = info.render_mode() else {
+            panic!("expected path tracing");
+        };
+        assert_eq!(settings.seed, 3);
+        assert_eq!(settings.max_bounces, PathSettings::default().max_bounces);
+        assert_eq!(
+            info.render_settings().tone_mapping.operator,
+            ToneOperator::Aces
+        );
+    }
+
+    #[test]
+    fn parses_bumpy_triangles() {
+        let json = r#"{
+            "shape": "triangle",
+            "points": [[0, 1, 0], [-1, 0, 0], [1, 0, 0]],
+            "uvs": [[0.5, 1], [0, 0], [1, 0]],
+            "material": { "bump": { "pattern": "waves", "scale": 1 } }
+        }"#;
+        let info: ObjectInfo = serde_json::from_str(json).unwrap();
+        let object = info.to_object().unwrap();
+        let flat = V3D::new(0.0, 0.0, -1.0);
+        let n = object.normal_at(&Point::new(0.0, 0.5, 0.0));
+        assert!(n.dot(&flat) < 0.99 && n.dot(&flat) > 0.0);
+        let bump = info.material.bump.unwrap();
+        assert_eq!(bump.pattern, BumpPattern::Waves);
+        assert_eq!(bump.frequency, 10.0);
+    }
+
+    #[test]
+    fn normal_maps_must_fill_their_size() {
+        let json = r#"{ "normal_map": { "width": 2, "height": 2, "pixels": [0, 0, 0] } }"#;
+        let info: MaterialInfo = serde_json::from_str(json).unwrap();
+        let error = Some(SceneError::TextureSize {
+            width: 2,
+            height: 2,
+        });
+        assert_eq!(info.to_material().err(), error);
+        let json = r#"{ "normal_map": { "width": 0, "height": 2, "pixels": [] } }"#;
+        let info: MaterialInfo = serde_json::from_str(json).unwrap();
+        assert!(info.to_material().is_err());
+        let json = r#"{ "normal_map": { "width": 1, "height": 1, "pixels": [8421631] } }"#;
+        let info: MaterialInfo = serde_json::from_str(json).unwrap();
+        assert!(info.to_material().unwrap().normal_perturbation.is_some());
+    }
+
+    #[test]
+    fn lens_focuses_on_the_target_by_default() {
+        let json = r#"{ "from": [0, 0, -5], "to": [0, 0, 0], "fov": 1.0, "aperture": 0.2 }"#;
+        let info: CameraInfo = serde_json::from_str(json).unwrap();
+        let lens = *info.to_camera(10, 10).unwrap().lens().unwrap();
+        assert_eq!(lens.focal_distance, 5.0);
+        assert_eq!(lens.aperture, 0.2);
+        let pinhole: CameraInfo = serde_json::from_str(SCENE_CAMERA).unwrap();
+        assert!(pinhole.to_camera(10, 10).unwrap().lens().is_none());
+    }
+
+    #[test]
+    fn bad_lenses_are_errors() {
+        let json = r#"{ "from": [0, 0, -5], "to": [0, 0, 0], "fov": 1.0, "aperture": 0.2 }"#;
+        let info: CameraInfo = serde_json::from_str(json).unwrap();
+        let lens = |aperture: f64, focal_distance: f64| {
+            let info = CameraInfo {
+                aperture,
+                focal_distance: Some(focal_distance),
+                ..info.clone()
+            };
+            info.to_camera(10, 10).err()
+        };
+        assert_eq!(lens(-0.1, 5.0), Some(SceneError::BadLens));
+        assert_eq!(lens(f64::NAN, 5.0), Some(SceneError::BadLens));
+        assert_eq!(lens(0.2, 0.0), Some(SceneError::BadLens));
+        assert_eq!(lens(0.2, -3.0), Some(SceneError::BadLens));
+        assert_eq!(lens(0.2, f64::INFINITY), Some(SceneError::BadLens));
+        assert_eq!(lens(0.2, 5.0), None);
+    }
+
+    #[test]
+    fn parses_environments() {
+        let mut info: SceneInfo = serde_json::from_str(SCENE).unwrap();
+        assert_eq!(
+            info.to_scene().unwrap().world.environment,
+            Environment::default()
+        );
+        let json = r#"{ "type": "equirectangular", "width": 2, "height": 1, "pixels": [4, 0, 0, 0, 0, 4] }"#;
+        info.environment = Some(serde_json::from_str(json).unwrap());
+        let environment = info.to_scene().unwrap().world.environment;
+        let left = environment.radiance(&V3D::new(-1.0, 0.0, 0.0));
+        assert_eq!(left, Color::new(4.0, 0.0, 0.0));
+        let json = r#"{ "type": "sky", "ground": [0, 1, 0] }"#;
+        let sky: EnvironmentInfo = serde_json::from_str(json).unwrap();
+        let down = sky
+            .to_environment()
+            .unwrap()
+            .radiance(&V3D::new(0.0, -1.0, 0.0));
+        assert_eq!(down, Color::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn hdr_pixels_must_fill_their_size() {
+        let json =
+            r#"{ "type": "equirectangular", "width": 2, "height": 1, "pixels": [4, 0, 0, 0] }"#;
+        let info: EnvironmentInfo = serde_json::from_str(json).unwrap();
+        let error = Some(SceneError::TextureSize {
+            width: 2,
+            height: 1,
+        });
+        assert_eq!(info.to_environment().err(), error);
+        let json = r#"{ "type": "equirectangular", "width": 0, "height": 0, "pixels": [] }"#;
+        let info: EnvironmentInfo = serde_json::from_str(json).unwrap();
+        assert!(info.to_environment().is_err());
+        let json = r#"{ "type": "cube_map", "size": 1, "faces": [[1, 1, 1], [], [], [], [], []] }"#;
+        let info: EnvironmentInfo = serde_json::from_str(json).unwrap();
+        assert!(info.to_environment().is_err());
+    }
+
+    #[test]
+    fn singular_transforms_are_errors() {
+        let json = r#"{ "shape": "group", "children": [
+            { "shape": "sphere", "transform": [{ "scale": [1, 0, 1] }] }
+        ] }"#;
+        let info: ObjectInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.to_object().err(), Some(SceneError::SingularTransform));
+        let json = r#"{ "from": [0, 0, -5], "to": [0, 0, -5], "fov": 1.0 }"#;
+        let camera: CameraInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            camera.to_camera(10, 10).err(),
+            Some(SceneError::SingularCamera)
+        );
+        let json = r#"{ "from": [0, 0, -5], "to": [0, 0, 0], "up": [0, 0, 1], "fov": 1.0 }"#;
+        let camera: CameraInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            camera.to_camera(10, 10).err(),
+            Some(SceneError::SingularCamera)
+        );
+    }
+
+    #[test]
+    fn transforms_apply_in_order() {
+        let m = transform_of(&[
+            TransformInfo::Scale([2.0, 2.0, 2.0]),
+            TransformInfo::Translate([1.0, 0.0, 0.0]),
+        ]);
+        assert_eq!(m * Point::new(1.0, 0.0, 0.0), Point::new(3.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn renders_the_description() {
+        let info: SceneInfo = serde_json::from_str(SCENE).unwrap();
+        let mut render = ProgressiveRender::new(info.to_scene().unwrap(), info.render_settings());
+        assert_eq!(render.render(), RenderState::Done);
+        let center = render.pixel(10, 5).unwrap();
+        assert!(center.r > center.g && center.r > center.b);
+    }
+}
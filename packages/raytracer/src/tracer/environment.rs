@@ -0,0 +1,153 @@
+use super::texture::Texture;
+use crate::math::{
+    color::Color,
+    point_vec::{TupleLike, V3D},
+};
+use std::{f64::consts::PI, sync::Arc};
+
+/// Light arriving from infinitely far away, it is what rays that miss every
+/// object see.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Environment {
+    Uniform(Color),
+    /// Gradient from the horizon up to the zenith, and down to the ground.
+    Sky {
+        zenith: Color,
+        horizon: Color,
+        ground: Color,
+    },
+    /// Latitude/longitude image, `u = 0.5` looks down -z and `v = 1` is straight up.
+    Equirectangular(Arc<Texture>),
+    /// Faces in the usual `+x, -x, +y, -y, +z, -z` order, each one seen
+    /// from the inside of the cube.
+    CubeMap(Arc<[Texture; 6]>),
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment::Uniform(Color::black())
+    }
+}
+
+impl Environment {
+    pub fn sky() -> Environment {
+        Environment::Sky {
+            zenith: Color::new(0.25, 0.45, 0.9),
+            horizon: Color::new(0.9, 0.92, 1.0),
+            ground: Color::new(0.3, 0.27, 0.25),
+        }
+    }
+
+    /// Radiance coming from `direction` (which doesn't need to be normalized).
+    pub fn radiance(&self, direction: &V3D) -> Color {
+        let d = direction.normalize();
+        match self {
+            Environment::Uniform(color) => *color,
+            Environment::Sky {
+                zenith,
+                horizon,
+                ground,
+            } => {
+                let (target, t) = if d.y >= 0.0 {
+                    (zenith, d.y)
+                } else {
+                    (ground, -d.y)
+                };
+                *horizon * (1.0 - t) + *target * t
+            }
+            Environment::Equirectangular(texture) => {
+                let u = 0.5 + d.x.atan2(-d.z) / (2.0 * PI);
+                let v = 0.5 + d.y.clamp(-1.0, 1.0).asin() / PI;
+                texture.sample(u, v)
+            }
+            Environment::CubeMap(faces) => {
+                let (face, s, t, major) = cube_face(&d);
+                let u = (s / major + 1.0) / 2.0;
+                let v = (t / major + 1.0) / 2.0;
+                faces[face].sample(u, v)
+            }
+        }
+    }
+}
+
+/// Face index and the (s, t, major axis) coordinates of the direction on it,
+/// `t` grows upwards in the image.
+fn cube_face(d: &V3D) -> (usize, f64, f64, f64) {
+    let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+    if ax >= ay && ax >= az {
+        if d.x > 0.0 {
+            (0, -d.z, d.y, ax)
+        } else {
+            (1, d.z, d.y, ax)
+        }
+    } else if ay >= az {
+        if d.y > 0.0 {
+            (2, d.x, -d.z, ay)
+        } else {
+            (3, d.x, d.z, ay)
+        }
+    } else if d.z > 0.0 {
+        (4, d.x, d.y, az)
+    } else {
+        (5, -d.x, d.y, az)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sky_blends_towards_the_horizon() {
+        let sky = Environment::sky();
+        let Environment::Sky {
+            zenith,
+            horizon,
+            ground,
+        } = sky.clone()
+        else {
+            unreachable!()
+        };
+        assert_eq!(sky.radiance(&V3D::new(0.0, 2.0, 0.0)), zenith);
+        assert_eq!(sky.radiance(&V3D::new(1.0, 0.0, 0.0)), horizon);
+        assert_eq!(sky.radiance(&V3D::new(0.0, -1.0, 0.0)), ground);
+    }
+
+    #[test]
+    fn equirectangular_lookup() {
+        //left half red, right half blue, so -x is red and +x is blue
+        let (red, blue) = (Color::new(4.0, 0.0, 0.0), Color::new(0.0, 0.0, 4.0));
+        let texture = Texture::new(2, 1, vec![red, blue]);
+        let env = Environment::Equirectangular(Arc::new(texture));
+        assert_eq!(env.radiance(&V3D::new(-1.0, 0.0, 0.0)), red);
+        assert_eq!(env.radiance(&V3D::new(1.0, 0.0, 0.0)), blue);
+    }
+
+    #[test]
+    fn cube_map_picks_the_face() {
+        let faces = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0].map(|i| Texture::solid(Color::new(i, 0.0, 0.0)));
+        let env = Environment::CubeMap(Arc::new(faces));
+        let directions = [
+            V3D::new(1.0, 0.2, 0.1),
+            V3D::new(-1.0, 0.2, 0.1),
+            V3D::new(0.1, 1.0, 0.2),
+            V3D::new(0.1, -1.0, 0.2),
+            V3D::new(0.1, 0.2, 1.0),
+            V3D::new(0.1, 0.2, -1.0),
+        ];
+        for (i, d) in directions.iter().enumerate() {
+            assert_eq!(env.radiance(d).r, i as f64);
+        }
+    }
+
+    #[test]
+    fn cube_faces_are_continuous() {
+        //the bottom row of the +y face touches the top of the +z face
+        let (s, t, major) = {
+            let (_, s, t, major) = cube_face(&V3D::new(0.0, 1.0, 0.999));
+            (s, t, major)
+        };
+        assert!(((s / major + 1.0) / 2.0 - 0.5).abs() < 1e-9);
+        assert!((t / major + 1.0) / 2.0 < 0.01);
+    }
+}
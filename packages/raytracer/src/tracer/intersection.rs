@@ -0,0 +1,195 @@
+use super::{ray::Ray, shapes::Object};
+use crate::math::point_vec::{Point, TupleLike, V3D};
+
+/// How far hit points are pushed off the surface to avoid self intersection (acne).
+pub const EPSILON: f64 = 1e-5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Intersection<'a> {
+    pub t: f64,
+    pub object: &'a Object,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(t: f64, object: &'a Object) -> Intersection<'a> {
+        Intersection { t, object }
+    }
+
+    /// `xs` are all the intersections of the ray sorted by `t`, they are used
+    /// to find out which materials the ray is leaving and entering.
+    pub fn prepare(&self, ray: &Ray, xs: &[Intersection<'a>]) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at(&point);
+        let inside = normalv.dot(&eyev) < 0.0;
+        if inside {
+            normalv = -normalv;
+        }
+        let (n1, n2) = self.refractive_indices(xs);
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            over_point: point + normalv * EPSILON,
+            under_point: point - normalv * EPSILON,
+            eyev,
+            normalv,
+            reflectv: ray.direction.reflect(&normalv),
+            inside,
+            n1,
+            n2,
+        }
+    }
+
+    fn refractive_indices(&self, xs: &[Intersection<'a>]) -> (f64, f64) {
+        let mut containers: Vec<&Object> = Vec::new();
+        let last_index = |containers: &Vec<&Object>| {
+            containers
+                .last()
+                .map(|object| object.material.refractive_index)
+                .unwrap_or(1.0)
+        };
+        let mut n1 = 1.0;
+        for i in xs {
+            let is_hit = i.t == self.t && std::ptr::eq(i.object, self.object);
+            if is_hit {
+                n1 = last_index(&containers);
+            }
+            if let Some(position) = containers
+                .iter()
+                .position(|object| std::ptr::eq(*object, i.object))
+            {
+                containers.remove(position);
+            } else {
+                containers.push(i.object);
+            }
+            if is_hit {
+                return (n1, last_index(&containers));
+            }
+        }
+        (n1, 1.0)
+    }
+}
+
+/// The closest intersection in front of the ray origin.
+pub fn hit<'a, 'b>(xs: &'b [Intersection<'a>]) -> Option<&'b Intersection<'a>> {
+    xs.iter()
+        .filter(|i| i.t >= 0.0)
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+}
+
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a Object,
+    pub point: Point,
+    pub over_point: Point,
+    pub under_point: Point,
+    pub eyev: V3D,
+    pub normalv: V3D,
+    pub reflectv: V3D,
+    pub inside: bool,
+    pub n1: f64,
+    pub n2: f64,
+}
+
+impl Computations<'_> {
+    /// Schlick's approximation of the Fresnel reflectance.
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(&self.normalv);
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n * n * (1.0 - cos * cos);
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+            cos = (1.0 - sin2_t).sqrt();
+        }
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{matrices::Mat4, point_vec::Point},
+        tracer::material::Material,
+    };
+
+    fn glass_sphere() -> Object {
+        Object::sphere().with_material(Material {
+            transparency: 1.0,
+            refractive_index: 1.5,
+            ..Material::default()
+        })
+    }
+
+    #[test]
+    fn hit_ignores_negative_t() {
+        let sphere = Object::sphere();
+        let xs = [
+            Intersection::new(5.0, &sphere),
+            Intersection::new(-3.0, &sphere),
+            Intersection::new(2.0, &sphere),
+        ];
+        assert_eq!(hit(&xs).map(|i| i.t), Some(2.0));
+        let xs = [Intersection::new(-1.0, &sphere)];
+        assert!(hit(&xs).is_none());
+    }
+
+    #[test]
+    fn hit_from_inside_flips_normal() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), V3D::new(0.0, 0.0, 1.0));
+        let sphere = Object::sphere();
+        let i = Intersection::new(1.0, &sphere);
+        let comps = i.prepare(&ray, &[i]);
+        assert!(comps.inside);
+        assert_eq!(comps.point, Point::new(0.0, 0.0, 1.0));
+        assert_eq!(comps.normalv, V3D::new(0.0, 0.0, -1.0));
+        assert!(comps.over_point.z < comps.point.z);
+    }
+
+    #[test]
+    fn refractive_indices_between_spheres() {
+        let a = glass_sphere().with_transform(Mat4::scaling(2.0, 2.0, 2.0));
+        let mut b = glass_sphere().with_transform(Mat4::translation(0.0, 0.0, -0.25));
+        b.material.refractive_index = 2.0;
+        let mut c = glass_sphere().with_transform(Mat4::translation(0.0, 0.0, 0.25));
+        c.material.refractive_index = 2.5;
+        let ray = Ray::new(Point::new(0.0, 0.0, -4.0), V3D::new(0.0, 0.0, 1.0));
+        let xs = [
+            Intersection::new(2.0, &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6.0, &a),
+        ];
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+        for (i, (n1, n2)) in xs.iter().zip(expected) {
+            let comps = i.prepare(&ray, &xs);
+            assert_eq!((comps.n1, comps.n2), (n1, n2));
+        }
+    }
+
+    #[test]
+    fn schlick_under_total_internal_reflection() {
+        let shape = glass_sphere();
+        let half = 2f64.sqrt() / 2.0;
+        let ray = Ray::new(Point::new(0.0, 0.0, half), V3D::new(0.0, 1.0, 0.0));
+        let xs = [
+            Intersection::new(-half, &shape),
+            Intersection::new(half, &shape),
+        ];
+        let comps = xs[1].prepare(&ray, &xs);
+        assert_eq!(comps.schlick(), 1.0);
+    }
+}
@@ -0,0 +1,121 @@
+use super::{
+    light::PointLight,
+    normal_map::{NormalPerturbation, SurfaceFrame},
+};
+use crate::math::{
+    color::Color,
+    point_vec::{Point, TupleLike, V3D},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
+    /// Light given off by the surface, it is what lights path traced scenes.
+    pub emissive: Color,
+    /// Normal map or bump function applied on top of the surface normal.
+    pub normal_perturbation: Option<NormalPerturbation>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            color: Color::white(),
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+            emissive: Color::black(),
+            normal_perturbation: None,
+        }
+    }
+}
+
+impl Material {
+    /// Normal used for shading, both the tracer and the rasterizer go through
+    /// here so normal maps look the same in both.
+    pub fn shading_normal(&self, frame: &SurfaceFrame) -> V3D {
+        match &self.normal_perturbation {
+            Some(perturbation) => perturbation.perturb(frame),
+            None => frame.normal.normalize(),
+        }
+    }
+
+    /// Phong shading of a point lit by a single light.
+    pub fn lighting(
+        &self,
+        light: &PointLight,
+        point: &Point,
+        eyev: &V3D,
+        normalv: &V3D,
+        in_shadow: bool,
+    ) -> Color {
+        let effective_color = self.color * light.intensity;
+        let ambient = effective_color * self.ambient;
+        if in_shadow {
+            return ambient;
+        }
+        let lightv = (light.position - *point).normalize();
+        let light_dot_normal = lightv.dot(normalv);
+        if light_dot_normal < 0.0 {
+            return ambient;
+        }
+        let diffuse = effective_color * self.diffuse * light_dot_normal;
+        let reflect_dot_eye = (-lightv).reflect(normalv).dot(eyev);
+        let specular = if reflect_dot_eye <= 0.0 {
+            Color::black()
+        } else {
+            light.intensity * self.specular * reflect_dot_eye.powf(self.shininess)
+        };
+        ambient + diffuse + specular
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup() -> (Material, Point, V3D) {
+        (
+            Material::default(),
+            Point::new(0.0, 0.0, 0.0),
+            V3D::new(0.0, 0.0, -1.0),
+        )
+    }
+
+    #[test]
+    fn eye_between_light_and_surface() {
+        let (m, position, normalv) = setup();
+        let eyev = V3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
+        let result = m.lighting(&light, &position, &eyev, &normalv, false);
+        assert_eq!(result, Color::new(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn light_behind_surface() {
+        let (m, position, normalv) = setup();
+        let eyev = V3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::white());
+        let result = m.lighting(&light, &position, &eyev, &normalv, false);
+        assert_eq!(result, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn surface_in_shadow() {
+        let (m, position, normalv) = setup();
+        let eyev = V3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
+        let result = m.lighting(&light, &position, &eyev, &normalv, true);
+        assert_eq!(result, Color::new(0.1, 0.1, 0.1));
+    }
+}
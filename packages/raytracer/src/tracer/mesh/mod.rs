@@ -0,0 +1,167 @@
+mod obj;
+mod primitives;
+mod subdivision;
+pub use obj::ObjError;
+
+use super::{
+    bounds::BoundingBox,
+    shapes::{Object, Triangle},
+};
+use crate::math::point_vec::{Point, TupleLike, V3D};
+
+/// Corner of a face, indexing into the mesh attribute lists. Like in OBJ
+/// files every attribute has its own index, so a position can be shared by
+/// faces on both sides of a uv seam or a hard edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaceVertex {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+impl FaceVertex {
+    pub fn new(position: usize, uv: Option<usize>, normal: Option<usize>) -> FaceVertex {
+        FaceVertex {
+            position,
+            uv,
+            normal,
+        }
+    }
+}
+
+/// Polygon mesh, faces may have any number of corners (in order around the
+/// face, counter clockwise seen from outside) and are fanned into triangles
+/// when the mesh is turned into shapes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Point>,
+    pub uvs: Vec<(f64, f64)>,
+    pub normals: Vec<V3D>,
+    pub faces: Vec<Vec<FaceVertex>>,
+}
+
+pub(crate) fn to_point(v: V3D) -> Point {
+    Point::new(v.x, v.y, v.z)
+}
+
+impl Mesh {
+    pub fn new() -> Mesh {
+        Mesh::default()
+    }
+
+    /// Parses the vertices and faces of a Wavefront OBJ file, everything
+    /// else (groups, materials, smoothing) is ignored.
+    pub fn from_obj(source: &str) -> Result<Mesh, ObjError> {
+        obj::parse(source)
+    }
+
+    pub fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for p in &self.positions {
+            bounds.add_point(p);
+        }
+        bounds
+    }
+
+    /// Every face split into a fan of triangles around its first corner.
+    pub fn triangle_indices(&self) -> impl Iterator<Item = [FaceVertex; 3]> + '_ {
+        self.faces.iter().flat_map(|face| {
+            (1..face.len().saturating_sub(1)).map(move |i| [face[0], face[i], face[i + 1]])
+        })
+    }
+
+    /// Triangle shapes for the faces, smooth shaded when every corner has a
+    /// normal and textured when every corner has a uv.
+    pub fn triangles(&self) -> Vec<Triangle> {
+        self.triangle_indices()
+            .map(|corners| {
+                let [p1, p2, p3] = corners.map(|c| self.positions[c.position]);
+                let mut triangle = Triangle::new(p1, p2, p3);
+                if let [Some(n1), Some(n2), Some(n3)] = corners.map(|c| c.normal) {
+                    triangle =
+                        triangle.with_normals([n1, n2, n3].map(|n| self.normals[n].normalize()));
+                }
+                if let [Some(t1), Some(t2), Some(t3)] = corners.map(|c| c.uv) {
+                    triangle = triangle.with_uvs([t1, t2, t3].map(|t| self.uvs[t]));
+                }
+                triangle
+            })
+            .collect()
+    }
+
+    /// A group holding one object per triangle.
+    pub fn to_object(&self) -> Object {
+        Object::group(self.triangles().into_iter().map(Object::new).collect())
+    }
+
+    /// Replaces the normals with smooth ones, the area weighted average of
+    /// the faces around every position.
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![V3D::new(0.0, 0.0, 0.0); self.positions.len()];
+        for [a, b, c] in self.triangle_indices() {
+            let (p1, p2, p3) = (
+                self.positions[a.position],
+                self.positions[b.position],
+                self.positions[c.position],
+            );
+            //the cross product length is twice the area, which does the weighting
+            let normal = (p2 - p1).cross(&(p3 - p1));
+            for corner in [a, b, c] {
+                normals[corner.position] = normals[corner.position] + normal;
+            }
+        }
+        self.normals = normals
+            .into_iter()
+            .map(|n| {
+                if n.magnitude() > 0.0 {
+                    n.normalize()
+                } else {
+                    n
+                }
+            })
+            .collect();
+        for face in &mut self.faces {
+            for corner in face {
+                corner.normal = Some(corner.position);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tracer::shapes::Shape;
+
+    fn quad() -> Mesh {
+        Mesh {
+            positions: vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            faces: vec![(0..4).map(|i| FaceVertex::new(i, None, None)).collect()],
+            ..Mesh::default()
+        }
+    }
+
+    #[test]
+    fn faces_are_fanned_into_triangles() {
+        let triangles = quad().triangles();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles[1].p1, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(triangles[1].p2, Point::new(1.0, 1.0, 0.0));
+        assert_eq!(triangles[1].p3, Point::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn computed_normals_face_out_of_counter_clockwise_faces() {
+        let mut mesh = quad();
+        mesh.compute_normals();
+        assert!(mesh.normals.iter().all(|n| *n == V3D::new(0.0, 0.0, 1.0)));
+        let triangles = mesh.triangles();
+        let normal = triangles[0].local_normal_at(&Point::new(0.5, 0.2, 0.0));
+        assert_eq!(normal, V3D::new(0.0, 0.0, 1.0));
+    }
+}
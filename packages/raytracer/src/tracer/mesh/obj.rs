@@ -0,0 +1,163 @@
+use super::{FaceVertex, Mesh};
+use crate::math::point_vec::{Point, V3D};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjError {
+    /// A number or index that doesn't parse, on the given (1 based) line.
+    Syntax(usize),
+    /// A face refers to a vertex, uv or normal that isn't defined yet.
+    BadIndex(usize),
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Syntax(line) => write!(f, "syntax error on line {}", line),
+            ObjError::BadIndex(line) => write!(f, "index out of range on line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+fn numbers<const N: usize>(fields: &[&str], line: usize) -> Result<[f64; N], ObjError> {
+    let mut out = [0.0; N];
+    for (i, value) in out.iter_mut().enumerate() {
+        let field = fields.get(i).ok_or(ObjError::Syntax(line))?;
+        *value = field.parse().map_err(|_| ObjError::Syntax(line))?;
+    }
+    Ok(out)
+}
+
+/// OBJ indices start at 1, negative ones count back from the latest element.
+fn index(field: &str, count: usize, line: usize) -> Result<usize, ObjError> {
+    let value: i64 = field.parse().map_err(|_| ObjError::Syntax(line))?;
+    let index = if value < 0 {
+        count as i64 + value
+    } else {
+        value - 1
+    };
+    if index < 0 || index >= count as i64 {
+        return Err(ObjError::BadIndex(line));
+    }
+    Ok(index as usize)
+}
+
+pub fn parse(source: &str) -> Result<Mesh, ObjError> {
+    let mut mesh = Mesh::new();
+    for (number, line) in source.lines().enumerate() {
+        let number = number + 1;
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        let Some(keyword) = fields.next() else {
+            continue;
+        };
+        let fields: Vec<&str> = fields.collect();
+        match keyword {
+            "v" => {
+                let [x, y, z] = numbers(&fields, number)?;
+                mesh.positions.push(Point::new(x, y, z));
+            }
+            "vt" => {
+                let [u] = numbers(&fields, number)?;
+                //v is optional for 1D textures
+                let v = match fields.get(1) {
+                    Some(v) => v.parse().map_err(|_| ObjError::Syntax(number))?,
+                    None => 0.0,
+                };
+                mesh.uvs.push((u, v));
+            }
+            "vn" => {
+                let [x, y, z] = numbers(&fields, number)?;
+                mesh.normals.push(V3D::new(x, y, z));
+            }
+            "f" => {
+                if fields.len() < 3 {
+                    return Err(ObjError::Syntax(number));
+                }
+                let face = fields
+                    .iter()
+                    .map(|field| {
+                        let mut parts = field.split('/');
+                        let position =
+                            index(parts.next().unwrap_or(""), mesh.positions.len(), number)?;
+                        let mut optional = |count: usize| match parts.next() {
+                            None | Some("") => Ok(None),
+                            Some(part) => index(part, count, number).map(Some),
+                        };
+                        let uv = optional(mesh.uvs.len())?;
+                        let normal = optional(mesh.normals.len())?;
+                        Ok(FaceVertex::new(position, uv, normal))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                mesh.faces.push(face);
+            }
+            _ => {}
+        }
+    }
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ignores_unknown_lines() {
+        let mesh = Mesh::from_obj(
+            "There was a young lady named Bright\nwho traveled much faster than light\n",
+        )
+        .unwrap();
+        assert_eq!(mesh, Mesh::new());
+    }
+
+    #[test]
+    fn parses_vertices_and_polygons() {
+        let source = "
+v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0 # comment
+g FirstGroup
+f 1 2 3 4
+";
+        let mesh = Mesh::from_obj(source).unwrap();
+        assert_eq!(mesh.positions[1], Point::new(-1.0, 0.5, 0.0));
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(mesh.triangles().len(), 2);
+        assert_eq!(mesh.faces[0][3], FaceVertex::new(3, None, None));
+    }
+
+    #[test]
+    fn parses_uvs_normals_and_relative_indices() {
+        let source = "
+v 0 1 0
+v -1 0 0
+v 1 0 0
+vt 0.5 1
+vt 0 0
+vn -1 0 0
+vn 1 0 0
+f 1/1/2 2//1 -1/-2/-1
+";
+        let mesh = Mesh::from_obj(source).unwrap();
+        assert_eq!(
+            mesh.faces[0],
+            vec![
+                FaceVertex::new(0, Some(0), Some(1)),
+                FaceVertex::new(1, None, Some(0)),
+                FaceVertex::new(2, Some(0), Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_the_failing_line() {
+        assert_eq!(Mesh::from_obj("v 1 2\n"), Err(ObjError::Syntax(1)));
+        assert_eq!(
+            Mesh::from_obj("v 1 2 3\nv 1 2 3\n\nf 1 2 4\n"),
+            Err(ObjError::BadIndex(4))
+        );
+    }
+}
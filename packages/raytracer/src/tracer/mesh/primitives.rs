@@ -0,0 +1,423 @@
+use super::{to_point, FaceVertex, Mesh};
+use crate::math::point_vec::{Point, TupleLike, V3D};
+use std::{collections::HashMap, f64::consts::PI};
+
+/// Point on the unit circle in the xz plane, going counter clockwise seen
+/// from above as `t` goes from 0 to 1.
+fn around(t: f64) -> (f64, f64) {
+    let angle = 2.0 * PI * t;
+    (angle.cos(), -angle.sin())
+}
+
+fn corner(position: usize, uv: usize, normal: usize) -> FaceVertex {
+    FaceVertex::new(position, Some(uv), Some(normal))
+}
+
+/// Generators for the usual primitives. Sizes match the ray traced shapes
+/// (unit sphere, cube from -1 to 1, ...) and faces are wound counter
+/// clockwise seen from outside.
+impl Mesh {
+    /// Sphere of radius 1 made of `segments` slices around the y axis and
+    /// `rings` stacks from pole to pole.
+    pub fn uv_sphere(segments: usize, rings: usize) -> Mesh {
+        let (segments, rings) = (segments.max(3), rings.max(2));
+        let mut mesh = Mesh::new();
+        let last = 1 + (rings - 1) * segments;
+        let position = |ring: usize, segment: usize| match ring {
+            0 => 0,
+            r if r == rings => last,
+            r => 1 + (r - 1) * segments + segment % segments,
+        };
+        mesh.positions.push(Point::new(0.0, 1.0, 0.0));
+        for ring in 1..rings {
+            let (sin, cos) = (PI * ring as f64 / rings as f64).sin_cos();
+            for segment in 0..segments {
+                let (x, z) = around(segment as f64 / segments as f64);
+                mesh.positions.push(Point::new(x * sin, cos, z * sin));
+            }
+        }
+        mesh.positions.push(Point::new(0.0, -1.0, 0.0));
+        mesh.normals = mesh.positions.iter().map(|p| V3D::from(*p)).collect();
+        for ring in 0..=rings {
+            for segment in 0..=segments {
+                mesh.uvs.push((
+                    segment as f64 / segments as f64,
+                    1.0 - ring as f64 / rings as f64,
+                ));
+            }
+        }
+        let uv = |ring: usize, segment: usize| ring * (segments + 1) + segment;
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let mut face = vec![(ring, segment), (ring + 1, segment)];
+                if ring + 1 < rings {
+                    face.push((ring + 1, segment + 1));
+                }
+                if ring > 0 {
+                    face.push((ring, segment + 1));
+                }
+                mesh.faces.push(
+                    face.into_iter()
+                        .map(|(r, s)| corner(position(r, s), uv(r, s), position(r, s)))
+                        .collect(),
+                );
+            }
+        }
+        mesh
+    }
+
+    /// Sphere of radius 1 from an icosahedron whose faces are split in four
+    /// `subdivisions` times, which spreads the vertices evenly.
+    pub fn icosphere(subdivisions: usize) -> Mesh {
+        let t = (1.0 + 5f64.sqrt()) / 2.0;
+        let mut positions: Vec<V3D> = [
+            (-1.0, t, 0.0),
+            (1.0, t, 0.0),
+            (-1.0, -t, 0.0),
+            (1.0, -t, 0.0),
+            (0.0, -1.0, t),
+            (0.0, 1.0, t),
+            (0.0, -1.0, -t),
+            (0.0, 1.0, -t),
+            (t, 0.0, -1.0),
+            (t, 0.0, 1.0),
+            (-t, 0.0, -1.0),
+            (-t, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|(x, y, z)| V3D::new(*x, *y, *z).normalize())
+        .collect();
+        let mut faces: Vec<[usize; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+        for _ in 0..subdivisions {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: usize, b: usize| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    positions.push(((positions[a] + positions[b]) * 0.5).normalize());
+                    positions.len() - 1
+                })
+            };
+            faces = faces
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+        let mut mesh = Mesh {
+            positions: positions.iter().map(|p| to_point(*p)).collect(),
+            normals: positions.clone(),
+            ..Mesh::new()
+        };
+        for face in faces {
+            let spherical = |p: &V3D| {
+                let u = 0.5 + (-p.z).atan2(p.x) / (2.0 * PI);
+                (u, 0.5 + p.y.clamp(-1.0, 1.0).asin() / PI)
+            };
+            let mut uvs = face.map(|i| spherical(&positions[i]));
+            //faces straddling the seam get their small u pushed past 1
+            let max_u = uvs.iter().map(|uv| uv.0).fold(0.0, f64::max);
+            for uv in uvs.iter_mut() {
+                if max_u - uv.0 > 0.5 {
+                    uv.0 += 1.0;
+                }
+            }
+            //the poles take the u of the face they are in
+            for i in 0..3 {
+                if positions[face[i]].y.abs() > 1.0 - 1e-9 {
+                    uvs[i].0 = (uvs[(i + 1) % 3].0 + uvs[(i + 2) % 3].0) / 2.0;
+                }
+            }
+            let start = mesh.uvs.len();
+            mesh.uvs.extend(uvs);
+            mesh.faces.push(
+                (0..3)
+                    .map(|i| corner(face[i], start + i, face[i]))
+                    .collect(),
+            );
+        }
+        mesh
+    }
+
+    /// Cube from -1 to 1 on every axis with one flat normal per face.
+    pub fn cube() -> Mesh {
+        let mut mesh = Mesh::new();
+        for i in 0..8 {
+            let coordinate = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            mesh.positions
+                .push(Point::new(coordinate(1), coordinate(2), coordinate(4)));
+        }
+        mesh.uvs = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let index = |p: V3D| {
+            (p.x > 0.0) as usize | ((p.y > 0.0) as usize) << 1 | ((p.z > 0.0) as usize) << 2
+        };
+        let axes = [
+            V3D::new(1.0, 0.0, 0.0),
+            V3D::new(0.0, 1.0, 0.0),
+            V3D::new(0.0, 0.0, 1.0),
+        ];
+        for axis in axes {
+            for normal in [axis, -axis] {
+                //u x v = normal makes the corners go counter clockwise
+                let v = if normal.y == 0.0 {
+                    V3D::new(0.0, 1.0, 0.0)
+                } else {
+                    V3D::new(0.0, 0.0, -normal.y)
+                };
+                let u = v.cross(&normal);
+                mesh.normals.push(normal);
+                let n = mesh.normals.len() - 1;
+                let corners = [u * -1.0 - v, u - v, u + v, v - u];
+                mesh.faces.push(
+                    corners
+                        .iter()
+                        .enumerate()
+                        .map(|(uv, offset)| corner(index(normal + *offset), uv, n))
+                        .collect(),
+                );
+            }
+        }
+        mesh
+    }
+
+    /// Cylinder of radius 1 around the y axis between `minimum` and
+    /// `maximum`, `closed` adds the caps.
+    pub fn cylinder(segments: usize, minimum: f64, maximum: f64, closed: bool) -> Mesh {
+        let segments = segments.max(3);
+        let mut mesh = Mesh::new();
+        for y in [minimum, maximum] {
+            for segment in 0..segments {
+                let (x, z) = around(segment as f64 / segments as f64);
+                mesh.positions.push(Point::new(x, y, z));
+                if y == minimum {
+                    mesh.normals.push(V3D::new(x, 0.0, z));
+                }
+            }
+        }
+        for v in [0.0, 1.0] {
+            for segment in 0..=segments {
+                mesh.uvs.push((segment as f64 / segments as f64, v));
+            }
+        }
+        let ring = |level: usize, segment: usize| level * segments + segment % segments;
+        for segment in 0..segments {
+            let next = segment + 1;
+            mesh.faces.push(vec![
+                corner(ring(0, segment), segment, segment),
+                corner(ring(0, next), next, next % segments),
+                corner(ring(1, next), segments + 1 + next, next % segments),
+                corner(ring(1, segment), segments + 1 + segment, segment),
+            ]);
+        }
+        if !closed {
+            return mesh;
+        }
+        for (level, y) in [(0, minimum), (1, maximum)] {
+            let center = mesh.positions.len();
+            mesh.positions.push(Point::new(0.0, y, 0.0));
+            mesh.normals
+                .push(V3D::new(0.0, if level == 0 { -1.0 } else { 1.0 }, 0.0));
+            let normal = mesh.normals.len() - 1;
+            let center_uv = mesh.uvs.len();
+            mesh.uvs.push((0.5, 0.5));
+            for segment in 0..segments {
+                let p = mesh.positions[ring(level, segment)];
+                mesh.uvs.push((p.x * 0.5 + 0.5, p.z * 0.5 + 0.5));
+            }
+            for segment in 0..segments {
+                let next = (segment + 1) % segments;
+                let mut rim = [segment, next];
+                if level == 0 {
+                    rim.reverse();
+                }
+                let mut face = vec![corner(center, center_uv, normal)];
+                face.extend(rim.map(|s| corner(ring(level, s), center_uv + 1 + s, normal)));
+                mesh.faces.push(face);
+            }
+        }
+        mesh
+    }
+
+    /// Ring around the y axis, `major` is the distance from the center to
+    /// the middle of the tube and `minor` the radius of the tube.
+    pub fn torus(major: f64, minor: f64, segments: usize, sides: usize) -> Mesh {
+        let (segments, sides) = (segments.max(3), sides.max(3));
+        let mut mesh = Mesh::new();
+        for segment in 0..segments {
+            let (x, z) = around(segment as f64 / segments as f64);
+            for side in 0..sides {
+                let (sin, cos) = (2.0 * PI * side as f64 / sides as f64).sin_cos();
+                let normal = V3D::new(x * cos, sin, z * cos);
+                mesh.positions.push(to_point(
+                    V3D::new(x * major, 0.0, z * major) + normal * minor,
+                ));
+                mesh.normals.push(normal);
+            }
+        }
+        for segment in 0..=segments {
+            for side in 0..=sides {
+                mesh.uvs
+                    .push((segment as f64 / segments as f64, side as f64 / sides as f64));
+            }
+        }
+        let at = |segment: usize, side: usize| (segment % segments) * sides + side % sides;
+        let uv = |segment: usize, side: usize| segment * (sides + 1) + side;
+        for segment in 0..segments {
+            for side in 0..sides {
+                mesh.faces.push(
+                    [
+                        (segment, side),
+                        (segment + 1, side),
+                        (segment + 1, side + 1),
+                        (segment, side + 1),
+                    ]
+                    .map(|(s, t)| corner(at(s, t), uv(s, t), at(s, t)))
+                    .to_vec(),
+                );
+            }
+        }
+        mesh
+    }
+
+    /// Square in the xz plane from -1 to 1, facing up and split in
+    /// `subdivisions`² quads.
+    pub fn plane(subdivisions: usize) -> Mesh {
+        let n = subdivisions.max(1);
+        let mut mesh = Mesh::new();
+        for i in 0..=n {
+            for j in 0..=n {
+                let (u, v) = (j as f64 / n as f64, i as f64 / n as f64);
+                mesh.positions
+                    .push(Point::new(u * 2.0 - 1.0, 0.0, v * 2.0 - 1.0));
+                mesh.uvs.push((u, v));
+            }
+        }
+        mesh.normals.push(V3D::new(0.0, 1.0, 0.0));
+        let at = |i: usize, j: usize| i * (n + 1) + j;
+        for i in 0..n {
+            for j in 0..n {
+                mesh.faces.push(
+                    [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)]
+                        .map(|(a, b)| corner(at(a, b), at(a, b), 0))
+                        .to_vec(),
+                );
+            }
+        }
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Every face normal points away from `center`, and every corner normal
+    /// agrees with its face.
+    fn assert_outward(mesh: &Mesh, center: Point) {
+        for [a, b, c] in mesh.triangle_indices() {
+            let [p1, p2, p3] = [a, b, c].map(|v| mesh.positions[v.position]);
+            let normal = (p2 - p1).cross(&(p3 - p1));
+            let middle = to_point((V3D::from(p1) + V3D::from(p2) + V3D::from(p3)) * (1.0 / 3.0));
+            assert!(normal.dot(&(middle - center)) > 0.0, "{:?}", [p1, p2, p3]);
+            for corner in [a, b, c] {
+                let n = mesh.normals[corner.normal.unwrap()];
+                assert!(n.dot(&normal) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn uv_sphere() {
+        let mesh = Mesh::uv_sphere(8, 4);
+        assert_eq!(mesh.positions.len(), 2 + 3 * 8);
+        assert_eq!(mesh.faces.len(), 8 * 4);
+        assert_eq!(mesh.triangles().len(), 2 * 8 + 2 * 2 * 8);
+        assert!(mesh
+            .positions
+            .iter()
+            .all(|p| (V3D::from(*p).magnitude() - 1.0).abs() < 1e-9));
+        assert_outward(&mesh, Point::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn icosphere() {
+        let mesh = Mesh::icosphere(2);
+        assert_eq!(mesh.faces.len(), 20 * 16);
+        //euler: V - E + F = 2 with E = 3F / 2
+        assert_eq!(mesh.positions.len(), 2 + 20 * 16 / 2);
+        assert_outward(&mesh, Point::new(0.0, 0.0, 0.0));
+        //no face stretches across the whole texture at the seam
+        for face in &mesh.faces {
+            let us: Vec<f64> = face.iter().map(|c| mesh.uvs[c.uv.unwrap()].0).collect();
+            let spread = us.iter().cloned().fold(f64::MIN, f64::max)
+                - us.iter().cloned().fold(f64::MAX, f64::min);
+            assert!(spread < 0.5);
+        }
+    }
+
+    #[test]
+    fn cube() {
+        let mesh = Mesh::cube();
+        assert_eq!(mesh.positions.len(), 8);
+        assert_eq!(mesh.faces.len(), 6);
+        assert_outward(&mesh, Point::new(0.0, 0.0, 0.0));
+        let bounds = mesh.bounds();
+        assert_eq!(bounds.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(bounds.max, Point::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn cylinder() {
+        let open = Mesh::cylinder(6, 0.0, 2.0, false);
+        assert_eq!(open.faces.len(), 6);
+        assert_outward(&open, Point::new(0.0, 1.0, 0.0));
+        let closed = Mesh::cylinder(6, 0.0, 2.0, true);
+        assert_eq!(closed.faces.len(), 6 * 3);
+        assert_outward(&closed, Point::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn torus() {
+        let mesh = Mesh::torus(2.0, 0.5, 12, 6);
+        assert_eq!(mesh.positions.len(), 12 * 6);
+        for [a, b, c] in mesh.triangle_indices() {
+            //faces point away from the middle of the tube
+            let p = mesh.positions[a.position];
+            let (x, z) = (p.x, p.z);
+            let scale = 2.0 / (x * x + z * z).sqrt();
+            let tube = Point::new(x * scale, 0.0, z * scale);
+            let [p1, p2, p3] = [a, b, c].map(|v| mesh.positions[v.position]);
+            assert!((p2 - p1).cross(&(p3 - p1)).dot(&(p1 - tube)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn plane() {
+        let mesh = Mesh::plane(3);
+        assert_eq!(mesh.positions.len(), 16);
+        assert_eq!(mesh.faces.len(), 9);
+        assert_outward(&mesh, Point::new(0.0, -1.0, 0.0));
+        assert_eq!(mesh.uvs[15], (1.0, 1.0));
+    }
+}
@@ -0,0 +1,343 @@
+use super::{to_point, FaceVertex, Mesh};
+use crate::math::point_vec::{Point, V3D};
+use std::{collections::HashMap, f64::consts::PI};
+
+struct Edge {
+    ends: (usize, usize),
+    faces: Vec<usize>,
+}
+
+/// Connectivity of the positions of a mesh: the edges with the faces on
+/// either side, and the edges meeting at every position.
+struct Topology {
+    edges: Vec<Edge>,
+    index: HashMap<(usize, usize), usize>,
+    incident: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    fn new(positions: usize, faces: &[Vec<FaceVertex>]) -> Topology {
+        let mut topology = Topology {
+            edges: vec![],
+            index: HashMap::new(),
+            incident: vec![vec![]; positions],
+        };
+        for (f, face) in faces.iter().enumerate() {
+            for i in 0..face.len() {
+                let (a, b) = (face[i].position, face[(i + 1) % face.len()].position);
+                let key = (a.min(b), a.max(b));
+                let edges = &mut topology.edges;
+                let incident = &mut topology.incident;
+                let e = *topology.index.entry(key).or_insert_with(|| {
+                    edges.push(Edge {
+                        ends: key,
+                        faces: vec![],
+                    });
+                    incident[a].push(edges.len() - 1);
+                    incident[b].push(edges.len() - 1);
+                    edges.len() - 1
+                });
+                topology.edges[e].faces.push(f);
+            }
+        }
+        topology
+    }
+
+    fn edge(&self, a: usize, b: usize) -> usize {
+        self.index[&(a.min(b), a.max(b))]
+    }
+
+    fn is_boundary(&self, edge: usize) -> bool {
+        self.edges[edge].faces.len() < 2
+    }
+
+    fn other_end(&self, edge: usize, position: usize) -> usize {
+        let (a, b) = self.edges[edge].ends;
+        if a == position {
+            b
+        } else {
+            a
+        }
+    }
+
+    /// Neighbors of `position` along boundary edges, a vertex on a
+    /// smooth open border has two.
+    fn boundary_neighbors(&self, position: usize) -> Vec<usize> {
+        self.incident[position]
+            .iter()
+            .filter(|e| self.is_boundary(**e))
+            .map(|e| self.other_end(*e, position))
+            .collect()
+    }
+}
+
+fn sum(points: impl Iterator<Item = Point>) -> V3D {
+    points.fold(V3D::new(0.0, 0.0, 0.0), |acc, p| acc + V3D::from(p))
+}
+
+/// Border vertices only follow the border, so open meshes keep their outline.
+/// `valence` counts every edge at the vertex.
+fn boundary_rule(position: Point, neighbors: &[Point], valence: usize) -> Option<Point> {
+    match neighbors {
+        //a border vertex with no inner edges is a corner of the outline
+        [_, _] if valence == 2 => Some(position),
+        [a, b] => Some(to_point(
+            V3D::from(position) * 0.75 + (V3D::from(*a) + V3D::from(*b)) * 0.125,
+        )),
+        [] => None,
+        //corners where several borders meet stay put
+        _ => Some(position),
+    }
+}
+
+/// Face-varying uvs are subdivided linearly, splitting every uv edge once.
+struct UvSplitter {
+    uvs: Vec<(f64, f64)>,
+    midpoints: HashMap<(usize, usize), usize>,
+}
+
+impl UvSplitter {
+    fn midpoint(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        let (a, b) = (a?, b?);
+        let uvs = &mut self.uvs;
+        Some(
+            *self
+                .midpoints
+                .entry((a.min(b), a.max(b)))
+                .or_insert_with(|| {
+                    uvs.push(((uvs[a].0 + uvs[b].0) / 2.0, (uvs[a].1 + uvs[b].1) / 2.0));
+                    uvs.len() - 1
+                }),
+        )
+    }
+
+    fn center(&mut self, corners: &[FaceVertex]) -> Option<usize> {
+        let uvs: Option<Vec<usize>> = corners.iter().map(|c| c.uv).collect();
+        let uvs = uvs?;
+        let n = uvs.len() as f64;
+        let (u, v) = uvs.iter().fold((0.0, 0.0), |(u, v), i| {
+            (u + self.uvs[*i].0, v + self.uvs[*i].1)
+        });
+        self.uvs.push((u / n, v / n));
+        Some(self.uvs.len() - 1)
+    }
+}
+
+impl Mesh {
+    /// One step of Loop subdivision: every triangle becomes four and the
+    /// surface moves towards a smooth limit. Polygons are triangulated first
+    /// and normals are recomputed as smooth normals.
+    pub fn loop_subdivide(&self) -> Mesh {
+        let faces: Vec<Vec<FaceVertex>> = self.triangle_indices().map(|t| t.to_vec()).collect();
+        let topology = Topology::new(self.positions.len(), &faces);
+        let mut positions: Vec<Point> = (0..self.positions.len())
+            .map(|v| {
+                let p = self.positions[v];
+                let boundary: Vec<Point> = topology
+                    .boundary_neighbors(v)
+                    .iter()
+                    .map(|n| self.positions[*n])
+                    .collect();
+                if let Some(p) = boundary_rule(p, &boundary, topology.incident[v].len()) {
+                    return p;
+                }
+                let neighbors = &topology.incident[v];
+                let n = neighbors.len() as f64;
+                //Loop's original weights
+                let beta = (0.625 - (0.375 + 0.25 * (2.0 * PI / n).cos()).powi(2)) / n;
+                let ring = sum(neighbors
+                    .iter()
+                    .map(|e| self.positions[topology.other_end(*e, v)]));
+                to_point(V3D::from(p) * (1.0 - n * beta) + ring * beta)
+            })
+            .collect();
+        let first_edge = positions.len();
+        for edge in &topology.edges {
+            let (a, b) = edge.ends;
+            let ends = V3D::from(self.positions[a]) + V3D::from(self.positions[b]);
+            let point = if let [f1, f2] = edge.faces[..] {
+                let opposite = |f: usize| {
+                    let corner = faces[f]
+                        .iter()
+                        .find(|c| c.position != a && c.position != b)
+                        .expect("triangles have a corner off every edge");
+                    V3D::from(self.positions[corner.position])
+                };
+                ends * 0.375 + (opposite(f1) + opposite(f2)) * 0.125
+            } else {
+                ends * 0.5
+            };
+            positions.push(to_point(point));
+        }
+        let mut uvs = UvSplitter {
+            uvs: self.uvs.clone(),
+            midpoints: HashMap::new(),
+        };
+        let mut mesh = Mesh {
+            positions,
+            ..Mesh::new()
+        };
+        for face in &faces {
+            let (a, b, c) = (face[0], face[1], face[2]);
+            let mut split = |x: FaceVertex, y: FaceVertex| {
+                let position = first_edge + topology.edge(x.position, y.position);
+                FaceVertex::new(position, uvs.midpoint(x.uv, y.uv), None)
+            };
+            let (ab, bc, ca) = (split(a, b), split(b, c), split(c, a));
+            mesh.faces.extend([
+                vec![a, ab, ca],
+                vec![ab, b, bc],
+                vec![ca, bc, c],
+                vec![ab, bc, ca],
+            ]);
+        }
+        mesh.uvs = uvs.uvs;
+        mesh.compute_normals();
+        mesh
+    }
+
+    /// One step of Catmull-Clark subdivision: every n sided face becomes n
+    /// quads and the surface moves towards a smooth limit. Normals are
+    /// recomputed as smooth normals.
+    pub fn catmull_clark(&self) -> Mesh {
+        let topology = Topology::new(self.positions.len(), &self.faces);
+        let face_points: Vec<V3D> = self
+            .faces
+            .iter()
+            .map(|face| {
+                sum(face.iter().map(|c| self.positions[c.position])) * (1.0 / face.len() as f64)
+            })
+            .collect();
+        let mut positions: Vec<Point> = (0..self.positions.len())
+            .map(|v| {
+                let p = self.positions[v];
+                let boundary: Vec<Point> = topology
+                    .boundary_neighbors(v)
+                    .iter()
+                    .map(|n| self.positions[*n])
+                    .collect();
+                if let Some(p) = boundary_rule(p, &boundary, topology.incident[v].len()) {
+                    return p;
+                }
+                let edges = &topology.incident[v];
+                let n = edges.len() as f64;
+                let mut faces: Vec<usize> = edges
+                    .iter()
+                    .flat_map(|e| topology.edges[*e].faces.iter().copied())
+                    .collect();
+                faces.sort_unstable();
+                faces.dedup();
+                let f = faces
+                    .iter()
+                    .fold(V3D::new(0.0, 0.0, 0.0), |acc, f| acc + face_points[*f])
+                    * (1.0 / faces.len() as f64);
+                let r = sum(edges
+                    .iter()
+                    .map(|e| self.positions[topology.other_end(*e, v)]))
+                    * (1.0 / n)
+                    + V3D::from(p);
+                //r above is twice the average edge midpoint
+                to_point((f + r + V3D::from(p) * (n - 3.0)) * (1.0 / n))
+            })
+            .collect();
+        let first_edge = positions.len();
+        for edge in &topology.edges {
+            let (a, b) = edge.ends;
+            let ends = V3D::from(self.positions[a]) + V3D::from(self.positions[b]);
+            let point = if let [f1, f2] = edge.faces[..] {
+                (ends + face_points[f1] + face_points[f2]) * 0.25
+            } else {
+                ends * 0.5
+            };
+            positions.push(to_point(point));
+        }
+        let first_face = positions.len();
+        positions.extend(face_points.into_iter().map(to_point));
+        let mut uvs = UvSplitter {
+            uvs: self.uvs.clone(),
+            midpoints: HashMap::new(),
+        };
+        let mut mesh = Mesh {
+            positions,
+            ..Mesh::new()
+        };
+        for (f, face) in self.faces.iter().enumerate() {
+            let center = FaceVertex::new(first_face + f, uvs.center(face), None);
+            let n = face.len();
+            let mut split = |x: FaceVertex, y: FaceVertex| {
+                let position = first_edge + topology.edge(x.position, y.position);
+                FaceVertex::new(position, uvs.midpoint(x.uv, y.uv), None)
+            };
+            let mids: Vec<FaceVertex> = (0..n).map(|i| split(face[i], face[(i + 1) % n])).collect();
+            for i in 0..n {
+                let corner = FaceVertex::new(face[i].position, face[i].uv, None);
+                mesh.faces
+                    .push(vec![corner, mids[i], center, mids[(i + n - 1) % n]]);
+            }
+        }
+        mesh.uvs = uvs.uvs;
+        mesh.compute_normals();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::point_vec::TupleLike;
+
+    fn radius(p: &Point) -> f64 {
+        V3D::from(*p).magnitude()
+    }
+
+    #[test]
+    fn loop_quadruples_the_triangles() {
+        let mesh = Mesh::icosphere(0);
+        let once = mesh.loop_subdivide();
+        assert_eq!(once.faces.len(), 80);
+        assert_eq!(once.positions.len(), 12 + 30);
+        let twice = once.loop_subdivide();
+        assert_eq!(twice.faces.len(), 320);
+        //the limit surface sits inside the icosahedron
+        for p in &twice.positions {
+            assert!(radius(p) > 0.7 && radius(p) < 1.0, "{}", radius(p));
+        }
+    }
+
+    #[test]
+    fn loop_keeps_the_winding() {
+        let mesh = Mesh::icosphere(0).loop_subdivide();
+        for [a, b, c] in mesh.triangle_indices() {
+            let [p1, p2, p3] = [a, b, c].map(|v| mesh.positions[v.position]);
+            assert!((p2 - p1).cross(&(p3 - p1)).dot(&V3D::from(p1)) > 0.0);
+        }
+        assert!(mesh.faces.iter().flatten().all(|c| c.uv.is_some()));
+    }
+
+    #[test]
+    fn catmull_clark_rounds_the_cube() {
+        let once = Mesh::cube().catmull_clark();
+        assert_eq!(once.faces.len(), 24);
+        //8 corners, 12 edges and 6 faces
+        assert_eq!(once.positions.len(), 26);
+        //corner vertices are pulled in: (F + 2R + (n - 3)P) / n with n = 3
+        assert_eq!(
+            once.positions[7],
+            Point::new(5.0 / 9.0, 5.0 / 9.0, 5.0 / 9.0)
+        );
+        //face centers stay where they were
+        assert!(once.positions.contains(&Point::new(1.0, 0.0, 0.0)));
+        assert_eq!(once.catmull_clark().faces.len(), 96);
+    }
+
+    #[test]
+    fn open_borders_stay_on_the_plane() {
+        let mesh = Mesh::plane(2).catmull_clark();
+        assert!(mesh.positions.iter().all(|p| p.y == 0.0));
+        //the outline corners don't move
+        assert!(mesh.positions.contains(&Point::new(-1.0, 0.0, -1.0)));
+        let mesh = Mesh::plane(2).loop_subdivide();
+        assert!(mesh.positions.iter().all(|p| p.y == 0.0));
+        assert!(mesh.normals.iter().all(|n| *n == V3D::new(0.0, 1.0, 0.0)));
+    }
+}
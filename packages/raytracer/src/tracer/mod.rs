@@ -1 +1,23 @@
+pub mod animation;
+pub mod bounds;
+pub mod bvh;
+pub mod camera;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod canvas;
+pub mod description;
+pub mod environment;
+pub mod intersection;
+pub mod light;
+pub mod material;
+pub mod mesh;
+pub mod normal_map;
+pub mod packet;
+pub mod path;
+pub mod ray;
+pub mod render;
+pub mod sampling;
+pub mod scene;
+pub mod shapes;
+pub mod texture;
+pub mod tone_mapping;
+pub mod world;
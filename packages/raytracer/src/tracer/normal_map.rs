@@ -0,0 +1,189 @@
+use super::texture::Texture;
+use crate::math::point_vec::{Point, TupleLike, V3D};
+use std::{fmt::Debug, sync::Arc};
+
+/// Height field used for procedural bump mapping, it is evaluated at object
+/// space points so the bumps move with the object.
+pub trait BumpFunction: Send + Sync {
+    fn height(&self, point: &Point) -> f64;
+}
+
+impl<F> BumpFunction for F
+where
+    F: Fn(&Point) -> f64 + Send + Sync,
+{
+    fn height(&self, point: &Point) -> f64 {
+        self(point)
+    }
+}
+
+/// What a shading normal is built from: the geometric (or interpolated)
+/// normal plus the tangent frame and texture coordinates at that point.
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceFrame {
+    pub point: Point,
+    pub normal: V3D,
+    pub tangent: V3D,
+    pub uv: (f64, f64),
+}
+
+#[derive(Clone)]
+pub enum NormalPerturbation {
+    /// Tangent space normal map, each texel stores `xyz * 0.5 + 0.5` where
+    /// z points away from the surface.
+    NormalMap {
+        texture: Arc<Texture>,
+        strength: f64,
+    },
+    /// Procedural heights, the normal is tilted against their gradient.
+    Bump {
+        function: Arc<dyn BumpFunction>,
+        scale: f64,
+    },
+}
+
+const GRADIENT_STEP: f64 = 1e-4;
+
+impl NormalPerturbation {
+    pub fn normal_map(texture: Texture) -> NormalPerturbation {
+        NormalPerturbation::NormalMap {
+            texture: Arc::new(texture),
+            strength: 1.0,
+        }
+    }
+
+    pub fn bump(function: impl BumpFunction + 'static, scale: f64) -> NormalPerturbation {
+        NormalPerturbation::Bump {
+            function: Arc::new(function),
+            scale,
+        }
+    }
+
+    /// The perturbed, normalized, normal. It is in the same space as the frame.
+    pub fn perturb(&self, frame: &SurfaceFrame) -> V3D {
+        let normal = frame.normal.normalize();
+        match self {
+            NormalPerturbation::NormalMap { texture, strength } => {
+                let texel = texture.sample(frame.uv.0, frame.uv.1);
+                let mapped = V3D::new(
+                    texel.r * 2.0 - 1.0,
+                    texel.g * 2.0 - 1.0,
+                    texel.b * 2.0 - 1.0,
+                );
+                let flat = V3D::new(0.0, 0.0, 1.0);
+                let mapped = flat + (mapped - flat) * *strength;
+                //Gram-Schmidt, interpolated tangents are rarely perpendicular to the normal
+                let tangent = (frame.tangent - normal * frame.tangent.dot(&normal)).normalize();
+                let bitangent = normal.cross(&tangent);
+                (tangent * mapped.x + bitangent * mapped.y + normal * mapped.z).normalize()
+            }
+            NormalPerturbation::Bump { function, scale } => {
+                let p = frame.point;
+                let h = GRADIENT_STEP;
+                let derivative = |offset: V3D| {
+                    (function.height(&(p + offset)) - function.height(&(p - offset))) / (2.0 * h)
+                };
+                let gradient = V3D::new(
+                    derivative(V3D::new(h, 0.0, 0.0)),
+                    derivative(V3D::new(0.0, h, 0.0)),
+                    derivative(V3D::new(0.0, 0.0, h)),
+                );
+                let surface_gradient = gradient - normal * gradient.dot(&normal);
+                (normal - surface_gradient * *scale).normalize()
+            }
+        }
+    }
+}
+
+impl Debug for NormalPerturbation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NormalPerturbation::NormalMap { texture, strength } => f
+                .debug_struct("NormalMap")
+                .field("width", &texture.width())
+                .field("height", &texture.height())
+                .field("strength", strength)
+                .finish(),
+            NormalPerturbation::Bump { scale, .. } => {
+                f.debug_struct("Bump").field("scale", scale).finish()
+            }
+        }
+    }
+}
+
+impl PartialEq for NormalPerturbation {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                NormalPerturbation::NormalMap { texture, strength },
+                NormalPerturbation::NormalMap {
+                    texture: other_texture,
+                    strength: other_strength,
+                },
+            ) => Arc::ptr_eq(texture, other_texture) && strength == other_strength,
+            (
+                NormalPerturbation::Bump { function, scale },
+                NormalPerturbation::Bump {
+                    function: other_function,
+                    scale: other_scale,
+                },
+            ) => Arc::ptr_eq(function, other_function) && scale == other_scale,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::color::Color;
+
+    fn frame() -> SurfaceFrame {
+        SurfaceFrame {
+            point: Point::new(0.0, 0.0, 0.0),
+            normal: V3D::new(0.0, 1.0, 0.0),
+            tangent: V3D::new(1.0, 0.0, 0.0),
+            uv: (0.5, 0.5),
+        }
+    }
+
+    #[test]
+    fn flat_normal_map_keeps_the_normal() {
+        let flat = Texture::solid(Color::new(0.5, 0.5, 1.0));
+        let n = NormalPerturbation::normal_map(flat).perturb(&frame());
+        assert_eq!(n, V3D::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn normal_map_tilts_along_the_tangent() {
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        let tilted = Texture::solid(Color::new(half * 0.5 + 0.5, 0.5, half * 0.5 + 0.5));
+        let n = NormalPerturbation::normal_map(tilted).perturb(&frame());
+        assert_eq!(n, V3D::new(half, half, 0.0));
+    }
+
+    #[test]
+    fn strength_zero_disables_the_map() {
+        let tilted = Texture::solid(Color::new(1.0, 0.5, 0.5));
+        let map = NormalPerturbation::NormalMap {
+            texture: Arc::new(tilted),
+            strength: 0.0,
+        };
+        assert_eq!(map.perturb(&frame()), V3D::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn bump_tilts_against_the_slope() {
+        //height grows with x, so the normal leans towards -x
+        let bump = NormalPerturbation::bump(|p: &Point| p.x, 1.0);
+        let n = bump.perturb(&frame());
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        assert_eq!(n, V3D::new(-half, half, 0.0));
+    }
+
+    #[test]
+    fn bump_ignores_slope_along_the_normal() {
+        let bump = NormalPerturbation::bump(|p: &Point| p.y * 10.0, 1.0);
+        assert_eq!(bump.perturb(&frame()), V3D::new(0.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,216 @@
+use super::{
+    bvh::{Bvh, Hit, Primitive, EPSILON},
+    ray::Ray,
+};
+use std::simd::{
+    cmp::{SimdPartialEq, SimdPartialOrd},
+    num::SimdFloat,
+    Mask, Select, Simd, StdFloat,
+};
+
+type Lanes<const N: usize> = Simd<f32, N>;
+type Vec3x<const N: usize> = [Lanes<N>; 3];
+
+fn dot<const N: usize>(a: &Vec3x<N>, b: &Vec3x<N>) -> Lanes<N> {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross<const N: usize>(a: &Vec3x<N>, b: &Vec3x<N>) -> Vec3x<N> {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn splat<const N: usize>(v: [f32; 3]) -> Vec3x<N> {
+    v.map(Simd::splat)
+}
+
+/// `N` rays stored structure of arrays, one lane per ray. Rays in a packet
+/// should be coherent (like neighboring camera rays) so they visit mostly
+/// the same BVH nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct RayPacket<const N: usize> {
+    origin: Vec3x<N>,
+    direction: Vec3x<N>,
+    inverse: Vec3x<N>,
+}
+
+pub type RayPacket4 = RayPacket<4>;
+pub type RayPacket8 = RayPacket<8>;
+
+impl<const N: usize> RayPacket<N> {
+    pub fn new(rays: &[Ray; N]) -> RayPacket<N> {
+        let lanes =
+            |f: &dyn Fn(&Ray) -> f64| Simd::from_array(rays.each_ref().map(|r| f(r) as f32));
+        let origin = [
+            lanes(&|r| r.origin.x),
+            lanes(&|r| r.origin.y),
+            lanes(&|r| r.origin.z),
+        ];
+        let direction = [
+            lanes(&|r| r.direction.x),
+            lanes(&|r| r.direction.y),
+            lanes(&|r| r.direction.z),
+        ];
+        RayPacket {
+            origin,
+            direction,
+            inverse: direction.map(|d| d.recip()),
+        }
+    }
+
+    /// Lanes whose ray crosses the box before `limit`.
+    fn hits_box(&self, min: [f32; 3], max: [f32; 3], limit: Lanes<N>) -> Mask<i32, N> {
+        let mut near = Lanes::splat(0.0);
+        let mut far = limit;
+        for axis in 0..3 {
+            let t1 = (Simd::splat(min[axis]) - self.origin[axis]) * self.inverse[axis];
+            let t2 = (Simd::splat(max[axis]) - self.origin[axis]) * self.inverse[axis];
+            near = near.simd_max(t1.simd_min(t2));
+            far = far.simd_min(t1.simd_max(t2));
+        }
+        near.simd_le(far)
+    }
+
+    /// Distance to the closest hit beyond [`EPSILON`] for every lane,
+    /// infinity where the ray misses.
+    fn intersect(&self, primitive: &Primitive) -> Lanes<N> {
+        let miss = Lanes::splat(f32::INFINITY);
+        let epsilon = Lanes::splat(EPSILON);
+        match *primitive {
+            Primitive::Sphere { center, radius } => {
+                let center = splat(center);
+                let oc = [0, 1, 2].map(|i| self.origin[i] - center[i]);
+                let a = dot(&self.direction, &self.direction);
+                let b = dot(&oc, &self.direction);
+                let c = dot(&oc, &oc) - Simd::splat(radius * radius);
+                let discriminant = b * b - a * c;
+                let root = discriminant.sqrt();
+                let near = (-b - root) / a;
+                let far = (-b + root) / a;
+                let t = near.simd_gt(epsilon).select(near, far);
+                (discriminant.simd_ge(Lanes::splat(0.0)) & t.simd_gt(epsilon)).select(t, miss)
+            }
+            Primitive::Triangle { p1, e1, e2 } => {
+                let (e1, e2) = (splat(e1), splat(e2));
+                let p1 = splat(p1);
+                let dir_cross_e2 = cross(&self.direction, &e2);
+                let det = dot(&e1, &dir_cross_e2);
+                let f = det.recip();
+                let p1_to_origin = [0, 1, 2].map(|i| self.origin[i] - p1[i]);
+                let u = f * dot(&p1_to_origin, &dir_cross_e2);
+                let origin_cross_e1 = cross(&p1_to_origin, &e1);
+                let v = f * dot(&self.direction, &origin_cross_e1);
+                let t = f * dot(&e2, &origin_cross_e1);
+                let zero = Lanes::splat(0.0);
+                let inside = det.abs().simd_ge(Lanes::splat(1e-8))
+                    & u.simd_ge(zero)
+                    & v.simd_ge(zero)
+                    & (u + v).simd_le(Lanes::splat(1.0))
+                    & t.simd_gt(epsilon);
+                inside.select(t, miss)
+            }
+        }
+    }
+}
+
+impl Bvh {
+    /// Closest hit for every ray of the packet. Nodes are visited as long as
+    /// any ray of the packet still crosses them.
+    pub fn intersect_packet<const N: usize>(&self, packet: &RayPacket<N>) -> [Option<Hit>; N] {
+        let none = Simd::splat(u32::MAX);
+        let mut closest = Lanes::<N>::splat(f32::INFINITY);
+        let mut ids = none;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                continue;
+            };
+            if !packet.hits_box(node.min, node.max, closest).any() {
+                continue;
+            }
+            if !node.is_leaf() {
+                stack.push(node.offset as usize);
+                stack.push(index + 1);
+                continue;
+            }
+            let leaf = node.offset as usize..(node.offset + node.count) as usize;
+            for (primitive, id) in &self.primitives[leaf] {
+                let t = packet.intersect(primitive);
+                let closer = t.simd_lt(closest);
+                closest = closer.select(t, closest);
+                ids = closer.select(Simd::splat(*id as u32), ids);
+            }
+        }
+        let found = ids.simd_ne(none);
+        let (closest, ids) = (closest.to_array(), ids.to_array());
+        std::array::from_fn(|lane| {
+            found.test(lane).then(|| Hit {
+                t: closest[lane],
+                primitive: ids[lane] as usize,
+            })
+        })
+    }
+
+    /// Closest hits of `rays`, traced `N` at a time with the rays that don't
+    /// fill the last packet traced one by one.
+    pub fn intersect_all<const N: usize>(&self, rays: &[Ray]) -> Vec<Option<Hit>> {
+        let mut chunks = rays.chunks_exact(N);
+        let mut hits = Vec::with_capacity(rays.len());
+        for chunk in chunks.by_ref() {
+            let chunk: &[Ray; N] = chunk.try_into().expect("chunks are N rays long");
+            hits.extend(self.intersect_packet(&RayPacket::new(chunk)));
+        }
+        hits.extend(chunks.remainder().iter().map(|ray| self.intersect(ray)));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tracer::bvh::test::{random_rays, scene};
+
+    fn assert_same(packet: &[Option<Hit>], single: &[Option<Hit>]) {
+        assert_eq!(packet.len(), single.len());
+        for (p, s) in packet.iter().zip(single) {
+            match (p, s) {
+                (Some(p), Some(s)) => {
+                    assert_eq!(p.primitive, s.primitive);
+                    assert!((p.t - s.t).abs() < 1e-4);
+                }
+                (None, None) => {}
+                _ => panic!("{:?} vs {:?}", p, s),
+            }
+        }
+    }
+
+    #[test]
+    fn packets_match_single_rays() {
+        let bvh = Bvh::new(&scene());
+        //an odd count leaves rays outside the last packet
+        let rays = random_rays(203);
+        let single: Vec<Option<Hit>> = rays.iter().map(|r| bvh.intersect(r)).collect();
+        assert_same(&bvh.intersect_all::<4>(&rays), &single);
+        assert_same(&bvh.intersect_all::<8>(&rays), &single);
+    }
+
+    #[test]
+    fn lanes_are_independent() {
+        let sphere = Primitive::sphere(&crate::math::point_vec::Point::new(0.0, 0.0, 0.0), 1.0);
+        let bvh = Bvh::new(&[sphere]);
+        let rays = random_rays(4);
+        let mut packet_rays: [Ray; 4] = rays.try_into().unwrap();
+        //one ray starts inside the sphere, one points away from it
+        packet_rays[0].origin = crate::math::point_vec::Point::new(0.0, 0.0, 0.0);
+        packet_rays[1].direction = -packet_rays[1].direction;
+        let hits = bvh.intersect_packet(&RayPacket4::new(&packet_rays));
+        assert!((hits[0].unwrap().t - 1.0).abs() < 1e-6);
+        assert_eq!(hits[1], None);
+        for lane in 0..4 {
+            assert_eq!(hits[lane], bvh.intersect(&packet_rays[lane]));
+        }
+    }
+}
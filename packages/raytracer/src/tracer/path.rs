@@ -0,0 +1,219 @@
+use super::{
+    intersection::hit,
+    ray::Ray,
+    sampling::{cosine_hemisphere, Rng},
+    world::World,
+};
+use crate::math::{color::Color, point_vec::TupleLike};
+use std::f64::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathSettings {
+    /// Hard limit on the length of a path.
+    pub max_bounces: usize,
+    /// Bounces before paths start being terminated by russian roulette.
+    pub roulette_depth: usize,
+    pub seed: u64,
+}
+
+impl Default for PathSettings {
+    fn default() -> Self {
+        PathSettings {
+            max_bounces: 8,
+            roulette_depth: 3,
+            seed: 0,
+        }
+    }
+}
+
+/// One sample of the radiance arriving along `ray`, estimated with a random
+/// walk. Surfaces are lambertian, with the `reflective` and `transparency`
+/// parts of the material picked as perfect specular lobes in proportion to
+/// their weight. Emissive materials light the scene and point lights are
+/// sampled directly as delta lights (with inverse square falloff).
+pub fn trace_path(world: &World, ray: &Ray, settings: &PathSettings, rng: &mut Rng) -> Color {
+    let mut radiance = Color::black();
+    let mut throughput = Color::white();
+    let mut ray = *ray;
+    for bounce in 0..=settings.max_bounces {
+        let xs = world.intersect(&ray);
+        let Some(intersection) = hit(&xs) else {
+            //the environment lights the scene through every bounce that escapes
+            radiance += throughput * world.environment.radiance(&ray.direction);
+            break;
+        };
+        let comps = intersection.prepare(&ray, &xs);
+        let material = &comps.object.material;
+        radiance += throughput * material.emissive;
+
+        let specular = material.reflective + material.transparency;
+        let scale = specular.max(1.0);
+        let lobe = rng.next_f64() * scale;
+        if lobe < material.reflective {
+            ray = Ray::new(comps.over_point, comps.reflectv);
+        } else if lobe < specular {
+            let n_ratio = comps.n1 / comps.n2;
+            let cos_i = comps.eyev.dot(&comps.normalv);
+            let sin2_t = n_ratio * n_ratio * (1.0 - cos_i * cos_i);
+            //fresnel decides between reflecting and refracting, total internal reflection always reflects
+            if sin2_t > 1.0 || rng.next_f64() < comps.schlick() {
+                ray = Ray::new(comps.over_point, comps.reflectv);
+            } else {
+                let cos_t = (1.0 - sin2_t).sqrt();
+                let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+                ray = Ray::new(comps.under_point, direction.normalize());
+            }
+        } else {
+            let albedo = material.color;
+            for light in &world.lights {
+                if world.is_shadowed(&light.position, &comps.over_point) {
+                    continue;
+                }
+                let to_light = light.position - comps.over_point;
+                let distance_squared = to_light.dot(&to_light);
+                let cos = to_light.normalize().dot(&comps.normalv);
+                if cos > 0.0 {
+                    radiance +=
+                        throughput * albedo * light.intensity * (cos / (PI * distance_squared));
+                }
+            }
+            let direction = cosine_hemisphere(&comps.normalv, rng.next_f64(), rng.next_f64());
+            ray = Ray::new(comps.over_point, direction);
+            //the cosine pdf cancels the lambertian cos/pi, leaving only the albedo
+            throughput = throughput * albedo;
+        }
+        //lobes were picked with probability weight/scale, so rescale when they don't add up to 1
+        if scale > 1.0 && lobe < specular {
+            throughput = throughput * scale;
+        }
+
+        if bounce >= settings.roulette_depth {
+            let survival = throughput.max_component().min(0.95);
+            if survival <= 0.0 || rng.next_f64() >= survival {
+                break;
+            }
+            throughput = throughput / survival;
+        }
+    }
+    radiance
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{
+            matrices::Mat4,
+            point_vec::{Point, V3D},
+        },
+        tracer::{environment::Environment, light::PointLight, material::Material, shapes::Object},
+    };
+
+    fn settings() -> PathSettings {
+        PathSettings {
+            max_bounces: 16,
+            ..PathSettings::default()
+        }
+    }
+
+    fn estimate(world: &World, ray: &Ray, samples: u32) -> Color {
+        let settings = settings();
+        (0..samples).fold(Color::black(), |acc, sample| {
+            let mut rng = Rng::for_sample(settings.seed, 0, 0, sample);
+            acc + trace_path(world, ray, &settings, &mut rng)
+        }) / samples as f64
+    }
+
+    #[test]
+    fn missing_everything_is_black() {
+        let world = World::new();
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(estimate(&world, &ray, 4), Color::black());
+    }
+
+    #[test]
+    fn sees_emitters_directly() {
+        let mut world = World::new();
+        world.add(Object::sphere().with_material(Material {
+            emissive: Color::new(2.0, 1.0, 0.5),
+            color: Color::black(),
+            ..Material::default()
+        }));
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(estimate(&world, &ray, 8), Color::new(2.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn furnace_converges_to_geometric_series() {
+        //inside an emissive sphere with albedo a every point sees E / (1 - a)
+        let mut world = World::new();
+        world.add(
+            Object::sphere()
+                .with_material(Material {
+                    emissive: Color::white() * 0.5,
+                    color: Color::white() * 0.5,
+                    ..Material::default()
+                })
+                .with_transform(Mat4::scaling(10.0, 10.0, 10.0)),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), V3D::new(0.0, 0.0, 1.0));
+        let color = estimate(&world, &ray, 4000);
+        assert!((color.r - 1.0).abs() < 0.05, "{:?}", color);
+    }
+
+    #[test]
+    fn point_lights_are_sampled_directly() {
+        let mut world = World::new();
+        world.add_light(PointLight::new(
+            Point::new(0.0, 2.0, 0.0),
+            Color::white() * PI * 4.0,
+        ));
+        world.add(Object::plane().with_material(Material {
+            color: Color::white() * 0.5,
+            ..Material::default()
+        }));
+        let ray = Ray::new(Point::new(0.0, 1.0, 0.0), V3D::new(0.0, -1.0, 0.0));
+        let settings = PathSettings {
+            max_bounces: 0,
+            ..settings()
+        };
+        let mut rng = Rng::new(0);
+        let color = trace_path(&world, &ray, &settings, &mut rng);
+        //albedo / pi * intensity * cos / d² = 0.5 / pi * 4 pi / 4
+        assert_eq!(color, Color::white() * 0.5);
+    }
+
+    #[test]
+    fn environment_lights_diffuse_surfaces() {
+        //every bounce off the floor escapes to a uniform sky of radiance 1
+        let mut world = World::new();
+        world.environment = Environment::Uniform(Color::white());
+        world.add(Object::plane().with_material(Material {
+            color: Color::white() * 0.5,
+            ..Material::default()
+        }));
+        let ray = Ray::new(Point::new(0.0, 1.0, 0.0), V3D::new(0.0, -1.0, 0.0));
+        assert_eq!(estimate(&world, &ray, 16), Color::white() * 0.5);
+    }
+
+    #[test]
+    fn mirrors_reflect_emitters() {
+        let mut world = World::new();
+        world.add(Object::plane().with_material(Material {
+            reflective: 1.0,
+            color: Color::black(),
+            ..Material::default()
+        }));
+        world.add(
+            Object::sphere()
+                .with_material(Material {
+                    emissive: Color::new(0.0, 1.0, 0.0),
+                    color: Color::black(),
+                    ..Material::default()
+                })
+                .with_transform(Mat4::translation(0.0, 5.0, 0.0)),
+        );
+        let ray = Ray::new(Point::new(0.0, 1.0, 0.0), V3D::new(0.0, -1.0, 0.0));
+        assert_eq!(estimate(&world, &ray, 4), Color::new(0.0, 1.0, 0.0));
+    }
+}
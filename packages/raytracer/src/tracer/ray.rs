@@ -0,0 +1,59 @@
+use crate::math::{
+    matrices::Mat4,
+    point_vec::{Point, V3D},
+    transformable::Transformable,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+    pub direction: V3D,
+}
+
+impl Ray {
+    pub fn new(origin: Point, direction: V3D) -> Ray {
+        Ray { origin, direction }
+    }
+
+    pub fn position(&self, t: f64) -> Point {
+        self.origin + self.direction * t
+    }
+}
+
+impl Transformable for Ray {
+    fn transform(&self, m: &Mat4<f64>) -> Self {
+        Ray {
+            origin: m.mul_tuple(&self.origin),
+            direction: m.mul_tuple(&self.direction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn position_along_ray() {
+        let ray = Ray::new(Point::new(2.0, 3.0, 4.0), V3D::new(1.0, 0.0, 0.0));
+        assert_eq!(ray.position(0.0), Point::new(2.0, 3.0, 4.0));
+        assert_eq!(ray.position(-1.0), Point::new(1.0, 3.0, 4.0));
+        assert_eq!(ray.position(2.5), Point::new(4.5, 3.0, 4.0));
+    }
+
+    #[test]
+    fn translate_ray() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), V3D::new(0.0, 1.0, 0.0));
+        let moved = ray.transform(&Mat4::translation(3.0, 4.0, 5.0));
+        assert_eq!(moved.origin, Point::new(4.0, 6.0, 8.0));
+        assert_eq!(moved.direction, V3D::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn scale_ray() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), V3D::new(0.0, 1.0, 0.0));
+        let scaled = ray.transform(&Mat4::scaling(2.0, 3.0, 4.0));
+        assert_eq!(scaled.origin, Point::new(2.0, 6.0, 12.0));
+        assert_eq!(scaled.direction, V3D::new(0.0, 3.0, 0.0));
+    }
+}
@@ -0,0 +1,491 @@
+use super::tone_mapping::ToneMapping;
+use crate::math::color::Color;
+use serde::Serialize;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use wasm_bindgen::prelude::*;
+
+/// Anything that can produce the color of a single sample of a pixel.
+/// `sample` is the index of the sample inside that pixel, so samplers can
+/// derive their jitter from it and stay deterministic.
+pub trait PixelSampler: Sync {
+    fn sample(&self, x: usize, y: usize, sample: u32) -> Color;
+}
+
+impl<F> PixelSampler for F
+where
+    F: Fn(usize, usize, u32) -> Color + Sync,
+{
+    fn sample(&self, x: usize, y: usize, sample: u32) -> Color {
+        self(x, y, sample)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub tile_size: usize,
+    pub max_samples: u32,
+    pub tone_mapping: ToneMapping,
+}
+
+impl RenderSettings {
+    pub fn new(width: usize, height: usize) -> Self {
+        RenderSettings {
+            width,
+            height,
+            tile_size: 32,
+            max_samples: 16,
+            tone_mapping: ToneMapping::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Tile {
+    fn split(settings: &RenderSettings) -> Vec<Tile> {
+        let size = settings.tile_size.max(1);
+        let mut tiles = Vec::new();
+        for y in (0..settings.height).step_by(size) {
+            for x in (0..settings.width).step_by(size) {
+                tiles.push(Tile {
+                    x,
+                    y,
+                    width: size.min(settings.width - x),
+                    height: size.min(settings.height - y),
+                });
+            }
+        }
+        tiles
+    }
+
+    fn render(
+        &self,
+        sampler: &impl PixelSampler,
+        first_sample: u32,
+        samples: u32,
+        cancel: &CancelHandle,
+    ) -> Option<Vec<Color>> {
+        let mut colors = Vec::with_capacity(self.width * self.height);
+        for y in self.y..self.y + self.height {
+            //checking once per row keeps cancellation responsive on big tiles
+            if cancel.is_cancelled() {
+                return None;
+            }
+            for x in self.x..self.x + self.width {
+                let mut color = Color::black();
+                for sample in first_sample..first_sample + samples {
+                    color += sampler.sample(x, y, sample);
+                }
+                colors.push(color);
+            }
+        }
+        Some(colors)
+    }
+}
+
+/// Cheap to clone handle that can stop a render from another thread
+/// (or from a js callback between two calls to [`ProgressiveRender::step`]).
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Progress {
+    pub pass: usize,
+    pub samples_per_pixel: u32,
+    pub max_samples: u32,
+    pub tiles_done: usize,
+    pub tiles_total: usize,
+}
+
+impl Progress {
+    /// Fraction of the whole render (all passes) that is finished, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        if self.tiles_total == 0 || self.max_samples == 0 {
+            return 1.0;
+        }
+        let next_target = 1u32
+            .checked_shl(self.pass as u32)
+            .unwrap_or(u32::MAX)
+            .min(self.max_samples);
+        let pass_fraction = self.tiles_done as f64 / self.tiles_total as f64;
+        let samples = self.samples_per_pixel as f64
+            + (next_target - self.samples_per_pixel.min(next_target)) as f64 * pass_fraction;
+        samples / self.max_samples as f64
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderState {
+    InProgress,
+    Done,
+    Cancelled,
+}
+
+/// Renders an image tile by tile in passes: the first pass takes a single
+/// sample per pixel and every following pass doubles the samples until
+/// `max_samples` is reached, so a rough image is available very early.
+pub struct ProgressiveRender<S: PixelSampler> {
+    sampler: S,
+    settings: RenderSettings,
+    tiles: Vec<Tile>,
+    tile_samples: Vec<u32>,
+    accumulator: Vec<Color>,
+    samples_done: u32,
+    pass: usize,
+    next_tile: usize,
+    cancel: CancelHandle,
+    on_progress: Option<Box<dyn FnMut(Progress)>>,
+}
+
+impl<S: PixelSampler> ProgressiveRender<S> {
+    pub fn new(sampler: S, settings: RenderSettings) -> Self {
+        let tiles = Tile::split(&settings);
+        ProgressiveRender {
+            sampler,
+            tile_samples: vec![0; tiles.len()],
+            tiles,
+            accumulator: vec![Color::black(); settings.width * settings.height],
+            samples_done: 0,
+            pass: 0,
+            next_tile: 0,
+            cancel: CancelHandle::default(),
+            on_progress: None,
+            settings,
+        }
+    }
+
+    pub fn settings(&self) -> &RenderSettings {
+        &self.settings
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    pub fn on_progress(&mut self, callback: impl FnMut(Progress) + 'static) {
+        self.on_progress = Some(Box::new(callback));
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            pass: self.pass,
+            samples_per_pixel: self.samples_done,
+            max_samples: self.settings.max_samples,
+            tiles_done: self.next_tile,
+            tiles_total: self.tiles.len(),
+        }
+    }
+
+    pub fn state(&self) -> RenderState {
+        if self.cancel.is_cancelled() {
+            RenderState::Cancelled
+        } else if self.samples_done >= self.settings.max_samples || self.tiles.is_empty() {
+            RenderState::Done
+        } else {
+            RenderState::InProgress
+        }
+    }
+
+    /// Renders at most `max_tiles` tiles of the current pass and returns.
+    /// Natively the tiles are rendered in parallel, in wasm they are rendered
+    /// one after the other, so a small `max_tiles` keeps each call short enough
+    /// to be driven from `requestAnimationFrame`.
+    pub fn step(&mut self, max_tiles: usize) -> RenderState {
+        if self.state() != RenderState::InProgress {
+            return self.state();
+        }
+        let target = self.pass_target();
+        let first_sample = self.samples_done;
+        let end = (self.next_tile + max_tiles.max(1)).min(self.tiles.len());
+        let batch = &self.tiles[self.next_tile..end];
+        let rendered = render_batch(
+            &self.sampler,
+            batch,
+            first_sample,
+            target - first_sample,
+            &self.cancel,
+        );
+        let mut finished = 0;
+        for (offset, colors) in rendered.into_iter().enumerate() {
+            let Some(colors) = colors else {
+                continue;
+            };
+            let index = self.next_tile + offset;
+            self.accumulate(index, &colors);
+            self.tile_samples[index] = target;
+            finished += 1;
+        }
+        if finished < end - self.next_tile {
+            return RenderState::Cancelled;
+        }
+        self.next_tile = end;
+        if self.next_tile == self.tiles.len() {
+            self.samples_done = target;
+            self.next_tile = 0;
+            self.pass += 1;
+        }
+        let progress = self.progress();
+        if let Some(callback) = self.on_progress.as_mut() {
+            callback(progress);
+        }
+        self.state()
+    }
+
+    /// Runs the render to completion, or until it is cancelled.
+    pub fn render(&mut self) -> RenderState {
+        let tiles_per_step = 2 * threads();
+        loop {
+            let state = self.step(tiles_per_step);
+            if state != RenderState::InProgress {
+                return state;
+            }
+        }
+    }
+
+    /// Average color of a pixel over the samples it received so far.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        let samples = self.samples_at(x, y)?;
+        if samples == 0 {
+            return None;
+        }
+        Some(self.accumulator[y * self.settings.width + x] / samples as f64)
+    }
+
+    /// The current HDR estimate of every pixel, black where nothing was sampled yet.
+    pub fn hdr_pixels(&self) -> Vec<Color> {
+        let (width, height) = (self.settings.width, self.settings.height);
+        (0..width * height)
+            .map(|index| self.pixel(index % width, index / width).unwrap_or_default())
+            .collect()
+    }
+
+    /// Writes the current estimate, tone mapped, into a canvas of the same
+    /// size. Pixels that have not received any sample yet are left untouched.
+    pub fn write_to(&self, canvas: &mut [u32]) {
+        let tone_mapping = self.settings.tone_mapping;
+        for y in 0..self.settings.height {
+            for x in 0..self.settings.width {
+                if let (Some(color), Some(pixel)) = (
+                    self.pixel(x, y),
+                    canvas.get_mut(y * self.settings.width + x),
+                ) {
+                    *pixel = tone_mapping.to_u32(color);
+                }
+            }
+        }
+    }
+
+    fn samples_at(&self, x: usize, y: usize) -> Option<u32> {
+        if x >= self.settings.width || y >= self.settings.height {
+            return None;
+        }
+        let size = self.settings.tile_size.max(1);
+        let tiles_per_row = self.settings.width.div_ceil(size);
+        let tile = (y / size) * tiles_per_row + x / size;
+        self.tile_samples.get(tile).copied()
+    }
+
+    fn pass_target(&self) -> u32 {
+        let doubled = 1u32.checked_shl(self.pass as u32).unwrap_or(u32::MAX);
+        doubled.min(self.settings.max_samples)
+    }
+
+    fn accumulate(&mut self, index: usize, colors: &[Color]) {
+        let tile = self.tiles[index];
+        let mut colors = colors.iter();
+        for y in tile.y..tile.y + tile.height {
+            let row = y * self.settings.width;
+            for pixel in &mut self.accumulator[row + tile.x..row + tile.x + tile.width] {
+                if let Some(color) = colors.next() {
+                    *pixel += *color;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn threads() -> usize {
+    1
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn render_batch(
+    sampler: &impl PixelSampler,
+    batch: &[Tile],
+    first_sample: u32,
+    samples: u32,
+    cancel: &CancelHandle,
+) -> Vec<Option<Vec<Color>>> {
+    let workers = threads().min(batch.len()).max(1);
+    let mut results: Vec<Option<Vec<Color>>> = vec![None; batch.len()];
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|worker| {
+                scope.spawn(move || {
+                    batch
+                        .iter()
+                        .enumerate()
+                        .skip(worker)
+                        .step_by(workers)
+                        .map(|(index, tile)| {
+                            (index, tile.render(sampler, first_sample, samples, cancel))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            for (index, colors) in handle.join().expect("render thread panicked") {
+                results[index] = colors;
+            }
+        }
+    });
+    results
+}
+
+#[cfg(target_arch = "wasm32")]
+fn render_batch(
+    sampler: &impl PixelSampler,
+    batch: &[Tile],
+    first_sample: u32,
+    samples: u32,
+    cancel: &CancelHandle,
+) -> Vec<Option<Vec<Color>>> {
+    batch
+        .iter()
+        .map(|tile| tile.render(sampler, first_sample, samples, cancel))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tracer::tone_mapping::ToneOperator;
+    use std::{cell::RefCell, rc::Rc};
+
+    fn settings(width: usize, height: usize) -> RenderSettings {
+        RenderSettings {
+            width,
+            height,
+            tile_size: 4,
+            max_samples: 4,
+            tone_mapping: ToneMapping::default(),
+        }
+    }
+
+    #[test]
+    fn tiles_cover_the_image() {
+        let tiles = Tile::split(&settings(10, 6));
+        assert_eq!(tiles.len(), 6);
+        let area: usize = tiles.iter().map(|t| t.width * t.height).sum();
+        assert_eq!(area, 60);
+        assert_eq!(tiles.last().map(|t| (t.width, t.height)), Some((2, 2)));
+    }
+
+    #[test]
+    fn renders_whole_image() {
+        let sampler = |x: usize, _y: usize, _s: u32| Color::new(x as f64 / 10.0, 0.0, 1.0);
+        let mut render = ProgressiveRender::new(sampler, settings(10, 6));
+        assert_eq!(render.render(), RenderState::Done);
+        let mut canvas = vec![0u32; 60];
+        render.write_to(&mut canvas);
+        assert_eq!(canvas[5], Color::new(0.5, 0.0, 1.0).to_u32());
+        assert_eq!(canvas[59], Color::new(0.9, 0.0, 1.0).to_u32());
+    }
+
+    #[test]
+    fn averages_every_sample() {
+        //samples 0..4 average to 0.375 per channel
+        let sampler = |_x: usize, _y: usize, s: u32| Color::white() * (s as f64 / 4.0);
+        let mut render = ProgressiveRender::new(sampler, settings(4, 4));
+        render.render();
+        assert_eq!(render.pixel(1, 1), Some(Color::white() * 0.375));
+    }
+
+    #[test]
+    fn passes_double_the_samples() {
+        let sampler = |_x: usize, _y: usize, _s: u32| Color::white();
+        let mut render = ProgressiveRender::new(sampler, settings(8, 8));
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_clone = seen.clone();
+        render.on_progress(move |progress| {
+            if progress.tiles_done == 0 {
+                seen_clone.borrow_mut().push(progress.samples_per_pixel);
+            }
+        });
+        render.render();
+        assert_eq!(*seen.borrow(), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn keeps_hdr_values_until_tone_mapping() {
+        let sampler = |_x: usize, _y: usize, _s: u32| Color::white() * 3.0;
+        let mut render = ProgressiveRender::new(
+            sampler,
+            RenderSettings {
+                tone_mapping: ToneMapping {
+                    operator: ToneOperator::Reinhard,
+                    ..ToneMapping::default()
+                },
+                ..settings(4, 4)
+            },
+        );
+        render.render();
+        assert_eq!(render.hdr_pixels()[0], Color::white() * 3.0);
+        let mut canvas = vec![0u32; 16];
+        render.write_to(&mut canvas);
+        assert_eq!(canvas[0], (Color::white() * 0.75).to_u32());
+    }
+
+    #[test]
+    fn step_is_bounded() {
+        let sampler = |_x: usize, _y: usize, _s: u32| Color::white();
+        let mut render = ProgressiveRender::new(sampler, settings(8, 8));
+        assert_eq!(render.step(1), RenderState::InProgress);
+        assert_eq!(render.progress().tiles_done, 1);
+        assert_eq!(render.pixel(0, 0), Some(Color::white()));
+        assert_eq!(render.pixel(7, 7), None);
+    }
+
+    #[test]
+    fn cancel_stops_the_render() {
+        let sampler = |_x: usize, _y: usize, _s: u32| Color::white();
+        let mut render = ProgressiveRender::new(sampler, settings(8, 8));
+        render.step(1);
+        render.cancel_handle().cancel();
+        assert_eq!(render.render(), RenderState::Cancelled);
+        assert_eq!(render.progress().tiles_done, 1);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::math::point_vec::{TupleLike, V3D};
+use std::f64::consts::PI;
+
+/// Small deterministic generator (splitmix64). Every pixel sample seeds its
+/// own generator, so renders are reproducible no matter how tiles are
+/// scheduled between threads.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn for_sample(seed: u64, x: usize, y: usize, sample: u32) -> Rng {
+        let mut rng = Rng::new(seed);
+        for value in [x as u64, y as u64, sample as u64] {
+            rng.state ^= value;
+            rng.next_u64();
+        }
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform number in `0..1`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Two unit vectors perpendicular to `n` and to each other.
+pub fn orthonormal_basis(n: &V3D) -> (V3D, V3D) {
+    let helper = if n.x.abs() > 0.9 {
+        V3D::new(0.0, 1.0, 0.0)
+    } else {
+        V3D::new(1.0, 0.0, 0.0)
+    };
+    let tangent = helper.cross(n).normalize();
+    let bitangent = n.cross(&tangent);
+    (tangent, bitangent)
+}
+
+/// Direction in the hemisphere around `normal` with a pdf of `cos(theta) / pi`.
+pub fn cosine_hemisphere(normal: &V3D, u1: f64, u2: f64) -> V3D {
+    let r = u1.sqrt();
+    let phi = 2.0 * PI * u2;
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    let z = (1.0 - u1).max(0.0).sqrt();
+    (tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + *normal * z).normalize()
+}
+
+/// Maps the unit square onto the unit disk (Shirley and Chiu's concentric
+/// mapping), it keeps strata intact so lens samples stay evenly spread.
+pub fn concentric_disk(u1: f64, u2: f64) -> (f64, f64) {
+    let (a, b) = (2.0 * u1 - 1.0, 2.0 * u2 - 1.0);
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, PI / 4.0 * (b / a))
+    } else {
+        (b, PI / 2.0 - PI / 4.0 * (a / b))
+    };
+    (r * theta.cos(), r * theta.sin())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = Rng::for_sample(7, 3, 4, 5);
+        let mut b = Rng::for_sample(7, 3, 4, 5);
+        let mut c = Rng::for_sample(7, 4, 3, 5);
+        let xs: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..4).map(|_| b.next_u64()).collect();
+        let zs: Vec<u64> = (0..4).map(|_| c.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn uniform_numbers_in_range() {
+        let mut rng = Rng::new(1);
+        let values: Vec<f64> = (0..1000).map(|_| rng.next_f64()).collect();
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!((mean - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn cosine_samples_stay_in_the_hemisphere() {
+        let normal = V3D::new(0.0, 0.0, -1.0);
+        let mut rng = Rng::new(3);
+        let n = 4000;
+        let mut cos_sum = 0.0;
+        for _ in 0..n {
+            let dir = cosine_hemisphere(&normal, rng.next_f64(), rng.next_f64());
+            let cos = dir.dot(&normal);
+            assert!(cos >= 0.0);
+            assert!((dir.magnitude() - 1.0).abs() < 1e-9);
+            cos_sum += cos;
+        }
+        //E[cos] for a cosine weighted hemisphere is 2/3
+        assert!((cos_sum / n as f64 - 2.0 / 3.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn disk_samples_stay_in_the_disk() {
+        assert_eq!(concentric_disk(0.5, 0.5), (0.0, 0.0));
+        let (x, y) = concentric_disk(1.0, 0.5);
+        assert!((x - 1.0).abs() < 1e-12 && y.abs() < 1e-12);
+        let mut rng = Rng::new(11);
+        for _ in 0..1000 {
+            let (x, y) = concentric_disk(rng.next_f64(), rng.next_f64());
+            assert!(x * x + y * y <= 1.0 + 1e-12);
+        }
+    }
+}
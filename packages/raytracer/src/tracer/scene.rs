@@ -0,0 +1,148 @@
+use super::{
+    camera::Camera,
+    path::{trace_path, PathSettings},
+    render::PixelSampler,
+    sampling::Rng,
+    world::{World, MAX_DEPTH},
+};
+use crate::math::color::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    /// Classic recursive tracing: Phong shading, hard shadows, mirror
+    /// reflections and refractions.
+    Whitted,
+    /// Monte Carlo path tracing, it needs many samples per pixel to converge.
+    Path(PathSettings),
+}
+
+pub struct Scene {
+    pub world: World,
+    pub camera: Camera,
+    pub max_depth: usize,
+    pub mode: RenderMode,
+}
+
+impl Scene {
+    pub fn new(world: World, camera: Camera) -> Scene {
+        Scene {
+            world,
+            camera,
+            max_depth: MAX_DEPTH,
+            mode: RenderMode::Whitted,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: RenderMode) -> Scene {
+        self.mode = mode;
+        self
+    }
+}
+
+impl PixelSampler for Scene {
+    fn sample(&self, x: usize, y: usize, sample: u32) -> Color {
+        let (dx, dy) = subpixel_offset(sample);
+        let (px, py) = (x as f64 + dx, y as f64 + dy);
+        let ray = match self.camera.lens() {
+            Some(lens) => {
+                let mut rng = Rng::for_sample(lens.seed ^ LENS_STREAM, x, y, sample);
+                self.camera
+                    .ray_through_lens(px, py, rng.next_f64(), rng.next_f64())
+            }
+            None => self.camera.ray_through(px, py),
+        };
+        match &self.mode {
+            RenderMode::Whitted => self.world.color_at(&ray, self.max_depth),
+            RenderMode::Path(settings) => {
+                let mut rng = Rng::for_sample(settings.seed, x, y, sample);
+                trace_path(&self.world, &ray, settings, &mut rng)
+            }
+        }
+    }
+}
+
+/// Keeps lens samples uncorrelated with the path tracer's, even when both
+/// use the same seed.
+const LENS_STREAM: u64 = 0x6c65_6e73;
+
+/// Position inside the pixel for the n-th sample, taken from the R2
+/// low discrepancy sequence so the first sample lands on the pixel center
+/// and the following ones spread evenly without any random state.
+pub fn subpixel_offset(sample: u32) -> (f64, f64) {
+    //1/g and 1/g² where g is the plastic number
+    const A1: f64 = 0.754_877_666_246_692_8;
+    const A2: f64 = 0.569_840_290_998_053_3;
+    let n = sample as f64;
+    ((0.5 + A1 * n).fract(), (0.5 + A2 * n).fract())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{
+            matrices::Mat4,
+            point_vec::{Point, V3D},
+        },
+        tracer::{camera::Lens, world::test::default_world},
+    };
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn first_sample_is_the_pixel_center() {
+        assert_eq!(subpixel_offset(0), (0.5, 0.5));
+        for sample in 1..64 {
+            let (dx, dy) = subpixel_offset(sample);
+            assert!((0.0..1.0).contains(&dx) && (0.0..1.0).contains(&dy));
+        }
+    }
+
+    #[test]
+    fn path_mode_is_reproducible() {
+        let camera = Camera::new(11, 11, FRAC_PI_2).with_transform(Mat4::view_transform(
+            &Point::new(0.0, 0.0, -5.0),
+            &Point::new(0.0, 0.0, 0.0),
+            &V3D::new(0.0, 1.0, 0.0),
+        ));
+        let scene = Scene::new(default_world(), camera)
+            .with_mode(RenderMode::Path(PathSettings::default()));
+        let a = scene.sample(5, 5, 3);
+        let b = scene.sample(5, 5, 3);
+        assert_eq!(a, b);
+        assert!(a.g > 0.0);
+    }
+
+    #[test]
+    fn samples_the_world_through_the_camera() {
+        let camera = Camera::new(11, 11, FRAC_PI_2).with_transform(Mat4::view_transform(
+            &Point::new(0.0, 0.0, -5.0),
+            &Point::new(0.0, 0.0, 0.0),
+            &V3D::new(0.0, 1.0, 0.0),
+        ));
+        let scene = Scene::new(default_world(), camera);
+        assert_eq!(scene.sample(5, 5, 0), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn depth_of_field_is_reproducible() {
+        let camera = |seed| {
+            Camera::new(11, 11, FRAC_PI_2)
+                .with_transform(Mat4::view_transform(
+                    &Point::new(0.0, 0.0, -5.0),
+                    &Point::new(0.0, 0.0, 0.0),
+                    &V3D::new(0.0, 1.0, 0.0),
+                ))
+                .with_lens(Lens {
+                    aperture: 2.0,
+                    focal_distance: 20.0,
+                    seed,
+                })
+        };
+        let scene = Scene::new(default_world(), camera(1));
+        let other = Scene::new(default_world(), camera(2));
+        //the edge of the sphere is out of focus, so the lens sample decides the color
+        let samples = |scene: &Scene| (0..8).map(|n| scene.sample(4, 5, n)).collect::<Vec<_>>();
+        assert_eq!(samples(&scene), samples(&scene));
+        assert_ne!(samples(&scene), samples(&other));
+    }
+}
@@ -0,0 +1,131 @@
+use super::Object;
+use crate::tracer::intersection::Intersection;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    /// `left_hit` tells if the intersection is on the left operand, `in_left`
+    /// and `in_right` if the ray is currently inside each operand.
+    pub fn allows(&self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+        }
+    }
+
+    /// Keeps only the intersections that lie on the surface of the combined
+    /// shape. `xs` must be sorted by `t`.
+    pub fn filter<'a>(&self, left: &Object, xs: Vec<Intersection<'a>>) -> Vec<Intersection<'a>> {
+        let mut in_left = false;
+        let mut in_right = false;
+        xs.into_iter()
+            .filter(|i| {
+                let left_hit = left.includes(i.object);
+                let allowed = self.allows(left_hit, in_left, in_right);
+                if left_hit {
+                    in_left = !in_left;
+                } else {
+                    in_right = !in_right;
+                }
+                allowed
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        math::{
+            matrices::Mat4,
+            point_vec::{Point, V3D},
+        },
+        tracer::ray::Ray,
+    };
+
+    #[test]
+    fn operation_rules() {
+        use CsgOperation::*;
+        //(left_hit, in_left, in_right) for every combination
+        let cases = [
+            (true, true, true),
+            (true, true, false),
+            (true, false, true),
+            (true, false, false),
+            (false, true, true),
+            (false, true, false),
+            (false, false, true),
+            (false, false, false),
+        ];
+        let expected = [
+            (Union, [false, true, false, true, false, false, true, true]),
+            (
+                Intersection,
+                [true, false, true, false, true, true, false, false],
+            ),
+            (
+                Difference,
+                [false, true, false, true, true, true, false, false],
+            ),
+        ];
+        for (operation, results) in expected {
+            for ((l, il, ir), result) in cases.iter().zip(results) {
+                assert_eq!(operation.allows(*l, *il, *ir), result);
+            }
+        }
+    }
+
+    #[test]
+    fn filters_intersections() {
+        let csg = |op| Object::csg(op, Object::sphere(), Object::cube());
+        let expected = [
+            (CsgOperation::Union, (0, 3)),
+            (CsgOperation::Intersection, (1, 2)),
+            (CsgOperation::Difference, (0, 1)),
+        ];
+        for (op, (first, second)) in expected {
+            let shape = csg(op);
+            let (left, right) = shape.operands().unwrap();
+            let xs = vec![
+                Intersection::new(1.0, left),
+                Intersection::new(2.0, right),
+                Intersection::new(3.0, left),
+                Intersection::new(4.0, right),
+            ];
+            let filtered = op.filter(left, xs.clone());
+            assert_eq!(filtered.len(), 2);
+            assert_eq!(filtered[0].t, xs[first].t);
+            assert_eq!(filtered[1].t, xs[second].t);
+        }
+    }
+
+    #[test]
+    fn ray_misses_csg() {
+        let shape = Object::csg(CsgOperation::Union, Object::sphere(), Object::cube());
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert!(shape.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn ray_hits_csg() {
+        let right = Object::sphere().with_transform(Mat4::translation(0.0, 0.0, 0.5));
+        let shape = Object::csg(CsgOperation::Union, Object::sphere(), right);
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        let xs = shape.intersect(&ray);
+        let (left, right) = shape.operands().unwrap();
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert!(std::ptr::eq(xs[0].object, left));
+        assert_eq!(xs[1].t, 6.5);
+        assert!(std::ptr::eq(xs[1].object, right));
+    }
+}
@@ -0,0 +1,97 @@
+use super::Shape;
+use crate::{
+    math::point_vec::{Point, V3D},
+    tracer::{
+        bounds::{check_axis, BoundingBox},
+        ray::Ray,
+    },
+};
+
+/// Axis aligned cube going from -1 to 1 on every axis.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cube;
+
+impl Shape for Cube {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, -1.0, 1.0);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, -1.0, 1.0);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, -1.0, 1.0);
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+        if tmin > tmax {
+            return vec![];
+        }
+        vec![tmin, tmax]
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    fn local_normal_at(&self, point: &Point) -> V3D {
+        let (x, y, z) = (point.x.abs(), point.y.abs(), point.z.abs());
+        let max = x.max(y).max(z);
+        if max == x {
+            V3D::new(point.x, 0.0, 0.0)
+        } else if max == y {
+            V3D::new(0.0, point.y, 0.0)
+        } else {
+            V3D::new(0.0, 0.0, point.z)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ray_hits_faces() {
+        let cases = [
+            (
+                Point::new(5.0, 0.5, 0.0),
+                V3D::new(-1.0, 0.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, -5.0, 0.0),
+                V3D::new(0.0, 1.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.0, 0.5, 0.0),
+                V3D::new(0.0, 0.0, 1.0),
+                -1.0,
+                1.0,
+            ),
+        ];
+        for (origin, direction, t1, t2) in cases {
+            let xs = Cube.local_intersect(&Ray::new(origin, direction));
+            assert_eq!(xs, vec![t1, t2]);
+        }
+    }
+
+    #[test]
+    fn ray_misses() {
+        let ray = Ray::new(Point::new(-2.0, 0.0, 0.0), V3D::new(0.2673, 0.5345, 0.8018));
+        assert!(Cube.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn normal_on_faces_and_corners() {
+        assert_eq!(
+            Cube.local_normal_at(&Point::new(1.0, 0.5, -0.8)),
+            V3D::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            Cube.local_normal_at(&Point::new(-0.4, 0.3, -1.0)),
+            V3D::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            Cube.local_normal_at(&Point::new(1.0, 1.0, 1.0)),
+            V3D::new(1.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use super::Shape;
+use crate::{
+    math::point_vec::{Point, TupleLike, V3D},
+    tracer::{bounds::BoundingBox, intersection::EPSILON, ray::Ray},
+};
+use std::f64::consts::PI;
+
+/// Cylinder of radius 1 around the y axis, cut at `minimum` and `maximum`
+/// (both exclusive) and optionally capped.
+#[derive(Debug, Clone, Copy)]
+pub struct Cylinder {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Cylinder {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Cylinder {
+    pub fn new(minimum: f64, maximum: f64, closed: bool) -> Cylinder {
+        Cylinder {
+            minimum,
+            maximum,
+            closed,
+        }
+    }
+
+    fn intersect_caps(&self, ray: &Ray, xs: &mut Vec<f64>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+        for cap in [self.minimum, self.maximum] {
+            let t = (cap - ray.origin.y) / ray.direction.y;
+            let x = ray.origin.x + t * ray.direction.x;
+            let z = ray.origin.z + t * ray.direction.z;
+            if x * x + z * z <= 1.0 {
+                xs.push(t);
+            }
+        }
+    }
+}
+
+impl Shape for Cylinder {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let mut xs = Vec::new();
+        let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
+        if a.abs() >= EPSILON {
+            let b = 2.0 * (ray.origin.x * ray.direction.x + ray.origin.z * ray.direction.z);
+            let c = ray.origin.x.powi(2) + ray.origin.z.powi(2) - 1.0;
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return xs;
+            }
+            let sqrt = discriminant.sqrt();
+            for t in [(-b - sqrt) / (2.0 * a), (-b + sqrt) / (2.0 * a)] {
+                let y = ray.origin.y + t * ray.direction.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(t);
+                }
+            }
+        }
+        self.intersect_caps(ray, &mut xs);
+        xs.sort_by(f64::total_cmp);
+        xs
+    }
+
+    fn local_normal_at(&self, point: &Point) -> V3D {
+        let distance = point.x * point.x + point.z * point.z;
+        if distance < 1.0 && point.y >= self.maximum - EPSILON {
+            V3D::new(0.0, 1.0, 0.0)
+        } else if distance < 1.0 && point.y <= self.minimum + EPSILON {
+            V3D::new(0.0, -1.0, 0.0)
+        } else {
+            V3D::new(point.x, 0.0, point.z)
+        }
+    }
+
+    /// Same wrap around the y axis as the sphere, `v` repeats every unit of height.
+    fn local_uv(&self, point: &Point) -> (f64, f64) {
+        let theta = point.x.atan2(point.z);
+        (1.0 - (theta / (2.0 * PI) + 0.5), point.y.rem_euclid(1.0))
+    }
+
+    fn local_tangent(&self, point: &Point, normal: &V3D) -> V3D {
+        if normal.y.abs() > 0.5 {
+            return V3D::new(1.0, 0.0, 0.0);
+        }
+        V3D::new(-point.z, 0.0, point.x).normalize()
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(-1.0, self.minimum, -1.0),
+            Point::new(1.0, self.maximum, 1.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ray_misses() {
+        let cases = [
+            (Point::new(1.0, 0.0, 0.0), V3D::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, -5.0), V3D::new(1.0, 1.0, 1.0)),
+        ];
+        for (origin, direction) in cases {
+            let ray = Ray::new(origin, direction);
+            assert!(Cylinder::default().local_intersect(&ray).is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_strikes_the_side() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(Cylinder::default().local_intersect(&ray), vec![4.0, 6.0]);
+    }
+
+    #[test]
+    fn truncated_cylinder() {
+        let cylinder = Cylinder::new(1.0, 2.0, false);
+        let through = Ray::new(Point::new(0.0, 1.5, -2.0), V3D::new(0.0, 0.0, 1.0));
+        let above = Ray::new(Point::new(0.0, 3.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(cylinder.local_intersect(&through).len(), 2);
+        assert!(cylinder.local_intersect(&above).is_empty());
+    }
+
+    #[test]
+    fn capped_cylinder() {
+        let cylinder = Cylinder::new(1.0, 2.0, true);
+        let ray = Ray::new(Point::new(0.0, 3.0, 0.0), V3D::new(0.0, -1.0, 0.0));
+        assert_eq!(cylinder.local_intersect(&ray), vec![1.0, 2.0]);
+        assert_eq!(
+            cylinder.local_normal_at(&Point::new(0.5, 2.0, 0.0)),
+            V3D::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            cylinder.local_normal_at(&Point::new(0.0, 1.5, -1.0)),
+            V3D::new(0.0, 0.0, -1.0)
+        );
+    }
+}
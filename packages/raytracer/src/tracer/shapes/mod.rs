@@ -0,0 +1,367 @@
+mod csg;
+mod cube;
+mod cylinder;
+mod plane;
+mod sphere;
+mod triangle;
+pub use csg::CsgOperation;
+pub use cube::Cube;
+pub use cylinder::Cylinder;
+pub use plane::Plane;
+pub use sphere::Sphere;
+pub use triangle::Triangle;
+
+use super::{
+    bounds::BoundingBox, intersection::Intersection, material::Material, normal_map::SurfaceFrame,
+    ray::Ray, sampling::orthonormal_basis,
+};
+use crate::math::{
+    matrices::Mat4,
+    point_vec::{Point, TupleLike, V3D},
+    transformable::Transformable,
+};
+use std::{fmt::Debug, sync::Arc};
+
+/// Geometry in its own object space, the [`Object`] wrapping it takes care
+/// of transforms and materials.
+pub trait Shape: Debug + Send + Sync {
+    /// Distances along the (object space) ray where it crosses the shape.
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64>;
+    fn local_normal_at(&self, point: &Point) -> V3D;
+    fn bounds(&self) -> BoundingBox;
+
+    /// Texture coordinates of an object space point on the surface.
+    fn local_uv(&self, _point: &Point) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+
+    /// Direction in which `u` grows, it is the x axis of the tangent space
+    /// normal maps are read in. Shapes without a natural one get an arbitrary
+    /// (but stable) direction perpendicular to the normal.
+    fn local_tangent(&self, _point: &Point, normal: &V3D) -> V3D {
+        orthonormal_basis(normal).0
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ObjectKind {
+    Primitive(Arc<dyn Shape>),
+    Group(Vec<Object>),
+    Csg(CsgOperation, Box<[Object; 2]>),
+}
+
+/// A shape placed in the world. Groups and CSG shapes don't keep a transform
+/// of their own at intersection time: setting one pushes it down into the
+/// leaves, so every intersection already knows its world transform and
+/// normals don't need to walk back up the tree.
+#[derive(Debug, Clone)]
+pub struct Object {
+    kind: ObjectKind,
+    transform: Mat4<f64>,
+    inverse: Mat4<f64>,
+    bounds: BoundingBox,
+    pub material: Material,
+}
+
+impl Object {
+    pub fn new(shape: impl Shape + 'static) -> Object {
+        let bounds = shape.bounds();
+        Object {
+            kind: ObjectKind::Primitive(Arc::new(shape)),
+            transform: Mat4::identity(),
+            inverse: Mat4::identity(),
+            bounds,
+            material: Material::default(),
+        }
+    }
+
+    pub fn sphere() -> Object {
+        Object::new(Sphere)
+    }
+
+    pub fn plane() -> Object {
+        Object::new(Plane)
+    }
+
+    pub fn cube() -> Object {
+        Object::new(Cube)
+    }
+
+    pub fn cylinder(minimum: f64, maximum: f64, closed: bool) -> Object {
+        Object::new(Cylinder::new(minimum, maximum, closed))
+    }
+
+    pub fn triangle(p1: Point, p2: Point, p3: Point) -> Object {
+        Object::new(Triangle::new(p1, p2, p3))
+    }
+
+    pub fn group(children: Vec<Object>) -> Object {
+        let bounds = children
+            .iter()
+            .fold(BoundingBox::empty(), |acc, child| acc.merge(&child.bounds));
+        Object {
+            kind: ObjectKind::Group(children),
+            transform: Mat4::identity(),
+            inverse: Mat4::identity(),
+            bounds,
+            material: Material::default(),
+        }
+    }
+
+    pub fn csg(operation: CsgOperation, left: Object, right: Object) -> Object {
+        let bounds = left.bounds.merge(&right.bounds);
+        Object {
+            kind: ObjectKind::Csg(operation, Box::new([left, right])),
+            transform: Mat4::identity(),
+            inverse: Mat4::identity(),
+            bounds,
+            material: Material::default(),
+        }
+    }
+
+    /// Sets the material of the object, for groups and CSG shapes the
+    /// material is also given to every child.
+    pub fn with_material(mut self, material: Material) -> Object {
+        for child in self.children_mut() {
+            *child = child.clone().with_material(material.clone());
+        }
+        self.material = material;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Mat4<f64>) -> Object {
+        let inverse = transform
+            .inverse()
+            .expect("object transforms must be invertible");
+        //children already carry our old transform, so undo it before applying the new one
+        let relative = transform.clone() * self.inverse.clone();
+        for child in self.children_mut() {
+            *child = child.transform(&relative);
+        }
+        self.bounds = match &self.kind {
+            ObjectKind::Primitive(shape) => shape.bounds().transform(&transform),
+            _ => self
+                .children()
+                .iter()
+                .fold(BoundingBox::empty(), |acc, child| acc.merge(&child.bounds)),
+        };
+        self.transform = transform;
+        self.inverse = inverse;
+        self
+    }
+
+    pub fn transform_matrix(&self) -> &Mat4<f64> {
+        &self.transform
+    }
+
+    /// World space bounds of the object.
+    pub fn bounds(&self) -> &BoundingBox {
+        &self.bounds
+    }
+
+    pub fn children(&self) -> &[Object] {
+        match &self.kind {
+            ObjectKind::Primitive(_) => &[],
+            ObjectKind::Group(children) => children,
+            ObjectKind::Csg(_, operands) => &operands[..],
+        }
+    }
+
+    fn children_mut(&mut self) -> &mut [Object] {
+        match &mut self.kind {
+            ObjectKind::Primitive(_) => &mut [],
+            ObjectKind::Group(children) => children,
+            ObjectKind::Csg(_, operands) => &mut operands[..],
+        }
+    }
+
+    /// Left and right operands when this is a CSG shape.
+    pub fn operands(&self) -> Option<(&Object, &Object)> {
+        match &self.kind {
+            ObjectKind::Csg(_, operands) => Some((&operands[0], &operands[1])),
+            _ => None,
+        }
+    }
+
+    /// True if `other` is this very object or one of its descendants.
+    pub fn includes(&self, other: &Object) -> bool {
+        std::ptr::eq(self, other) || self.children().iter().any(|child| child.includes(other))
+    }
+
+    pub fn world_to_object(&self, point: &Point) -> Point {
+        self.inverse.mul_tuple(point)
+    }
+
+    /// Intersections sorted by `t`, they always point to primitive objects.
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        match &self.kind {
+            ObjectKind::Primitive(shape) => {
+                let local_ray = ray.transform(&self.inverse);
+                shape
+                    .local_intersect(&local_ray)
+                    .into_iter()
+                    .map(|t| Intersection::new(t, self))
+                    .collect()
+            }
+            ObjectKind::Group(children) => {
+                if !self.bounds.intersects(ray) {
+                    return vec![];
+                }
+                let mut xs: Vec<_> = children
+                    .iter()
+                    .flat_map(|child| child.intersect(ray))
+                    .collect();
+                xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+                xs
+            }
+            ObjectKind::Csg(operation, operands) => {
+                if !self.bounds.intersects(ray) {
+                    return vec![];
+                }
+                let [left, right] = &**operands;
+                let mut xs = left.intersect(ray);
+                xs.extend(right.intersect(ray));
+                xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+                operation.filter(left, xs)
+            }
+        }
+    }
+
+    pub fn normal_at(&self, world_point: &Point) -> V3D {
+        let ObjectKind::Primitive(shape) = &self.kind else {
+            panic!("normals are only defined for primitive shapes");
+        };
+        let local_point = self.world_to_object(world_point);
+        let normal = shape.local_normal_at(&local_point).normalize();
+        //perturbations happen in object space, so bumps stick to the shape
+        let local_normal = self.material.shading_normal(&SurfaceFrame {
+            point: local_point,
+            normal,
+            tangent: shape.local_tangent(&local_point, &normal),
+            uv: shape.local_uv(&local_point),
+        });
+        self.inverse
+            .transpose()
+            .mul_tuple(&local_normal)
+            .normalize()
+    }
+}
+
+impl Transformable for Object {
+    fn transform(&self, m: &Mat4<f64>) -> Self {
+        self.clone()
+            .with_transform(m.clone() * self.transform.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2};
+
+    #[test]
+    fn intersect_scaled_sphere() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        let sphere = Object::sphere().with_transform(Mat4::scaling(2.0, 2.0, 2.0));
+        let xs = sphere.intersect(&ray);
+        let ts: Vec<f64> = xs.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![3.0, 7.0]);
+    }
+
+    #[test]
+    fn intersect_translated_sphere() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        let sphere = Object::sphere().with_transform(Mat4::translation(5.0, 0.0, 0.0));
+        assert!(sphere.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn normal_on_translated_sphere() {
+        let sphere = Object::sphere().with_transform(Mat4::translation(0.0, 1.0, 0.0));
+        let n = sphere.normal_at(&Point::new(0.0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        assert_eq!(n, V3D::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn transform_composes_on_the_left() {
+        let sphere = Object::sphere()
+            .with_transform(Mat4::scaling(2.0, 2.0, 2.0))
+            .transform(&Mat4::translation(1.0, 0.0, 0.0));
+        let center = sphere
+            .transform_matrix()
+            .mul_tuple(&Point::new(0.0, 0.0, 0.0));
+        let edge = sphere
+            .transform_matrix()
+            .mul_tuple(&Point::new(1.0, 0.0, 0.0));
+        assert_eq!(center, Point::new(1.0, 0.0, 0.0));
+        assert_eq!(edge, Point::new(3.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn intersect_transformed_group() {
+        let sphere = Object::sphere().with_transform(Mat4::translation(5.0, 0.0, 0.0));
+        let group = Object::group(vec![sphere]).with_transform(Mat4::scaling(2.0, 2.0, 2.0));
+        let ray = Ray::new(Point::new(10.0, 0.0, -10.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(group.intersect(&ray).len(), 2);
+    }
+
+    #[test]
+    fn group_transform_can_be_replaced() {
+        let group = Object::group(vec![Object::sphere()])
+            .with_transform(Mat4::translation(5.0, 0.0, 0.0))
+            .with_transform(Mat4::translation(0.0, 5.0, 0.0));
+        let child = &group.children()[0];
+        let center = child
+            .transform_matrix()
+            .mul_tuple(&Point::new(0.0, 0.0, 0.0));
+        assert_eq!(center, Point::new(0.0, 5.0, 0.0));
+        assert_eq!(group.bounds().min, Point::new(-1.0, 4.0, -1.0));
+    }
+
+    #[test]
+    fn normal_on_child_of_nested_groups() {
+        let sphere = Object::sphere().with_transform(Mat4::translation(5.0, 0.0, 0.0));
+        let inner = Object::group(vec![sphere]).with_transform(Mat4::scaling(1.0, 2.0, 3.0));
+        let outer = Object::group(vec![inner]).with_transform(Mat4::rotation_y(-FRAC_PI_2));
+        let leaf = &outer.children()[0].children()[0];
+        let n = leaf.normal_at(&Point::new(1.7321, 1.1547, -5.5774));
+        let expected = V3D::new(0.2857, 0.4286, -0.8571);
+        assert!((n - expected).magnitude() < 1e-3);
+    }
+
+    #[test]
+    fn group_and_csg_include_their_children() {
+        let csg = Object::csg(CsgOperation::Difference, Object::sphere(), Object::cube());
+        let group = Object::group(vec![csg]);
+        let (left, right) = group.children()[0].operands().unwrap();
+        assert!(group.includes(left));
+        assert!(group.includes(right));
+        assert!(!left.includes(right));
+    }
+
+    #[test]
+    fn sphere_with_a_hole() {
+        let hole = Object::cylinder(-2.0, 2.0, true).with_transform(Mat4::scaling(0.5, 1.0, 0.5));
+        let shape = Object::csg(CsgOperation::Difference, Object::sphere(), hole);
+        let through_hole = Ray::new(Point::new(0.0, 5.0, 0.0), V3D::new(0.0, -1.0, 0.0));
+        assert!(shape.intersect(&through_hole).is_empty());
+        let ray = Ray::new(Point::new(0.75, 5.0, 0.0), V3D::new(0.0, -1.0, 0.0));
+        assert_eq!(shape.intersect(&ray).len(), 2);
+    }
+
+    #[test]
+    fn bump_follows_the_object_transform() {
+        use crate::tracer::normal_map::NormalPerturbation;
+        let material = Material {
+            normal_perturbation: Some(NormalPerturbation::bump(|p: &Point| p.x, 1.0)),
+            ..Material::default()
+        };
+        let plane = Object::plane().with_material(material);
+        let n = plane.normal_at(&Point::new(0.0, 0.0, 0.0));
+        assert_eq!(n, V3D::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0));
+        //a quarter turn around y moves the slope from x to z
+        let turned = plane.with_transform(Mat4::rotation_y(-FRAC_PI_2));
+        let n = turned.normal_at(&Point::new(0.0, 0.0, 0.0));
+        assert!(n.x.abs() < 1e-4 && n.z.abs() > 0.7);
+    }
+}
@@ -0,0 +1,66 @@
+use super::Shape;
+use crate::{
+    math::point_vec::{Point, V3D},
+    tracer::{bounds::BoundingBox, intersection::EPSILON, ray::Ray},
+};
+
+/// Infinite xz plane.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Plane;
+
+impl Shape for Plane {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        if ray.direction.y.abs() < EPSILON {
+            return vec![];
+        }
+        vec![-ray.origin.y / ray.direction.y]
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
+
+    fn local_normal_at(&self, _point: &Point) -> V3D {
+        V3D::new(0.0, 1.0, 0.0)
+    }
+
+    /// Repeats every unit along x and z.
+    fn local_uv(&self, point: &Point) -> (f64, f64) {
+        (point.x.rem_euclid(1.0), point.z.rem_euclid(1.0))
+    }
+
+    fn local_tangent(&self, _point: &Point, _normal: &V3D) -> V3D {
+        V3D::new(1.0, 0.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parallel_ray_misses() {
+        let ray = Ray::new(Point::new(0.0, 10.0, 0.0), V3D::new(0.0, 0.0, 1.0));
+        assert!(Plane.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn ray_from_above() {
+        let ray = Ray::new(Point::new(0.0, 1.0, 0.0), V3D::new(0.0, -1.0, 0.0));
+        assert_eq!(Plane.local_intersect(&ray), vec![1.0]);
+    }
+
+    #[test]
+    fn normal_is_constant() {
+        let n = Plane.local_normal_at(&Point::new(10.0, 0.0, -10.0));
+        assert_eq!(n, V3D::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn uv_repeats() {
+        assert_eq!(Plane.local_uv(&Point::new(-0.25, 0.0, 1.5)), (0.75, 0.5));
+    }
+}
@@ -0,0 +1,103 @@
+use super::Shape;
+use crate::{
+    math::point_vec::{Point, TupleLike, V3D},
+    tracer::{bounds::BoundingBox, intersection::EPSILON, ray::Ray, sampling::orthonormal_basis},
+};
+use std::f64::consts::PI;
+
+/// Unit sphere centered at the origin.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sphere;
+
+impl Shape for Sphere {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let sphere_to_ray = ray.origin - Point::new(0.0, 0.0, 0.0);
+        let a = ray.direction.dot(&ray.direction);
+        let b = 2.0 * ray.direction.dot(&sphere_to_ray);
+        let c = sphere_to_ray.dot(&sphere_to_ray) - 1.0;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return vec![];
+        }
+        let sqrt = discriminant.sqrt();
+        vec![(-b - sqrt) / (2.0 * a), (-b + sqrt) / (2.0 * a)]
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    fn local_normal_at(&self, point: &Point) -> V3D {
+        *point - Point::new(0.0, 0.0, 0.0)
+    }
+
+    /// Spherical mapping, `u` goes once around the y axis and `v` from the
+    /// south pole to the north pole.
+    fn local_uv(&self, point: &Point) -> (f64, f64) {
+        let theta = point.x.atan2(point.z);
+        let radius = (*point - Point::new(0.0, 0.0, 0.0)).magnitude();
+        let phi = (point.y / radius).clamp(-1.0, 1.0).acos();
+        let u = 1.0 - (theta / (2.0 * PI) + 0.5);
+        (u, 1.0 - phi / PI)
+    }
+
+    fn local_tangent(&self, point: &Point, normal: &V3D) -> V3D {
+        //at the poles every direction is as good as any other
+        if point.x.abs() < EPSILON && point.z.abs() < EPSILON {
+            return orthonormal_basis(normal).0;
+        }
+        V3D::new(-point.z, 0.0, point.x).normalize()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ray_through_center() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(Sphere.local_intersect(&ray), vec![4.0, 6.0]);
+    }
+
+    #[test]
+    fn ray_tangent() {
+        let ray = Ray::new(Point::new(0.0, 1.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(Sphere.local_intersect(&ray), vec![5.0, 5.0]);
+    }
+
+    #[test]
+    fn ray_misses() {
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert!(Sphere.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn ray_inside() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(Sphere.local_intersect(&ray), vec![-1.0, 1.0]);
+    }
+
+    #[test]
+    fn normal_points_outwards() {
+        let n = Sphere.local_normal_at(&Point::new(1.0, 0.0, 0.0));
+        assert_eq!(n, V3D::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn uv_mapping() {
+        let uv = |x, y, z| Sphere.local_uv(&Point::new(x, y, z));
+        assert_eq!(uv(0.0, 0.0, -1.0), (0.0, 0.5));
+        assert_eq!(uv(1.0, 0.0, 0.0), (0.25, 0.5));
+        assert_eq!(uv(0.0, 1.0, 0.0).1, 1.0);
+        assert_eq!(uv(0.0, -1.0, 0.0).1, 0.0);
+    }
+
+    #[test]
+    fn tangent_follows_u() {
+        let point = Point::new(0.0, 0.0, -1.0);
+        let tangent = Sphere.local_tangent(&point, &Sphere.local_normal_at(&point));
+        let (u, _) = Sphere.local_uv(&(point + tangent * 1e-3));
+        assert!(u > 0.0 && u < 0.01);
+    }
+}
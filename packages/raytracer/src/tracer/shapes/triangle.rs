@@ -0,0 +1,196 @@
+use super::Shape;
+use crate::{
+    math::point_vec::{Point, TupleLike, V3D},
+    tracer::{bounds::BoundingBox, intersection::EPSILON, ray::Ray, sampling::orthonormal_basis},
+};
+
+/// Single mesh face. Vertex normals make it smooth shaded and vertex uvs
+/// give it texture coordinates and a tangent space for normal maps.
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    e1: V3D,
+    e2: V3D,
+    normal: V3D,
+    normals: Option<[V3D; 3]>,
+    uvs: Option<[(f64, f64); 3]>,
+    tangent: Option<V3D>,
+}
+
+impl Triangle {
+    pub fn new(p1: Point, p2: Point, p3: Point) -> Triangle {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        Triangle {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(&e1).normalize(),
+            normals: None,
+            uvs: None,
+            tangent: None,
+        }
+    }
+
+    pub fn with_normals(mut self, normals: [V3D; 3]) -> Triangle {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Triangle {
+        let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+        let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+        let det = du1 * dv2 - du2 * dv1;
+        //degenerate uvs have no tangent, fall back to the arbitrary one
+        self.tangent = (det.abs() > EPSILON)
+            .then(|| ((self.e1 * dv2 - self.e2 * dv1) * (1.0 / det)).normalize());
+        self.uvs = Some(uvs);
+        self
+    }
+
+    pub fn normals(&self) -> Option<&[V3D; 3]> {
+        self.normals.as_ref()
+    }
+
+    pub fn uvs(&self) -> Option<&[(f64, f64); 3]> {
+        self.uvs.as_ref()
+    }
+
+    /// Weights of the three vertices at a point on the triangle.
+    pub fn barycentric(&self, point: &Point) -> (f64, f64, f64) {
+        let p = *point - self.p1;
+        let (d00, d01, d11) = (
+            self.e1.dot(&self.e1),
+            self.e1.dot(&self.e2),
+            self.e2.dot(&self.e2),
+        );
+        let (d20, d21) = (p.dot(&self.e1), p.dot(&self.e2));
+        let denom = d00 * d11 - d01 * d01;
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        (1.0 - v - w, v, w)
+    }
+}
+
+impl Shape for Triangle {
+    /// Möller–Trumbore.
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let dir_cross_e2 = ray.direction.cross(&self.e2);
+        let det = self.e1.dot(&dir_cross_e2);
+        if det.abs() < EPSILON {
+            return vec![];
+        }
+        let f = 1.0 / det;
+        let p1_to_origin = ray.origin - self.p1;
+        let u = f * p1_to_origin.dot(&dir_cross_e2);
+        if !(0.0..=1.0).contains(&u) {
+            return vec![];
+        }
+        let origin_cross_e1 = p1_to_origin.cross(&self.e1);
+        let v = f * ray.direction.dot(&origin_cross_e1);
+        if v < 0.0 || u + v > 1.0 {
+            return vec![];
+        }
+        vec![f * self.e2.dot(&origin_cross_e1)]
+    }
+
+    fn local_normal_at(&self, point: &Point) -> V3D {
+        match &self.normals {
+            Some([n1, n2, n3]) => {
+                let (a, b, c) = self.barycentric(point);
+                *n1 * a + *n2 * b + *n3 * c
+            }
+            None => self.normal,
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for p in [&self.p1, &self.p2, &self.p3] {
+            bounds.add_point(p);
+        }
+        bounds
+    }
+
+    fn local_uv(&self, point: &Point) -> (f64, f64) {
+        let Some([t1, t2, t3]) = &self.uvs else {
+            return (0.0, 0.0);
+        };
+        let (a, b, c) = self.barycentric(point);
+        (
+            t1.0 * a + t2.0 * b + t3.0 * c,
+            t1.1 * a + t2.1 * b + t3.1 * c,
+        )
+    }
+
+    fn local_tangent(&self, _point: &Point, normal: &V3D) -> V3D {
+        self.tangent.unwrap_or_else(|| orthonormal_basis(normal).0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn flat_normal() {
+        let n = triangle().local_normal_at(&Point::new(0.0, 0.5, 0.0));
+        assert_eq!(n, V3D::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn ray_strikes_the_triangle() {
+        let ray = Ray::new(Point::new(0.0, 0.5, -2.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(triangle().local_intersect(&ray), vec![2.0]);
+    }
+
+    #[test]
+    fn ray_misses_the_edges() {
+        let t = triangle();
+        for origin in [
+            Point::new(1.0, 1.0, -2.0),
+            Point::new(-1.0, 1.0, -2.0),
+            Point::new(0.0, -1.0, -2.0),
+        ] {
+            assert!(t
+                .local_intersect(&Ray::new(origin, V3D::new(0.0, 0.0, 1.0)))
+                .is_empty());
+        }
+        let parallel = Ray::new(Point::new(0.0, -1.0, -2.0), V3D::new(0.0, 1.0, 0.0));
+        assert!(t.local_intersect(&parallel).is_empty());
+    }
+
+    #[test]
+    fn smooth_normals_are_interpolated() {
+        let t = triangle().with_normals([
+            V3D::new(0.0, 1.0, 0.0),
+            V3D::new(-1.0, 0.0, 0.0),
+            V3D::new(1.0, 0.0, 0.0),
+        ]);
+        let n = t.local_normal_at(&Point::new(-0.2, 0.3, 0.0));
+        assert_eq!(n, V3D::new(-0.2, 0.3, 0.0));
+    }
+
+    #[test]
+    fn tangent_points_along_u() {
+        let t = triangle().with_uvs([(0.5, 1.0), (0.0, 0.0), (1.0, 0.0)]);
+        let n = t.local_normal_at(&Point::new(0.0, 0.5, 0.0));
+        assert_eq!(
+            t.local_tangent(&Point::new(0.0, 0.5, 0.0), &n),
+            V3D::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(t.local_uv(&Point::new(0.0, 0.5, 0.0)), (0.5, 0.5));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::math::color::Color;
+
+/// Image of (possibly HDR) colors sampled with wrapping texture coordinates.
+/// `v` grows upwards, so `(0, 0)` is the bottom left corner of the image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Texture {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "texture size doesn't match its pixels"
+        );
+        Texture {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Texture from a canvas in the same `u32` format the renderers write.
+    pub fn from_u32(width: usize, height: usize, pixels: &[u32]) -> Texture {
+        Texture::new(
+            width,
+            height,
+            pixels.iter().map(|p| Color::from_u32(*p)).collect(),
+        )
+    }
+
+    pub fn solid(color: Color) -> Texture {
+        Texture::new(1, 1, vec![color])
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Pixel with wrap around addressing, `y = 0` is the top row.
+    pub fn pixel(&self, x: i64, y: i64) -> Color {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    /// Bilinear lookup.
+    pub fn sample(&self, u: f64, v: f64) -> Color {
+        let x = u * self.width as f64 - 0.5;
+        let y = (1.0 - v) * self.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = self.pixel(x0, y0) * (1.0 - tx) + self.pixel(x0 + 1, y0) * tx;
+        let bottom = self.pixel(x0, y0 + 1) * (1.0 - tx) + self.pixel(x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checker() -> Texture {
+        let (black, white) = (Color::black(), Color::white());
+        Texture::new(2, 2, vec![black, white, white, black])
+    }
+
+    #[test]
+    fn samples_pixel_centers() {
+        let texture = checker();
+        assert_eq!(texture.sample(0.25, 0.75), Color::black());
+        assert_eq!(texture.sample(0.75, 0.75), Color::white());
+        assert_eq!(texture.sample(0.25, 0.25), Color::white());
+    }
+
+    #[test]
+    fn blends_between_pixels() {
+        let texture = checker();
+        assert_eq!(texture.sample(0.5, 0.75), Color::white() * 0.5);
+    }
+
+    #[test]
+    fn wraps_around() {
+        let texture = checker();
+        assert_eq!(texture.sample(1.25, -0.25), texture.sample(0.25, 0.75));
+        assert_eq!(texture.pixel(-1, 2), Color::white());
+    }
+
+    #[test]
+    fn reads_canvas_pixels() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let texture = Texture::from_u32(1, 1, &[red.to_u32()]);
+        assert_eq!(texture.sample(0.3, 0.3), red);
+    }
+}
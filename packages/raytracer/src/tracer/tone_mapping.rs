@@ -0,0 +1,109 @@
+use crate::math::color::Color;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToneOperator {
+    /// Just clamps every channel to 0..1, what the Whitted renders always did.
+    #[default]
+    Clamp,
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve.
+    Aces,
+}
+
+/// Maps the HDR colors accumulated by the renderer to the `u32` canvas format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneOperator,
+    pub exposure: f64,
+    pub gamma: f64,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        ToneMapping {
+            operator: ToneOperator::Clamp,
+            exposure: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl ToneMapping {
+    /// Filmic mapping with the usual display gamma, a good fit for path tracing.
+    pub fn filmic() -> ToneMapping {
+        ToneMapping {
+            operator: ToneOperator::Aces,
+            exposure: 1.0,
+            gamma: 2.2,
+        }
+    }
+
+    pub fn map(&self, color: Color) -> Color {
+        let exposed = color * self.exposure;
+        let channel = |value: f64| {
+            let value = value.max(0.0);
+            let mapped = match self.operator {
+                ToneOperator::Clamp => value.min(1.0),
+                ToneOperator::Reinhard => value / (1.0 + value),
+                ToneOperator::Aces => {
+                    let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                    ((value * (a * value + b)) / (value * (c * value + d) + e)).clamp(0.0, 1.0)
+                }
+            };
+            mapped.powf(1.0 / self.gamma)
+        };
+        Color::new(channel(exposed.r), channel(exposed.g), channel(exposed.b))
+    }
+
+    pub fn to_u32(&self, color: Color) -> u32 {
+        self.map(color).to_u32()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_is_plain_clamp() {
+        let color = Color::new(0.25, 2.0, -1.0);
+        assert_eq!(ToneMapping::default().to_u32(color), color.to_u32());
+    }
+
+    #[test]
+    fn reinhard_compresses_highlights() {
+        let mapping = ToneMapping {
+            operator: ToneOperator::Reinhard,
+            ..ToneMapping::default()
+        };
+        assert_eq!(
+            mapping.map(Color::new(1.0, 3.0, 0.0)),
+            Color::new(0.5, 0.75, 0.0)
+        );
+    }
+
+    #[test]
+    fn filmic_is_monotonic_and_bounded() {
+        let mapping = ToneMapping::filmic();
+        let mut last = -1.0;
+        for i in 0..100 {
+            let value = mapping.map(Color::white() * (i as f64 * 0.2)).r;
+            assert!(value >= last && value <= 1.0);
+            last = value;
+        }
+    }
+
+    #[test]
+    fn exposure_scales_before_mapping() {
+        let mapping = ToneMapping {
+            exposure: 2.0,
+            ..ToneMapping::default()
+        };
+        assert_eq!(
+            mapping.map(Color::new(0.25, 0.0, 0.0)),
+            Color::new(0.5, 0.0, 0.0)
+        );
+    }
+}
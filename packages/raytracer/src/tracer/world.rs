@@ -0,0 +1,264 @@
+use super::{
+    environment::Environment,
+    intersection::{hit, Computations, Intersection},
+    light::PointLight,
+    ray::Ray,
+    shapes::Object,
+};
+use crate::math::{
+    color::Color,
+    point_vec::{Point, TupleLike},
+};
+
+/// How many times a ray may bounce for reflections and refractions.
+pub const MAX_DEPTH: usize = 5;
+
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    pub objects: Vec<Object>,
+    pub lights: Vec<PointLight>,
+    /// Seen by rays that miss everything, in the background and in reflections.
+    pub environment: Environment,
+}
+
+impl World {
+    pub fn new() -> World {
+        World::default()
+    }
+
+    pub fn add(&mut self, object: Object) -> &mut Self {
+        self.objects.push(object);
+        self
+    }
+
+    pub fn add_light(&mut self, light: PointLight) -> &mut Self {
+        self.lights.push(light);
+        self
+    }
+
+    /// Every intersection of the ray with the world, sorted by distance.
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut xs: Vec<_> = self
+            .objects
+            .iter()
+            .flat_map(|object| object.intersect(ray))
+            .collect();
+        xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+        xs
+    }
+
+    pub fn color_at(&self, ray: &Ray, remaining: usize) -> Color {
+        let xs = self.intersect(ray);
+        match hit(&xs) {
+            Some(i) => {
+                let comps = i.prepare(ray, &xs);
+                self.shade_hit(&comps, remaining)
+            }
+            None => self.environment.radiance(&ray.direction),
+        }
+    }
+
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
+        let material = &comps.object.material;
+        let surface = self.lights.iter().fold(material.emissive, |acc, light| {
+            let shadowed = self.is_shadowed(&light.position, &comps.over_point);
+            acc + material.lighting(
+                light,
+                &comps.over_point,
+                &comps.eyev,
+                &comps.normalv,
+                shadowed,
+            )
+        });
+        let reflected = self.reflected_color(comps, remaining);
+        let refracted = self.refracted_color(comps, remaining);
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+
+    pub fn is_shadowed(&self, light_position: &Point, point: &Point) -> bool {
+        let v = *light_position - *point;
+        let distance = v.magnitude();
+        let ray = Ray::new(*point, v.normalize());
+        let xs = self.intersect(&ray);
+        hit(&xs).is_some_and(|i| i.t < distance)
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let reflective = comps.object.material.reflective;
+        if remaining == 0 || reflective == 0.0 {
+            return Color::black();
+        }
+        let ray = Ray::new(comps.over_point, comps.reflectv);
+        self.color_at(&ray, remaining - 1) * reflective
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let transparency = comps.object.material.transparency;
+        if remaining == 0 || transparency == 0.0 {
+            return Color::black();
+        }
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(&comps.normalv);
+        let sin2_t = n_ratio * n_ratio * (1.0 - cos_i * cos_i);
+        if sin2_t > 1.0 {
+            return Color::black();
+        }
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let ray = Ray::new(comps.under_point, direction);
+        self.color_at(&ray, remaining - 1) * transparency
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{
+        math::{matrices::Mat4, point_vec::V3D},
+        tracer::{material::Material, shapes::CsgOperation},
+    };
+
+    pub(crate) fn default_world() -> World {
+        let mut world = World::new();
+        world.add_light(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::white(),
+        ));
+        world.add(Object::sphere().with_material(Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            diffuse: 0.7,
+            specular: 0.2,
+            ..Material::default()
+        }));
+        world.add(Object::sphere().with_transform(Mat4::scaling(0.5, 0.5, 0.5)));
+        world
+    }
+
+    #[test]
+    fn intersections_are_sorted() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        let ts: Vec<f64> = world.intersect(&ray).iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![4.0, 4.5, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn color_when_ray_misses() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 1.0, 0.0));
+        assert_eq!(world.color_at(&ray, MAX_DEPTH), Color::black());
+    }
+
+    #[test]
+    fn missed_rays_see_the_environment() {
+        let mut world = default_world();
+        world.environment = Environment::sky();
+        let up = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 1.0, 0.0));
+        let Environment::Sky { zenith, .. } = world.environment else {
+            unreachable!()
+        };
+        assert_eq!(world.color_at(&up, MAX_DEPTH), zenith);
+    }
+
+    #[test]
+    fn color_when_ray_hits() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            world.color_at(&ray, MAX_DEPTH),
+            Color::new(0.38066, 0.47583, 0.2855)
+        );
+    }
+
+    #[test]
+    fn shadow_behind_sphere() {
+        let world = default_world();
+        let light = world.lights[0].position;
+        assert!(!world.is_shadowed(&light, &Point::new(0.0, 10.0, 0.0)));
+        assert!(world.is_shadowed(&light, &Point::new(10.0, -10.0, 10.0)));
+        assert!(!world.is_shadowed(&light, &Point::new(-20.0, 20.0, -20.0)));
+    }
+
+    #[test]
+    fn reflective_floor() {
+        let mut world = default_world();
+        let half = 2f64.sqrt() / 2.0;
+        world.add(
+            Object::plane()
+                .with_material(Material {
+                    reflective: 0.5,
+                    ..Material::default()
+                })
+                .with_transform(Mat4::translation(0.0, -1.0, 0.0)),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, -3.0), V3D::new(0.0, -half, half));
+        let xs = world.intersect(&ray);
+        let floor = xs
+            .iter()
+            .find(|i| (i.t - 2f64.sqrt()).abs() < 1e-9)
+            .unwrap();
+        let comps = floor.prepare(&ray, &xs);
+        assert_eq!(
+            world.reflected_color(&comps, MAX_DEPTH),
+            Color::new(0.19033, 0.23791, 0.14274)
+        );
+        assert_eq!(world.reflected_color(&comps, 0), Color::black());
+    }
+
+    #[test]
+    fn shades_csg_with_the_material_of_the_hit_operand() {
+        let mut world = World::new();
+        world.add_light(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::white(),
+        ));
+        let red = Material {
+            color: Color::new(1.0, 0.0, 0.0),
+            ..Material::default()
+        };
+        let blue = Material {
+            color: Color::new(0.0, 0.0, 1.0),
+            ..Material::default()
+        };
+        let left = Object::sphere().with_material(red);
+        let right = Object::cube()
+            .with_material(blue)
+            .with_transform(Mat4::translation(0.0, 0.0, -1.5));
+        let csg = Object::csg(CsgOperation::Difference, left, right);
+        world.add(Object::group(vec![csg]).with_transform(Mat4::translation(0.0, 0.0, 1.0)));
+        //the cube carves the front of the sphere, so the ray hits the cube face inside it
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), V3D::new(0.0, 0.0, 1.0));
+        let xs = world.intersect(&ray);
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t - 5.5).abs() < 1e-9);
+        let color = world.color_at(&ray, MAX_DEPTH);
+        assert!(color.b > color.r);
+    }
+
+    #[test]
+    fn mutually_reflective_surfaces_terminate() {
+        let mut world = World::new();
+        world.add_light(PointLight::new(Point::new(0.0, 0.0, 0.0), Color::white()));
+        let mirror = Material {
+            reflective: 1.0,
+            ..Material::default()
+        };
+        world.add(
+            Object::plane()
+                .with_material(mirror.clone())
+                .with_transform(Mat4::translation(0.0, -1.0, 0.0)),
+        );
+        world.add(
+            Object::plane()
+                .with_material(mirror)
+                .with_transform(Mat4::translation(0.0, 1.0, 0.0)),
+        );
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), V3D::new(0.0, 1.0, 0.0));
+        world.color_at(&ray, MAX_DEPTH);
+    }
+}
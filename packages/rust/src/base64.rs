@@ -1,4 +1,3 @@
-#[allow(arithmetic_overflow)]
 use wasm_bindgen::prelude::*;
 
 static BASE64_TABLE: [u8; 64] =
@@ -7,16 +6,18 @@ static BASE64_TABLE: [u8; 64] =
 #[wasm_bindgen]
 pub struct Base64Decoder {
     bytes: Vec<u8>,
-    index: usize,
+}
+
+impl Default for Base64Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
 impl Base64Decoder {
     pub fn new() -> Self {
-        Self {
-            bytes: Vec::new(),
-            index: 0,
-        }
+        Self { bytes: Vec::new() }
     }
 
     pub fn to_base64(&mut self, bytes: &[u8]) -> *const u8 {
@@ -31,9 +31,7 @@ fn rot(n: i64, x: &mut i64, y: &mut i64, rx: i64, ry: i64) {
         }
 
         //Swap x and y
-        let t: i64 = *x;
-        *x = *y;
-        *y = t;
+        std::mem::swap(x, y);
     }
 }
 
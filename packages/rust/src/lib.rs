@@ -1,4 +1,3 @@
-#[allow(arithmetic_overflow)]
 pub mod base64;
 pub mod hilbert;
 pub mod mandelbrot;
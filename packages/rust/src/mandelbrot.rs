@@ -9,7 +9,7 @@ struct Complex {
 
 impl Complex {
     fn module(&self) -> f64 {
-        return self.i * self.i + self.r * self.r;
+        self.i * self.i + self.r * self.r
     }
 }
 
@@ -43,7 +43,7 @@ fn calc_score(c: &Complex) -> f64 {
             return i as f64 / N as f64;
         }
     }
-    return 1 as f64;
+    1.0
 }
 
 struct Scale {
@@ -84,7 +84,7 @@ pub fn calc_set(width: usize, heigth: usize, region: &[f64]) -> Vec<u8> {
         }
         return image;
     }
-    return vec![];
+    vec![]
 }
 
 #[cfg(test)]
@@ -93,6 +93,6 @@ mod test {
 
     #[test]
     fn sanity() {
-        assert_eq!(16, calc_set(2, 2, &vec![1.0, 1.0, 1.0, 1.0]).len());
+        assert_eq!(16, calc_set(2, 2, &[1.0, 1.0, 1.0, 1.0]).len());
     }
 }
@@ -15,13 +15,13 @@ pub struct ParticleWorld {
 }
 
 #[wasm_bindgen]
-pub fn random_world(max_x: f32, max_y: f32, number_of_particles: usize) -> ParticleWorld {
+pub fn random_world(_max_x: f32, _max_y: f32, number_of_particles: usize) -> ParticleWorld {
     let v = (0..number_of_particles).map(|_| {
-        return V4::xyz(
+        V4::xyz(
             random() as f32 * 100.0,
             random() as f32 * 100.0,
             100.0 * random() as f32,
-        );
+        )
     });
 
     ParticleWorld {
@@ -47,7 +47,7 @@ impl ParticleWorld {
         });
     }
 
-    pub fn rotate(&mut self, angle_x: f32, angle_y: f32) {
+    pub fn rotate(&mut self, _angle_x: f32, angle_y: f32) {
         let v_rotated = Mat4::rotate_y(angle_y).v_mul(&V4::xyz(0.0, 0.0, 1000.0));
         // let v_rotated = Mat4::rotate_x(angle_x).v_mul(&v_rotated);
         let translate = Mat4::translation_mat(0.0, 0.0, 0.0);
@@ -87,16 +87,16 @@ struct ParticleWorldCalc {
 
 impl ParticleWorldCalc {
     #[inline(never)]
-    fn calc_acc(&self, position: &Vec<V4>, speed: &Vec<V4>) -> Vec<V4> {
+    fn calc_acc(&self, position: &[V4], speed: &[V4]) -> Vec<V4> {
         position
             .iter()
             .enumerate()
             .map(|(i, x)| {
-                let mut acc = self.base_influence(&x);
+                let mut acc = self.base_influence(x);
                 acc.add_mut(&speed[i].mul_scalar(-DAMPING)); //speed damping
                 position
                     .iter()
-                    .for_each(|other| acc.add_mut(&self.influence(&x, &other)));
+                    .for_each(|other| acc.add_mut(&self.influence(x, other)));
                 acc
             })
             .collect()
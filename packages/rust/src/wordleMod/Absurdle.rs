@@ -0,0 +1,177 @@
+use wasm_bindgen::prelude::*;
+
+use super::{
+    Alphabet::{Alphabet, Normalization, Segmentation},
+    Guess,
+    Wordle::{parse_words, to_word, ByteStr, Correctness, WordleError},
+};
+
+/// Adversarial Wordle: there is no answer up front, after every guess the
+/// game keeps the biggest group of words that give the same mask.
+#[wasm_bindgen]
+pub struct Absurdle {
+    size: usize,
+    remaining: Vec<ByteStr>,
+    history: Vec<Guess>,
+    alphabet: Alphabet,
+}
+
+#[wasm_bindgen]
+impl Absurdle {
+    /// Words of `size` letters from `words`, or the bundled list.
+    pub fn new(words: Option<String>, size: usize) -> Result<Absurdle, WordleError> {
+        Absurdle::with_alphabet(words, size, Segmentation::Chars, Normalization::None)
+    }
+
+    /// Like `new`, splitting words into letters with `segmentation` and
+    /// comparing them after `normalization`.
+    pub fn with_alphabet(
+        words: Option<String>,
+        size: usize,
+        segmentation: Segmentation,
+        normalization: Normalization,
+    ) -> Result<Absurdle, WordleError> {
+        let mut alphabet = Alphabet::new(segmentation, normalization);
+        let remaining = parse_words(words, size, &mut alphabet)?;
+        if remaining.is_empty() {
+            return Err(WordleError::EmptyWordList(size));
+        }
+        Ok(Absurdle {
+            size,
+            remaining,
+            history: Vec::new(),
+            alphabet,
+        })
+    }
+
+    /// The mask of the guess and how many words are left. Fails once the
+    /// game is solved.
+    pub fn play(&mut self, guess_word: &str) -> Result<JsValue, WordleError> {
+        let mask = self.play_word(guess_word)?;
+        let mask: Vec<u32> = mask.iter().map(|value| *value as u32).collect();
+        Ok(serde_wasm_bindgen::to_value(&(mask, self.remaining.len()))
+            .expect("could not turn the result into a js value"))
+    }
+
+    /// Words that could still be the answer.
+    pub fn remaining(&self) -> usize {
+        self.remaining.len()
+    }
+
+    pub fn guesses(&self) -> usize {
+        self.history.len()
+    }
+
+    /// The last guess was all green, only possible once a single word is left.
+    pub fn solved(&self) -> bool {
+        self.history
+            .last()
+            .is_some_and(|guess| guess.mask.iter().all(|c| *c == Correctness::Correct))
+    }
+}
+
+impl Absurdle {
+    /// [`Absurdle::play`] without the conversion to js.
+    fn play_word(&mut self, guess_word: &str) -> Result<Vec<Correctness>, WordleError> {
+        if self.solved() {
+            return Err(WordleError::AlreadySolved);
+        }
+        let guess_word = to_word(guess_word, self.size, &self.alphabet)?;
+        Ok(self.reveal(&guess_word))
+    }
+
+    fn reveal(&mut self, guess_word: &[u8]) -> Vec<Correctness> {
+        let distribution = Guess::calc_distribution(&self.remaining, guess_word);
+        //ties go to the mask that reveals the least
+        let radix = (0..distribution.len())
+            .max_by_key(|radix| {
+                let revealed: usize = Correctness::from_radix(*radix, self.size)
+                    .iter()
+                    .map(|c| *c as usize)
+                    .sum();
+                (
+                    distribution[*radix],
+                    std::cmp::Reverse(revealed),
+                    std::cmp::Reverse(*radix),
+                )
+            })
+            .unwrap_or(0);
+        let mask = Correctness::from_radix(radix, self.size);
+        let guess = Guess {
+            word: guess_word.to_vec(),
+            mask: mask.clone(),
+        };
+        self.remaining.retain(|word| guess.matches(word));
+        self.history.push(guess);
+        mask
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wordleMod::Wordle::Correctness::{Correct, Misplaced, Wrong};
+
+    #[test]
+    fn keeps_the_biggest_bucket() {
+        let words = "kills fills hills pills crane";
+        let mut game = Absurdle::new(Some(words.to_string()), 5).unwrap();
+        //"kills" itself is alone in its bucket, fills/hills/pills stay together
+        assert_eq!(
+            game.reveal(b"kills"),
+            [Wrong, Correct, Correct, Correct, Correct]
+        );
+        assert_eq!(game.remaining(), 3);
+        assert!(!game.solved());
+    }
+
+    #[test]
+    fn is_solved_when_one_word_is_left() {
+        let mut game = Absurdle::new(Some("crane slate".to_string()), 5).unwrap();
+        //a tie, the mask with less information wins
+        assert_eq!(
+            game.reveal(b"crane"),
+            [Wrong, Wrong, Correct, Wrong, Correct]
+        );
+        assert_eq!(game.remaining(), 1);
+        assert_eq!(game.play_word("slate"), Ok(vec![Correct; 5]));
+        assert!(game.solved());
+        assert_eq!(game.play_word("crane"), Err(WordleError::AlreadySolved));
+        assert_eq!(game.guesses(), 2);
+        let mut game = Absurdle::new(Some("abbey".to_string()), 5).unwrap();
+        assert_eq!(
+            game.reveal(b"yeast"),
+            [Misplaced, Misplaced, Misplaced, Wrong, Wrong]
+        );
+    }
+
+    #[test]
+    fn needs_words_of_the_size() {
+        assert_eq!(
+            Absurdle::new(Some("crane slate".to_string()), 6).err(),
+            Some(WordleError::EmptyWordList(6))
+        );
+    }
+
+    #[test]
+    fn folds_accents_when_asked() {
+        let words = "Limão manga";
+        let game = Absurdle::new(Some(words.to_string()), 5).unwrap();
+        assert_eq!(game.alphabet.decode(&game.remaining[0]), "Limão");
+        let mut game = Absurdle::with_alphabet(
+            Some(words.to_string()),
+            5,
+            Segmentation::Chars,
+            Normalization::FoldAccents,
+        )
+        .unwrap();
+        assert_eq!(game.remaining[0], b"limao");
+        let word = to_word("LIMÃO", 5, &game.alphabet).unwrap();
+        //"manga" is left, its mask reveals less
+        assert_eq!(
+            game.reveal(&word),
+            [Wrong, Wrong, Misplaced, Misplaced, Wrong]
+        );
+        assert_eq!(game.remaining[0], b"manga");
+    }
+}
@@ -0,0 +1,223 @@
+use unicode_segmentation::UnicodeSegmentation;
+use wasm_bindgen::prelude::*;
+
+use super::Wordle::{ByteStr, WordleError};
+
+/// What counts as one letter of a word.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Segmentation {
+    /// Every Unicode scalar value.
+    #[default]
+    Chars,
+    /// Every grapheme cluster, a letter and the accents combined with it
+    /// are a single letter.
+    Graphemes,
+}
+
+/// Which letters are the same.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Letters are compared as written.
+    #[default]
+    None,
+    /// Upper and lower case are the same letter.
+    Lowercase,
+    /// Lower case without accents, "Açaí" plays as "acai".
+    FoldAccents,
+}
+
+/// First code given to letters outside ASCII, ASCII letters are their own byte.
+const FIRST_CODE: u8 = 128;
+/// Code of the letters no word of the list has, they never match.
+const UNKNOWN: u8 = u8::MAX;
+/// Most letters outside ASCII a word list may use.
+pub const MAX_LETTERS: usize = (UNKNOWN - FIRST_CODE) as usize;
+
+/// Gives every letter a one byte code so the solver compares bytes in any
+/// script. ASCII words skip segmentation and keep their bytes.
+#[derive(Default)]
+pub struct Alphabet {
+    segmentation: Segmentation,
+    normalization: Normalization,
+    /// Letters outside ASCII, the first one has code [`FIRST_CODE`].
+    letters: Vec<String>,
+}
+
+impl Alphabet {
+    pub fn new(segmentation: Segmentation, normalization: Normalization) -> Self {
+        Alphabet {
+            segmentation,
+            normalization,
+            letters: Vec::new(),
+        }
+    }
+
+    /// Codes of the letters of `word`, its new letters get a code.
+    pub fn learn(&mut self, word: &str) -> Result<ByteStr, WordleError> {
+        if let Some(word) = self.ascii(word) {
+            return Ok(word);
+        }
+        self.split(word)
+            .into_iter()
+            .map(|letter| {
+                if let Some(code) = self.code_of(&letter) {
+                    return Ok(code);
+                }
+                if self.letters.len() == MAX_LETTERS {
+                    return Err(WordleError::TooManyLetters(MAX_LETTERS));
+                }
+                self.letters.push(letter);
+                Ok(FIRST_CODE + (self.letters.len() - 1) as u8)
+            })
+            .collect()
+    }
+
+    /// Codes of the letters of `word`, letters never learnt can't match any.
+    pub fn encode(&self, word: &str) -> ByteStr {
+        self.ascii(word).unwrap_or_else(|| {
+            self.split(word)
+                .iter()
+                .map(|letter| self.code_of(letter).unwrap_or(UNKNOWN))
+                .collect()
+        })
+    }
+
+    /// The normalized word `word` codes.
+    pub fn decode(&self, word: &[u8]) -> String {
+        let mut decoded = String::with_capacity(word.len());
+        for code in word {
+            match code.checked_sub(FIRST_CODE) {
+                None => decoded.push(char::from(*code)),
+                Some(index) => match self.letters.get(index as usize) {
+                    Some(letter) => decoded.push_str(letter),
+                    None => decoded.push(char::REPLACEMENT_CHARACTER),
+                },
+            }
+        }
+        decoded
+    }
+
+    pub fn letter(&self, code: u8) -> String {
+        self.decode(&[code])
+    }
+
+    fn ascii(&self, word: &str) -> Option<ByteStr> {
+        if !word.is_ascii() {
+            return None;
+        }
+        Some(match self.normalization {
+            Normalization::None => word.as_bytes().to_vec(),
+            _ => word.bytes().map(|c| c.to_ascii_lowercase()).collect(),
+        })
+    }
+
+    /// The normalized letters of `word`, marks left without a letter by
+    /// accent folding are dropped.
+    pub fn split(&self, word: &str) -> Vec<String> {
+        let letters: Vec<&str> = match self.segmentation {
+            Segmentation::Chars => word.split_inclusive(|_| true).collect(),
+            Segmentation::Graphemes => word.graphemes(true).collect(),
+        };
+        letters
+            .into_iter()
+            .map(|letter| match self.normalization {
+                Normalization::None => letter.to_string(),
+                Normalization::Lowercase => letter.to_lowercase(),
+                Normalization::FoldAccents => letter
+                    .to_lowercase()
+                    .chars()
+                    .filter(|c| !is_combining(*c))
+                    .map(fold_accent)
+                    .collect(),
+            })
+            .filter(|letter| !letter.is_empty())
+            .collect()
+    }
+
+    fn code_of(&self, letter: &str) -> Option<u8> {
+        match letter.as_bytes() {
+            [code] if code.is_ascii() => Some(*code),
+            _ => self
+                .letters
+                .iter()
+                .position(|known| known == letter)
+                .map(|index| FIRST_CODE + index as u8),
+        }
+    }
+}
+
+/// Combining diacritical marks, the accents of decomposed letters.
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36f}')
+}
+
+/// The base letter of a lower case accented latin letter, and ё as е.
+fn fold_accent(c: char) -> char {
+    match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ĥ' | 'ħ' => 'h',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'ĵ' => 'j',
+        'ķ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'ţ' | 'ť' | 'ŧ' => 't',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        'ё' => 'е',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ascii_words_keep_their_bytes() {
+        let mut alphabet = Alphabet::default();
+        assert_eq!(alphabet.learn("Crane").unwrap(), b"Crane");
+        let mut alphabet = Alphabet::new(Segmentation::Chars, Normalization::Lowercase);
+        assert_eq!(alphabet.learn("Crane").unwrap(), b"crane");
+        assert!(alphabet.letters.is_empty());
+    }
+
+    #[test]
+    fn letters_outside_ascii_get_one_byte() {
+        let mut alphabet = Alphabet::default();
+        let word = alphabet.learn("açaí").unwrap();
+        assert_eq!(word, [b'a', FIRST_CODE, b'a', FIRST_CODE + 1]);
+        assert_eq!(alphabet.decode(&word), "açaí");
+        let word = alphabet.learn("книга").unwrap();
+        assert_eq!(word.len(), 5);
+        assert_eq!(alphabet.decode(&word), "книга");
+        assert_eq!(alphabet.encode("ñandú")[0], UNKNOWN);
+        assert_eq!(alphabet.letter(UNKNOWN), "\u{fffd}");
+    }
+
+    #[test]
+    fn folds_accents_and_combining_marks() {
+        let decomposed = "Ac\u{327}ai\u{301}";
+        let mut alphabet = Alphabet::new(Segmentation::Chars, Normalization::FoldAccents);
+        assert_eq!(alphabet.learn("Açaí").unwrap(), b"acai");
+        assert_eq!(alphabet.learn(decomposed).unwrap(), b"acai");
+        assert_eq!(alphabet.learn("ёлка").unwrap(), alphabet.encode("елка"));
+        let mut alphabet = Alphabet::new(Segmentation::Graphemes, Normalization::None);
+        let word = alphabet.learn(decomposed).unwrap();
+        assert_eq!(word.len(), 4);
+        assert_eq!(alphabet.decode(&word), decomposed);
+        let mut alphabet = Alphabet::default();
+        assert_eq!(alphabet.learn(decomposed).unwrap().len(), 6);
+    }
+}
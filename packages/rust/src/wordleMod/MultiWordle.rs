@@ -0,0 +1,249 @@
+use std::cell::OnceCell;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use super::{
+    Alphabet::{Alphabet, Normalization, Segmentation},
+    Guess, Naive,
+    Patterns::PatternMatrix,
+    Wordle::{parse_words, to_word, ByteStr, Correctness, WordleError},
+};
+
+struct Board {
+    answer: ByteStr,
+    /// Indices of the words that can still be this board's answer.
+    remaining: Vec<usize>,
+    /// Guesses it took, once solved.
+    solved_in: Option<usize>,
+}
+
+/// Several boards played with the same guesses (Dordle, Quordle, Octordle).
+#[wasm_bindgen]
+pub struct MultiWordle {
+    words: Vec<ByteStr>,
+    boards: Vec<Board>,
+    guesses: usize,
+    /// Patterns of every word against every word, built on first use.
+    matrix: OnceCell<PatternMatrix>,
+    alphabet: Alphabet,
+}
+
+#[wasm_bindgen]
+impl MultiWordle {
+    /// One board per word of `answers`, guesses and answers come from
+    /// `words` (or the bundled list for that length).
+    pub fn new(words: Option<String>, answers: &str) -> Result<MultiWordle, WordleError> {
+        MultiWordle::with_alphabet(words, answers, Segmentation::Chars, Normalization::None)
+    }
+
+    /// Like `new`, splitting words into letters with `segmentation` and
+    /// comparing them after `normalization`.
+    pub fn with_alphabet(
+        words: Option<String>,
+        answers: &str,
+        segmentation: Segmentation,
+        normalization: Normalization,
+    ) -> Result<MultiWordle, WordleError> {
+        let mut alphabet = Alphabet::new(segmentation, normalization);
+        let answers = answers
+            .split_whitespace()
+            .map(|answer| alphabet.learn(answer))
+            .collect::<Result<Vec<_>, _>>()?;
+        let size = answers.first().map_or(0, Vec::len);
+        if let Some(answer) = answers.iter().find(|answer| answer.len() != size) {
+            return Err(WordleError::WrongLength {
+                word: alphabet.decode(answer),
+                expected: size,
+            });
+        }
+        let mut words = parse_words(words, size, &mut alphabet)?;
+        for answer in &answers {
+            if !words.contains(answer) {
+                words.push(answer.clone());
+            }
+        }
+        let boards = answers
+            .into_iter()
+            .map(|answer| Board {
+                answer,
+                remaining: (0..words.len()).collect(),
+                solved_in: None,
+            })
+            .collect();
+        Ok(MultiWordle {
+            words,
+            boards,
+            guesses: 0,
+            matrix: OnceCell::new(),
+            alphabet,
+        })
+    }
+
+    pub fn boards(&self) -> usize {
+        self.boards.len()
+    }
+
+    pub fn guesses(&self) -> usize {
+        self.guesses
+    }
+
+    /// Every board is solved.
+    pub fn solved(&self) -> bool {
+        self.boards.iter().all(|board| board.solved_in.is_some())
+    }
+
+    /// The mask of every board the guess was played on, `null` for boards
+    /// solved before it. Fails once every board is solved.
+    pub fn play(&mut self, guess_word: &str) -> Result<JsValue, WordleError> {
+        let masks: Vec<Option<Vec<u32>>> = self
+            .play_word(guess_word)?
+            .into_iter()
+            .map(|mask| mask.map(|mask| mask.iter().map(|value| *value as u32).collect()))
+            .collect();
+        Ok(masks
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .expect("could not turn the result into a js value"))
+    }
+
+    /// Guess giving the most information summed over the unsolved boards.
+    pub fn best_guess(&self) -> Option<String> {
+        self.best_guess_index()
+            .map(|guess| self.alphabet.decode(&self.words[guess]))
+    }
+}
+
+impl MultiWordle {
+    fn matrix(&self) -> &PatternMatrix {
+        self.matrix
+            .get_or_init(|| PatternMatrix::new(&self.words, &self.words))
+    }
+
+    fn open_boards(&self) -> impl Iterator<Item = &Board> {
+        self.boards.iter().filter(|board| board.solved_in.is_none())
+    }
+
+    /// [`MultiWordle::play`] without the conversion to js.
+    fn play_word(
+        &mut self,
+        guess_word: &str,
+    ) -> Result<Vec<Option<Vec<Correctness>>>, WordleError> {
+        if self.solved() {
+            return Err(WordleError::AlreadySolved);
+        }
+        let size = self.words.first().map_or(0, Vec::len);
+        let guess_word = to_word(guess_word, size, &self.alphabet)?;
+        Ok(self.reveal(&guess_word))
+    }
+
+    fn reveal(&mut self, guess_word: &[u8]) -> Vec<Option<Vec<Correctness>>> {
+        self.guesses += 1;
+        let guesses = self.guesses;
+        let words = &self.words;
+        self.boards
+            .iter_mut()
+            .map(|board| {
+                if board.solved_in.is_some() {
+                    return None;
+                }
+                let mask = Correctness::check(&board.answer, guess_word);
+                if mask.iter().all(|c| *c == Correctness::Correct) {
+                    board.solved_in = Some(guesses);
+                }
+                let guess = Guess {
+                    word: guess_word.to_vec(),
+                    mask: mask.clone(),
+                };
+                board
+                    .remaining
+                    .retain(|index| guess.matches(&words[*index]));
+                Some(mask)
+            })
+            .collect()
+    }
+
+    fn best_guess_index(&self) -> Option<usize> {
+        //a board down to one word is solved right away
+        if let Some(board) = self.open_boards().find(|board| board.remaining.len() == 1) {
+            return Some(board.remaining[0]);
+        }
+        let matrix = self.matrix();
+        let open: Vec<&Board> = self.open_boards().collect();
+        if open.is_empty() {
+            return None;
+        }
+        (0..self.words.len())
+            .map(|guess| {
+                let information: f64 = open
+                    .iter()
+                    .map(|board| Naive::matrix_entropy(matrix, guess, &board.remaining))
+                    .sum();
+                //between equally good guesses, one that may solve a board
+                let may_solve = open.iter().any(|board| board.remaining.contains(&guess));
+                (guess, information, may_solve)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(a.2.cmp(&b.2)))
+            .map(|(guess, _, _)| guess)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wordleMod::Patterns::test::WORDS;
+
+    #[test]
+    fn boards_share_guesses() {
+        let mut game = MultiWordle::new(Some(WORDS.to_string()), "cigar rebut").unwrap();
+        assert_eq!(game.boards(), 2);
+        let masks = game.reveal(b"cigar");
+        assert_eq!(masks[0], Some(vec![Correctness::Correct; 5]));
+        assert!(masks[1].is_some());
+        assert!(!game.solved());
+        let masks = game.play_word("rebut").unwrap();
+        assert_eq!(masks[0], None);
+        assert!(game.solved());
+        assert_eq!(game.play_word("cigar"), Err(WordleError::AlreadySolved));
+        assert_eq!(game.guesses(), 2);
+        assert_eq!(game.boards[0].solved_in, Some(1));
+        assert_eq!(game.boards[1].solved_in, Some(2));
+    }
+
+    #[test]
+    fn solver_finishes_a_quordle() {
+        let mut game =
+            MultiWordle::new(Some(WORDS.to_string()), "humph naval stink abate").unwrap();
+        while let Some(guess) = game.best_guess_index() {
+            let word = game.words[guess].clone();
+            game.reveal(&word);
+            assert!(game.guesses() <= 9, "too many guesses");
+        }
+        assert!(game.solved());
+    }
+
+    #[test]
+    fn boards_use_the_alphabet() {
+        let words = "книга актёр";
+        let game = MultiWordle::new(Some(words.to_string()), "Актер книга").unwrap();
+        assert_eq!(game.words.len(), 3);
+        let mut game = MultiWordle::with_alphabet(
+            Some(words.to_string()),
+            "Актер книга",
+            Segmentation::Graphemes,
+            Normalization::FoldAccents,
+        )
+        .unwrap();
+        assert_eq!(game.words.len(), 2);
+        let guess = to_word("АКТЁР", 5, &game.alphabet).unwrap();
+        assert_eq!(game.reveal(&guess)[0], Some(vec![Correctness::Correct; 5]));
+        assert_eq!(game.best_guess().as_deref(), Some("книга"));
+    }
+
+    #[test]
+    fn answers_must_have_the_same_length() {
+        assert!(matches!(
+            MultiWordle::new(Some(WORDS.to_string()), "cigar bench planet"),
+            Err(WordleError::WrongLength { .. })
+        ));
+    }
+}
@@ -1,51 +1,102 @@
 use std::ops::Neg;
 
-use super::{Guess, Wordle::ByteStr};
+use super::{
+    map_arr_size,
+    Alphabet::Alphabet,
+    Guess,
+    Patterns::PatternMatrix,
+    Wordle::{ByteStr, Correctness},
+};
 
-pub fn guess(words: &[ByteStr]) -> ByteStr {
-    let best_guess = words
-        .iter()
-        .map(|word| (*word, entropy_of(word, words)))
-        .reduce(|a, b| if a.1 > b.1 { a } else { b });
-    if let Some(guess_word) = best_guess {
-        println!("guessing: {:?}", guess_word);
-        guess_word.0
-    } else {
-        panic!("no words available to make a guess")
-    }
+/// Indices of the `candidates` hard mode still accepts after `history`.
+pub fn hard_mode_guesses(
+    candidates: &[ByteStr],
+    history: &[Guess],
+    alphabet: &Alphabet,
+) -> Vec<usize> {
+    (0..candidates.len())
+        .filter(|index| {
+            history.iter().all(|guess| {
+                guess
+                    .hard_mode_violation(&candidates[*index], alphabet)
+                    .is_none()
+            })
+        })
+        .collect()
 }
 
-pub fn calc_best_guesses(words: &[ByteStr], candidates: &[ByteStr]) -> Vec<(ByteStr, f64)> {
+/// Scores every guess of `candidates` against `answers`, all matrix indices,
+/// and keeps the 10 best. Answers count as much as their `priors`.
+pub fn calc_best_guesses(
+    matrix: &PatternMatrix,
+    answers: &[usize],
+    candidates: &[usize],
+    priors: &[f64],
+) -> Vec<(usize, f64)> {
     let mut best_guesses = candidates
         .iter()
-        .map(|word| (*word, entropy_of(word, words)))
+        .map(|guess| (*guess, weighted_entropy(matrix, *guess, answers, priors)))
         .collect::<Vec<_>>();
-    best_guesses.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-    best_guesses[..10.min(best_guesses.len())].to_vec()
+    best_guesses.sort_by(|a, b| b.1.total_cmp(&a.1));
+    best_guesses.truncate(10);
+    best_guesses
 }
 
-pub fn guess_information(words: &[ByteStr], guess: &Guess) -> f64 {
-    let after_guess_count = words.iter().filter(|&&word| guess.matches(&word)).count();
-    if words.len() != 0 {
-        (after_guess_count as f64 / words.len() as f64).log2().neg()
+/// Bits gained by `guess`, `weights` being the prior of each word.
+pub fn guess_information(words: &[ByteStr], weights: &[f64], guess: &Guess) -> f64 {
+    let total: f64 = weights.iter().sum();
+    let after_guess: f64 = words
+        .iter()
+        .zip(weights)
+        .filter(|(word, _)| guess.matches(word))
+        .map(|(_, weight)| weight)
+        .sum();
+    if total > 0.0 {
+        (after_guess / total).log2().neg()
     } else {
         0.0
     }
 }
 
-pub fn entropy_of(guess_word: &ByteStr, valid_words: &[ByteStr]) -> f64 {
-    let map_arr = Guess::calc_distribution(valid_words, guess_word);
-    map_arr.iter().fold(0f64, |acc, value| {
-        if *value != 0 {
-            acc + calc_information(*value as f64 / valid_words.len() as f64)
+/// Expected bits of `guess_word` when each of `valid_words` is the answer
+/// with a probability proportional to its weight.
+pub fn entropy_of(guess_word: &[u8], valid_words: &[ByteStr], weights: &[f64]) -> f64 {
+    let mut map_arr = vec![0f64; map_arr_size(guess_word.len())];
+    valid_words.iter().zip(weights).for_each(|(word, weight)| {
+        map_arr[Correctness::pattern(word, guess_word)] += weight;
+    });
+    distribution_entropy(map_arr, weights.iter().sum())
+}
+
+pub fn matrix_entropy(matrix: &PatternMatrix, guess: usize, answers: &[usize]) -> f64 {
+    let map_arr = matrix.distribution(guess, answers);
+    distribution_entropy(
+        map_arr.into_iter().map(|count| count as f64),
+        answers.len() as f64,
+    )
+}
+
+/// [`matrix_entropy`] with `priors` (indexed by answer) weighting the answers.
+pub fn weighted_entropy(
+    matrix: &PatternMatrix,
+    guess: usize,
+    answers: &[usize],
+    priors: &[f64],
+) -> f64 {
+    let total = answers.iter().map(|answer| priors[*answer]).sum();
+    distribution_entropy(matrix.weighted_distribution(guess, answers, priors), total)
+}
+
+fn distribution_entropy(map_arr: impl IntoIterator<Item = f64>, total: f64) -> f64 {
+    map_arr.into_iter().fold(0f64, |acc, value| {
+        if value > 0.0 {
+            acc + calc_information(value / total)
         } else {
             acc
         }
     })
 }
 
-// pub fn calc_best_guesses(words: &[ByteStr]) -> Vec<(ByteStr, f64)> {}
-
 fn calc_information(probability: f64) -> f64 {
-    probability as f64 * probability.log2().abs()
+    probability * probability.log2().abs()
 }
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use super::{map_arr_size, Wordle::ByteStr, Wordle::Correctness};
+
+/// Mask radixes in one byte while they fit (up to 5 letters), two bytes for
+/// longer words.
+enum Radixes {
+    Narrow(Vec<u8>),
+    Wide(Vec<u16>),
+}
+
+/// Mask radix of every guess against every answer, computed once so the
+/// solver only does table lookups on each turn.
+pub struct PatternMatrix {
+    size: usize,
+    answers: usize,
+    radixes: Radixes,
+}
+
+impl PatternMatrix {
+    pub fn new(guesses: &[ByteStr], answers: &[ByteStr]) -> Self {
+        let size = guesses.first().or(answers.first()).map_or(0, |w| w.len());
+        let radixes = guesses.iter().flat_map(|guess| {
+            answers
+                .iter()
+                .map(|answer| Correctness::pattern(answer, guess))
+        });
+        let count = guesses.len() * answers.len();
+        let radixes = if map_arr_size(size) <= u8::MAX as usize + 1 {
+            let mut narrow = Vec::with_capacity(count);
+            narrow.extend(radixes.map(|radix| radix as u8));
+            Radixes::Narrow(narrow)
+        } else {
+            let mut wide = Vec::with_capacity(count);
+            wide.extend(radixes.map(|radix| radix as u16));
+            Radixes::Wide(wide)
+        };
+        PatternMatrix {
+            size,
+            answers: answers.len(),
+            radixes,
+        }
+    }
+
+    /// Mask radix of the `guess`-th guess when the answer is the `answer`-th.
+    pub fn pattern(&self, guess: usize, answer: usize) -> usize {
+        let index = guess * self.answers + answer;
+        match &self.radixes {
+            Radixes::Narrow(radixes) => radixes[index] as usize,
+            Radixes::Wide(radixes) => radixes[index] as usize,
+        }
+    }
+
+    /// Radix of the all green mask.
+    pub fn solved(&self) -> usize {
+        map_arr_size(self.size) - 1
+    }
+
+    /// How many of `answers` fall in each mask bucket after `guess`.
+    pub fn distribution(&self, guess: usize, answers: &[usize]) -> Vec<usize> {
+        let mut map_arr = vec![0usize; map_arr_size(self.size)];
+        answers
+            .iter()
+            .for_each(|answer| map_arr[self.pattern(guess, *answer)] += 1);
+        map_arr
+    }
+
+    /// Summed `weights` (indexed by answer) of the `answers` in each mask
+    /// bucket after `guess`.
+    pub fn weighted_distribution(
+        &self,
+        guess: usize,
+        answers: &[usize],
+        weights: &[f64],
+    ) -> Vec<f64> {
+        let mut map_arr = vec![0f64; map_arr_size(self.size)];
+        answers
+            .iter()
+            .for_each(|answer| map_arr[self.pattern(guess, *answer)] += weights[*answer]);
+        map_arr
+    }
+
+    /// `answers` grouped by the mask radix they give for `guess`.
+    pub fn partition(&self, guess: usize, answers: &[usize]) -> BTreeMap<usize, Vec<usize>> {
+        let mut buckets: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for answer in answers {
+            buckets
+                .entry(self.pattern(guess, *answer))
+                .or_default()
+                .push(*answer);
+        }
+        buckets
+    }
+
+    /// The `answers` that give `pattern` for `guess`.
+    pub fn filter(&self, guess: usize, pattern: usize, answers: &[usize]) -> Vec<usize> {
+        answers
+            .iter()
+            .copied()
+            .filter(|answer| self.pattern(guess, *answer) == pattern)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Answers from the original list, shared by the solver tests.
+    pub(crate) const WORDS: &str = "cigar rebut sissy humph awake blush focal evade naval serve \
+                                    heath dwarf model karma stink grade quiet bench abate feign";
+
+    pub(crate) fn words(list: &str) -> Vec<ByteStr> {
+        list.split_whitespace()
+            .map(|word| word.as_bytes().to_vec())
+            .collect()
+    }
+
+    /// The words of `list` and their patterns against each other.
+    pub(crate) fn setup(list: &str) -> (Vec<ByteStr>, PatternMatrix) {
+        let words = words(list);
+        let matrix = PatternMatrix::new(&words, &words);
+        (words, matrix)
+    }
+
+    #[test]
+    fn matches_check() {
+        let guesses = words("crane hello there flees");
+        let answers = words("hello there aabcd");
+        let matrix = PatternMatrix::new(&guesses, &answers);
+        for (g, guess) in guesses.iter().enumerate() {
+            for (a, answer) in answers.iter().enumerate() {
+                let mask = Correctness::check(answer, guess);
+                assert_eq!(matrix.pattern(g, a), Correctness::mask_radix(&mask));
+            }
+        }
+    }
+
+    #[test]
+    fn long_words_use_wide_radixes() {
+        let (_, matrix) = setup("planets leaflet sparkle");
+        assert!(matches!(matrix.radixes, Radixes::Wide(_)));
+        assert_eq!(matrix.pattern(2, 2), map_arr_size(7) - 1);
+        assert_eq!(matrix.distribution(0, &[0, 1, 2]).len(), map_arr_size(7));
+    }
+
+    #[test]
+    fn filters_answers_by_pattern() {
+        let (_, matrix) = setup("hello hells jello world");
+        let pattern = matrix.pattern(0, 2);
+        assert_eq!(matrix.filter(0, pattern, &[0, 1, 2, 3]), [2]);
+        assert_eq!(
+            matrix.distribution(0, &[0, 1, 2, 3]).iter().sum::<usize>(),
+            4
+        );
+    }
+}
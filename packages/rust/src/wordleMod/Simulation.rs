@@ -0,0 +1,320 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Serialize, Serializer};
+
+use super::{Alphabet::Alphabet, Patterns::PatternMatrix, Strategy::Strategy, Wordle::ByteStr};
+
+/// What a simulated player knows besides the answers left: the patterns,
+/// the guesses it may make and how likely each answer is.
+pub struct Player<'a> {
+    pub matrix: &'a PatternMatrix,
+    /// Allowed guesses as matrix indices, usually every word.
+    pub candidates: &'a [usize],
+    /// Weights of the answers, indexed by answer.
+    pub priors: &'a [f64],
+    pub strategy: &'a dyn Strategy,
+}
+
+impl Player<'_> {
+    fn guess(&self, answers: &[usize]) -> usize {
+        //with two answers left no guess does better than trying the likelier
+        //one, which also spares scoring every candidate near the leaves
+        if answers.len() <= 2 {
+            if let Some(answer) = answers.iter().copied().reduce(|a, b| {
+                if self.priors[b] > self.priors[a] {
+                    b
+                } else {
+                    a
+                }
+            }) {
+                return answer;
+            }
+        }
+        self.strategy
+            .guess(self.matrix, answers, self.candidates, self.priors)
+    }
+}
+
+/// Guesses `player` takes to find `answer`, the final one included, starting
+/// with every word of `answers` possible. `None` when it needs more than
+/// `max_guesses`.
+pub fn play_out(
+    player: &Player,
+    answers: &[usize],
+    answer: usize,
+    max_guesses: usize,
+) -> Option<usize> {
+    let matrix = player.matrix;
+    let mut answers = answers.to_vec();
+    for guesses in 1..=max_guesses {
+        let guess = player.guess(&answers);
+        if guess == answer {
+            return Some(guesses);
+        }
+        answers = matrix.filter(guess, matrix.pattern(guess, answer), &answers);
+        answers.retain(|index| *index != guess);
+    }
+    None
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct WordResult {
+    pub word: String,
+    /// `None` when the word wasn't found in time.
+    pub guesses: Option<usize>,
+}
+
+/// Outcome of a strategy over a whole answer list.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SimulationReport {
+    pub strategy: &'static str,
+    /// How many answers took each number of guesses.
+    #[serde(serialize_with = "string_keys")]
+    pub histogram: BTreeMap<usize, usize>,
+    pub failures: usize,
+    /// Over the answers that were found.
+    pub average: f64,
+    pub results: Vec<WordResult>,
+}
+
+impl SimulationReport {
+    /// Plays every answer of `answers` (matrix indices into `words`, coded
+    /// by `alphabet`), with the same guesses as [`play_out`].
+    pub fn new(
+        player: &Player,
+        words: &[ByteStr],
+        alphabet: &Alphabet,
+        answers: &[usize],
+        max_guesses: usize,
+    ) -> SimulationReport {
+        let found = play_all(player, answers, max_guesses);
+        let guesses: Vec<Option<usize>> = answers.iter().map(|answer| found[answer]).collect();
+        let mut histogram = BTreeMap::new();
+        guesses
+            .iter()
+            .flatten()
+            .for_each(|count| *histogram.entry(*count).or_insert(0) += 1);
+        let found: usize = histogram.values().sum();
+        let total: usize = histogram.iter().map(|(count, words)| count * words).sum();
+        SimulationReport {
+            strategy: player.strategy.name(),
+            histogram,
+            failures: answers.len() - found,
+            average: if found > 0 {
+                total as f64 / found as f64
+            } else {
+                0.0
+            },
+            results: answers
+                .iter()
+                .zip(guesses)
+                .map(|(answer, guesses)| WordResult {
+                    word: alphabet.decode(&words[*answer]),
+                    guesses,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Writes the keys of `map` as strings, so it can become a plain JS object.
+fn string_keys<S: Serializer>(
+    map: &BTreeMap<usize, usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().map(|(key, value)| (key.to_string(), value)))
+}
+
+/// [`play_out`] for every answer at once. Answers that gave the same masks
+/// so far have the same answers left, so the games are walked as a tree and
+/// each guess is searched once for all of them instead of once per answer.
+fn play_all(
+    player: &Player,
+    answers: &[usize],
+    max_guesses: usize,
+) -> HashMap<usize, Option<usize>> {
+    let mut found = HashMap::with_capacity(answers.len());
+    if max_guesses == 0 || answers.is_empty() {
+        found.extend(answers.iter().map(|answer| (*answer, None)));
+        return found;
+    }
+    let opening = player.guess(answers);
+    let buckets: Vec<Vec<usize>> = player
+        .matrix
+        .partition(opening, answers)
+        .into_values()
+        .collect();
+    for_each_bucket(&buckets, |bucket| {
+        let mut found = HashMap::new();
+        walk(player, opening, bucket, 1, max_guesses, &mut found);
+        found
+    })
+    .into_iter()
+    .for_each(|bucket| found.extend(bucket));
+    found
+}
+
+/// Records the guesses of the `answers` that gave the same masks up to
+/// `guess`, the `guesses`-th guess.
+fn walk(
+    player: &Player,
+    guess: usize,
+    answers: &[usize],
+    guesses: usize,
+    max_guesses: usize,
+    found: &mut HashMap<usize, Option<usize>>,
+) {
+    let answers: Vec<usize> = answers
+        .iter()
+        .copied()
+        .filter(|answer| {
+            let solved = *answer == guess;
+            if solved {
+                found.insert(guess, Some(guesses));
+            }
+            !solved
+        })
+        .collect();
+    if answers.is_empty() {
+        return;
+    }
+    if guesses == max_guesses {
+        found.extend(answers.iter().map(|answer| (*answer, None)));
+        return;
+    }
+    let next = player.guess(&answers);
+    for bucket in player.matrix.partition(next, &answers).values() {
+        walk(player, next, bucket, guesses + 1, max_guesses, found);
+    }
+}
+
+/// `play` for every bucket, split over the available cores natively.
+#[cfg(not(target_arch = "wasm32"))]
+fn for_each_bucket<F, R>(buckets: &[Vec<usize>], play: F) -> Vec<R>
+where
+    F: Fn(&[usize]) -> R + Sync,
+    R: Send,
+{
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = buckets.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = buckets
+            .chunks(chunk)
+            .map(|chunk| {
+                let play = &play;
+                scope.spawn(move || chunk.iter().map(|bucket| play(bucket)).collect::<Vec<_>>())
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("a simulation thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn for_each_bucket<F, R>(buckets: &[Vec<usize>], play: F) -> Vec<R>
+where
+    F: Fn(&[usize]) -> R,
+{
+    buckets.iter().map(|bucket| play(bucket)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wordleMod::Patterns::test::{setup, WORDS};
+    use crate::wordleMod::Strategy::{MaxEntropy, Minimax};
+
+    #[test]
+    fn reports_every_answer() {
+        let (words, matrix) = setup(WORDS);
+        let answers: Vec<usize> = (0..words.len()).collect();
+        let priors = vec![1.0; words.len()];
+        let player = Player {
+            matrix: &matrix,
+            candidates: &answers,
+            priors: &priors,
+            strategy: &MaxEntropy,
+        };
+        let report = SimulationReport::new(&player, &words, &Alphabet::default(), &answers, 6);
+        assert_eq!(report.results.len(), words.len());
+        assert_eq!(report.failures, 0);
+        assert_eq!(report.histogram.values().sum::<usize>(), words.len());
+        assert_eq!(report.histogram.get(&1), Some(&1));
+        let sequential: Vec<_> = answers
+            .iter()
+            .map(|answer| play_out(&player, &answers, *answer, 6))
+            .collect();
+        let threaded: Vec<_> = report.results.iter().map(|r| r.guesses).collect();
+        assert_eq!(threaded, sequential);
+    }
+
+    #[test]
+    fn counts_failures_instead_of_panicking() {
+        //nothing tells these apart but guessing them one by one
+        let (words, matrix) = setup("kills fills hills pills bills mills");
+        let answers: Vec<usize> = (0..words.len()).collect();
+        let priors = vec![1.0; words.len()];
+        let player = Player {
+            matrix: &matrix,
+            candidates: &answers,
+            priors: &priors,
+            strategy: &Minimax,
+        };
+        let report = SimulationReport::new(&player, &words, &Alphabet::default(), &answers, 3);
+        assert_eq!(report.failures, 3);
+        assert_eq!(report.histogram, BTreeMap::from([(1, 1), (2, 1), (3, 1)]));
+        assert_eq!(report.average, 2.0);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json["histogram"],
+            serde_json::json!({ "1": 1, "2": 1, "3": 1 })
+        );
+        assert_eq!(
+            report
+                .results
+                .iter()
+                .filter(|r| r.guesses.is_none())
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn opens_with_a_word_that_is_not_an_answer() {
+        //"fhpbz" can't be the answer but tells every answer apart
+        let (words, matrix) = setup("kills fills hills pills bills fhpbz");
+        let answers = [0, 1, 2, 3, 4];
+        let candidates: Vec<usize> = (0..words.len()).collect();
+        let priors = vec![1.0; words.len()];
+        let player = Player {
+            matrix: &matrix,
+            candidates: &candidates,
+            priors: &priors,
+            strategy: &MaxEntropy,
+        };
+        let report = SimulationReport::new(&player, &words, &Alphabet::default(), &answers, 6);
+        assert_eq!(report.histogram, BTreeMap::from([(2, 5)]));
+        assert_eq!(play_out(&player, &answers, 0, 6), Some(2));
+    }
+
+    /// Too slow for debug builds, run it with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn simulates_the_bundled_list() {
+        let (words, matrix) = setup(crate::wordleMod::WORDS);
+        let answers: Vec<usize> = (0..words.len()).collect();
+        let priors = vec![1.0; words.len()];
+        let player = Player {
+            matrix: &matrix,
+            candidates: &answers,
+            priors: &priors,
+            strategy: &MaxEntropy,
+        };
+        let report = SimulationReport::new(&player, &words, &Alphabet::default(), &answers, 6);
+        assert_eq!(report.results.len(), words.len());
+        assert!(report.average < 5.0);
+    }
+}
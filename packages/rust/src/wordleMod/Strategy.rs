@@ -0,0 +1,256 @@
+use std::collections::HashSet;
+
+use wasm_bindgen::prelude::*;
+
+use super::{Naive, Patterns::PatternMatrix};
+
+/// Picks the next guess. `answers` are the words still possible and
+/// `candidates` the guesses allowed, both as matrix indices. `priors`
+/// (indexed by answer) weights how likely each answer is.
+pub trait Strategy: Sync {
+    fn name(&self) -> &'static str;
+
+    /// How good `guess` is against `answers`, higher is better.
+    fn score(&self, matrix: &PatternMatrix, guess: usize, answers: &[usize], priors: &[f64])
+        -> f64;
+
+    /// The best scored candidate, a possible answer among equal scores since
+    /// it may win right away.
+    fn guess(
+        &self,
+        matrix: &PatternMatrix,
+        answers: &[usize],
+        candidates: &[usize],
+        priors: &[f64],
+    ) -> usize {
+        let possible: HashSet<usize> = answers.iter().copied().collect();
+        candidates
+            .iter()
+            .map(|guess| {
+                let score = self.score(matrix, *guess, answers, priors);
+                (*guess, score, possible.contains(guess))
+            })
+            .reduce(|a, b| if (a.1, a.2) > (b.1, b.2) { a } else { b })
+            .expect("no words available to make a guess")
+            .0
+    }
+}
+
+/// Strategies the JS side can pick from.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrategyKind {
+    MaxEntropy,
+    Minimax,
+    ExpectedSize,
+    Lookahead,
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 4] = [
+        StrategyKind::MaxEntropy,
+        StrategyKind::Minimax,
+        StrategyKind::ExpectedSize,
+        StrategyKind::Lookahead,
+    ];
+
+    pub fn strategy(self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::MaxEntropy => Box::new(MaxEntropy),
+            StrategyKind::Minimax => Box::new(Minimax),
+            StrategyKind::ExpectedSize => Box::new(ExpectedSize),
+            StrategyKind::Lookahead => Box::new(Lookahead { width: 10 }),
+        }
+    }
+}
+
+/// Most information on average, the original solver.
+pub struct MaxEntropy;
+
+impl Strategy for MaxEntropy {
+    fn name(&self) -> &'static str {
+        "max-entropy"
+    }
+
+    fn score(
+        &self,
+        matrix: &PatternMatrix,
+        guess: usize,
+        answers: &[usize],
+        priors: &[f64],
+    ) -> f64 {
+        Naive::weighted_entropy(matrix, guess, answers, priors)
+    }
+}
+
+/// Smallest worst case: the heaviest bucket left after the guess.
+pub struct Minimax;
+
+impl Strategy for Minimax {
+    fn name(&self) -> &'static str {
+        "minimax"
+    }
+
+    fn score(
+        &self,
+        matrix: &PatternMatrix,
+        guess: usize,
+        answers: &[usize],
+        priors: &[f64],
+    ) -> f64 {
+        let worst = matrix
+            .weighted_distribution(guess, answers, priors)
+            .into_iter()
+            .fold(0.0, f64::max);
+        -worst
+    }
+}
+
+/// Fewest answers left on average after the guess, counting answers by weight.
+pub struct ExpectedSize;
+
+impl Strategy for ExpectedSize {
+    fn name(&self) -> &'static str {
+        "expected-size"
+    }
+
+    fn score(
+        &self,
+        matrix: &PatternMatrix,
+        guess: usize,
+        answers: &[usize],
+        priors: &[f64],
+    ) -> f64 {
+        let total: f64 = answers.iter().map(|answer| priors[*answer]).sum();
+        let squares: f64 = matrix
+            .weighted_distribution(guess, answers, priors)
+            .into_iter()
+            .map(|weight| weight * weight)
+            .sum();
+        -(squares / total)
+    }
+}
+
+/// Entropy of the guess plus the expected entropy of the best follow up.
+/// Only the `width` guesses with the most entropy are looked ahead from, and
+/// follow ups are picked among the answers left.
+pub struct Lookahead {
+    pub width: usize,
+}
+
+impl Strategy for Lookahead {
+    fn name(&self) -> &'static str {
+        "lookahead"
+    }
+
+    fn score(
+        &self,
+        matrix: &PatternMatrix,
+        guess: usize,
+        answers: &[usize],
+        priors: &[f64],
+    ) -> f64 {
+        let entropy = Naive::weighted_entropy(matrix, guess, answers, priors);
+        let total: f64 = answers.iter().map(|answer| priors[*answer]).sum();
+        let solved = matrix.solved();
+        let follow_up: f64 = matrix
+            .partition(guess, answers)
+            .iter()
+            .filter(|(pattern, bucket)| **pattern != solved && bucket.len() > 1)
+            .map(|(_, bucket)| {
+                let next = MaxEntropy.guess(matrix, bucket, bucket, priors);
+                let best = MaxEntropy.score(matrix, next, bucket, priors);
+                let weight: f64 = bucket.iter().map(|answer| priors[*answer]).sum();
+                best * weight / total
+            })
+            .sum();
+        entropy + follow_up
+    }
+
+    fn guess(
+        &self,
+        matrix: &PatternMatrix,
+        answers: &[usize],
+        candidates: &[usize],
+        priors: &[f64],
+    ) -> usize {
+        //possible answers go first among equal entropies, so the last answer
+        //left always makes the shortlist
+        let possible: HashSet<usize> = answers.iter().copied().collect();
+        let mut ranked: Vec<(usize, f64, bool)> = candidates
+            .iter()
+            .map(|guess| {
+                let entropy = Naive::weighted_entropy(matrix, *guess, answers, priors);
+                (*guess, entropy, possible.contains(guess))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)));
+        ranked.truncate(self.width);
+        ranked
+            .into_iter()
+            .map(|(guess, _, possible)| {
+                (guess, self.score(matrix, guess, answers, priors), possible)
+            })
+            .reduce(|a, b| if (a.1, a.2) >= (b.1, b.2) { a } else { b })
+            .expect("no words available to make a guess")
+            .0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wordleMod::Patterns::test::{setup, WORDS};
+
+    #[test]
+    fn minimax_minimizes_the_worst_bucket() {
+        //"fhpzz" tells every answer apart, "kills" leaves fills/hills/pills together
+        let (_, matrix) = setup("kills fills hills pills bills fhpzz");
+        let answers = [0, 1, 2, 3];
+        let priors = [1.0; 6];
+        assert_eq!(Minimax.score(&matrix, 0, &answers, &priors), -3.0);
+        assert_eq!(Minimax.score(&matrix, 5, &answers, &priors), -1.0);
+        assert_eq!(Minimax.guess(&matrix, &answers, &[0, 4, 5], &priors), 5);
+        assert_eq!(ExpectedSize.score(&matrix, 5, &answers, &priors), -1.0);
+        assert_eq!(ExpectedSize.score(&matrix, 0, &answers, &priors), -2.5);
+    }
+
+    #[test]
+    fn answers_count_by_their_priors() {
+        let (_, matrix) = setup("kills fills hills pills");
+        let answers = [0, 1, 2, 3];
+        //"kills" alone weighs as much as the other three together
+        let priors = [3.0, 1.0, 1.0, 1.0];
+        assert_eq!(MaxEntropy.score(&matrix, 0, &answers, &priors), 1.0);
+        assert_eq!(Minimax.score(&matrix, 0, &answers, &priors), -3.0);
+        assert_eq!(ExpectedSize.score(&matrix, 0, &answers, &priors), -3.0);
+    }
+
+    #[test]
+    fn ties_go_to_a_possible_answer() {
+        let (_, matrix) = setup("kills fills fhpzz");
+        //every guess tells the two answers apart
+        assert_eq!(MaxEntropy.guess(&matrix, &[0, 1], &[2, 0, 2], &[1.0; 3]), 0);
+    }
+
+    #[test]
+    fn every_kind_guesses_a_candidate() {
+        let (words, matrix) = setup(WORDS);
+        let answers: Vec<usize> = (0..words.len()).collect();
+        let priors = vec![1.0; words.len()];
+        for kind in StrategyKind::ALL {
+            let strategy = kind.strategy();
+            let guess = strategy.guess(&matrix, &answers, &answers[2..], &priors);
+            assert!(guess >= 2, "{} guessed {}", strategy.name(), guess);
+        }
+    }
+
+    #[test]
+    fn lookahead_adds_the_follow_up() {
+        let (_, matrix) = setup("kills fills hills pills bills");
+        let answers = [0, 1, 2, 3, 4];
+        let priors = [1.0; 5];
+        let entropy = MaxEntropy.score(&matrix, 0, &answers, &priors);
+        assert!(Lookahead { width: 5 }.score(&matrix, 0, &answers, &priors) > entropy);
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{Alphabet::Alphabet, Naive, Patterns::PatternMatrix, Wordle::ByteStr};
+
+/// Limits of the decision tree search.
+#[derive(Clone, Copy, Debug)]
+pub struct TreeOptions {
+    /// Most guesses any answer may take.
+    pub max_depth: usize,
+    /// Only this many guesses, the ones with the highest entropy, are tried
+    /// at every node.
+    pub breadth: usize,
+}
+
+struct Node {
+    guess: usize,
+    /// The guess is one of the answers left at this node.
+    solves: bool,
+    /// Subtrees by mask radix, the all green mask has none.
+    children: Vec<(usize, Node)>,
+}
+
+/// Guess to make for every mask, built to minimize the expected number of
+/// guesses over all answers.
+pub struct DecisionTree {
+    root: Node,
+    answers: usize,
+    /// Guesses summed over every answer.
+    cost: usize,
+}
+
+#[derive(Serialize)]
+struct JsonNode {
+    guess: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    children: BTreeMap<String, JsonNode>,
+}
+
+#[derive(Serialize)]
+struct JsonTree {
+    average: f64,
+    worst: usize,
+    tree: JsonNode,
+}
+
+struct Search<'a> {
+    matrix: &'a PatternMatrix,
+    candidates: &'a [usize],
+    options: TreeOptions,
+}
+
+/// Fewest guesses `count` answers can take together: one may be guessed
+/// right away, every other one needs a second guess at least.
+fn lower_bound(count: usize) -> usize {
+    (2 * count).saturating_sub(1)
+}
+
+impl Search<'_> {
+    /// Cheapest tree for `answers` costing less than `budget` within `depth`
+    /// guesses, `None` when there is none.
+    fn best(&self, answers: &[usize], depth: usize, budget: usize) -> Option<(usize, Node)> {
+        if depth == 0 || lower_bound(answers.len()) >= budget {
+            return None;
+        }
+        if let [answer] = answers {
+            let leaf = Node {
+                guess: *answer,
+                solves: true,
+                children: Vec::new(),
+            };
+            return Some((1, leaf));
+        }
+        if depth == 1 {
+            return None;
+        }
+        let mut best: Option<(usize, Node)> = None;
+        for guess in self.ranked_guesses(answers) {
+            let budget = best.as_ref().map_or(budget, |(cost, _)| *cost);
+            if let Some(found) = self.try_guess(guess, answers, depth, budget) {
+                best = Some(found);
+            }
+        }
+        best
+    }
+
+    /// Guesses to try for `answers`, the highest entropy first, ties going
+    /// to possible answers.
+    fn ranked_guesses(&self, answers: &[usize]) -> Vec<usize> {
+        let mut scored: Vec<(usize, f64, bool)> = self
+            .candidates
+            .iter()
+            .map(|guess| {
+                let entropy = Naive::matrix_entropy(self.matrix, *guess, answers);
+                (*guess, entropy, answers.contains(guess))
+            })
+            //a guess that doesn't split the answers only wastes a turn
+            .filter(|(_, entropy, solves)| *entropy > 0.0 || *solves)
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)));
+        scored.truncate(self.options.breadth);
+        scored.into_iter().map(|(guess, _, _)| guess).collect()
+    }
+
+    fn try_guess(
+        &self,
+        guess: usize,
+        answers: &[usize],
+        depth: usize,
+        budget: usize,
+    ) -> Option<(usize, Node)> {
+        let solved = self.matrix.solved();
+        let mut buckets = self.matrix.partition(guess, answers);
+        let solves = buckets.remove(&solved).is_some();
+        //this guess counts for every answer, the rest is bounded per bucket
+        let mut cost = answers.len()
+            + buckets
+                .values()
+                .map(|bucket| lower_bound(bucket.len()))
+                .sum::<usize>();
+        if cost >= budget {
+            return None;
+        }
+        let mut children = Vec::with_capacity(buckets.len());
+        //the biggest buckets first, they are the likeliest to exceed the budget
+        let mut buckets: Vec<_> = buckets.into_iter().collect();
+        buckets.sort_by_key(|(_, bucket)| std::cmp::Reverse(bucket.len()));
+        for (pattern, bucket) in buckets {
+            let bound = lower_bound(bucket.len());
+            let (sub_cost, node) = self.best(&bucket, depth - 1, budget - cost + bound)?;
+            cost += sub_cost - bound;
+            children.push((pattern, node));
+        }
+        children.sort_by_key(|(pattern, _)| *pattern);
+        let node = Node {
+            guess,
+            solves,
+            children,
+        };
+        Some((cost, node))
+    }
+}
+
+impl DecisionTree {
+    /// Searches a tree for `answers` using the `candidates` guesses, both
+    /// matrix indices. `None` when there are no answers or when some answer
+    /// can't be found within `options.max_depth` guesses.
+    pub fn new(
+        matrix: &PatternMatrix,
+        answers: &[usize],
+        candidates: &[usize],
+        options: TreeOptions,
+    ) -> Option<DecisionTree> {
+        if answers.is_empty() {
+            return None;
+        }
+        let search = Search {
+            matrix,
+            candidates,
+            options,
+        };
+        let (cost, root) = search.best(answers, options.max_depth, usize::MAX)?;
+        Some(DecisionTree {
+            root,
+            answers: answers.len(),
+            cost,
+        })
+    }
+
+    pub fn average_guesses(&self) -> f64 {
+        self.cost as f64 / self.answers as f64
+    }
+
+    pub fn worst_guesses(&self) -> usize {
+        fn worst(node: &Node, depth: usize) -> usize {
+            node.children
+                .iter()
+                .map(|(_, child)| worst(child, depth + 1))
+                .chain(node.solves.then_some(depth))
+                .max()
+                .unwrap_or(depth)
+        }
+        worst(&self.root, 1)
+    }
+
+    /// The first guess, then the next guess for every mask (written as the
+    /// digits of [`super::Wordle::Correctness`] per letter), with the average
+    /// and worst number of guesses. `words` are the words the matrix was
+    /// built from, coded by `alphabet`.
+    pub fn to_json(&self, words: &[ByteStr], alphabet: &Alphabet, size: usize) -> String {
+        fn convert(node: &Node, words: &[ByteStr], alphabet: &Alphabet, size: usize) -> JsonNode {
+            JsonNode {
+                guess: alphabet.decode(&words[node.guess]),
+                children: node
+                    .children
+                    .iter()
+                    .map(|(pattern, child)| {
+                        (
+                            mask_digits(*pattern, size),
+                            convert(child, words, alphabet, size),
+                        )
+                    })
+                    .collect(),
+            }
+        }
+        let tree = JsonTree {
+            average: self.average_guesses(),
+            worst: self.worst_guesses(),
+            tree: convert(&self.root, words, alphabet, size),
+        };
+        serde_json::to_string(&tree).expect("the tree only holds strings and numbers")
+    }
+}
+
+fn mask_digits(mut pattern: usize, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let digit = pattern % 3;
+            pattern /= 3;
+            char::from(b'0' + digit as u8)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wordleMod::Patterns::test::{setup, WORDS};
+    use crate::wordleMod::Wordle::Correctness;
+
+    /// Guesses it takes to find `answer` following the tree.
+    fn play(tree: &DecisionTree, matrix: &PatternMatrix, answer: usize) -> usize {
+        let mut node = &tree.root;
+        let mut guesses = 1;
+        while node.guess != answer {
+            let pattern = matrix.pattern(node.guess, answer);
+            node = &node
+                .children
+                .iter()
+                .find(|(p, _)| *p == pattern)
+                .expect("every mask has a subtree")
+                .1;
+            guesses += 1;
+        }
+        guesses
+    }
+
+    #[test]
+    fn tree_finds_every_answer() {
+        let (words, matrix) = setup(WORDS);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let options = TreeOptions {
+            max_depth: 6,
+            breadth: 5,
+        };
+        let tree = DecisionTree::new(&matrix, &all, &all, options).unwrap();
+        let guesses: Vec<usize> = all.iter().map(|a| play(&tree, &matrix, *a)).collect();
+        assert_eq!(tree.cost, guesses.iter().sum::<usize>());
+        assert_eq!(tree.worst_guesses(), *guesses.iter().max().unwrap());
+        assert!(tree.average_guesses() < 2.5);
+    }
+
+    #[test]
+    fn wider_search_is_never_worse() {
+        let (words, matrix) = setup(WORDS);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let cost = |breadth| {
+            let options = TreeOptions {
+                max_depth: 6,
+                breadth,
+            };
+            DecisionTree::new(&matrix, &all, &all, options)
+                .unwrap()
+                .cost
+        };
+        assert!(cost(words.len()) <= cost(1));
+    }
+
+    #[test]
+    fn depth_limit_can_fail() {
+        let (words, matrix) = setup(WORDS);
+        let all: Vec<usize> = (0..words.len()).collect();
+        let options = TreeOptions {
+            max_depth: 1,
+            breadth: 5,
+        };
+        assert!(DecisionTree::new(&matrix, &all, &all, options).is_none());
+        assert!(DecisionTree::new(&matrix, &all[..1], &all, options).is_some());
+        assert!(DecisionTree::new(&matrix, &[], &all, options).is_none());
+        assert_eq!(lower_bound(0), 0);
+    }
+
+    #[test]
+    fn exports_json() {
+        let (words, matrix) = setup(WORDS);
+        let answers = [0, 1, 2];
+        let options = TreeOptions {
+            max_depth: 3,
+            breadth: 3,
+        };
+        let tree = DecisionTree::new(&matrix, &answers, &answers, options).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&tree.to_json(&words, &Alphabet::default(), 5)).unwrap();
+        assert_eq!(json["worst"], tree.worst_guesses());
+        let first = json["tree"]["guess"].as_str().unwrap();
+        let children = json["tree"]["children"].as_object().unwrap();
+        assert_eq!(children.len(), 2);
+        for (mask, child) in children {
+            let answer = child["guess"].as_str().unwrap();
+            let expected = Correctness::check(answer.as_bytes(), first.as_bytes());
+            let digits: String = expected.iter().map(|c| (*c as u8).to_string()).collect();
+            assert_eq!(*mask, digits);
+        }
+    }
+}
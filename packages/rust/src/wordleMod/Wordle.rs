@@ -1,106 +1,426 @@
-use super::{Guess, WORDLE_SIZE};
-use super::{Naive, WORDS};
+use super::Alphabet::{Alphabet, Normalization, Segmentation};
+use super::Naive;
+use super::Patterns::PatternMatrix;
+use super::Simulation::{play_out, Player, SimulationReport};
+use super::Strategy::{Strategy, StrategyKind};
+use super::Tree::{DecisionTree, TreeOptions};
+use super::{word_list, Guess, MAX_WORDLE_SIZE};
+use serde::Serialize;
+use std::cell::OnceCell;
 use std::collections::HashSet;
+use std::fmt;
 use wasm_bindgen::prelude::*;
-pub type ByteStr = [u8; 5];
-use super::super::random;
+pub type ByteStr = Vec<u8>;
+
+/// `simulate` gives up after this many guesses.
+const MAX_SIMULATED_GUESSES: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordleError {
+    /// The word doesn't have as many letters as the answer.
+    WrongLength { word: String, expected: usize },
+    /// Empty words or words longer than [`MAX_WORDLE_SIZE`].
+    UnsupportedLength(usize),
+    /// No list was passed and none is bundled for this length.
+    NoWordList(usize),
+    /// Hard mode: a letter found in the right spot moved away.
+    MustKeep { letter: String, position: usize },
+    /// Hard mode: a revealed letter isn't used (as many times) again.
+    MustUse { letter: String },
+    /// No decision tree finds every answer within this many guesses.
+    TooDeep(usize),
+    /// Every answer was ruled out, the answer has been guessed.
+    NoAnswersLeft,
+    /// The word isn't in the word list.
+    UnknownWord(String),
+    /// The weight of this word is missing or not a positive number.
+    BadPrior(String),
+    /// A simulation didn't find the answer within this many guesses.
+    OutOfGuesses(usize),
+    /// The word list uses more than this many letters outside ASCII.
+    TooManyLetters(usize),
+    /// The word list has no word of this many letters.
+    EmptyWordList(usize),
+    /// The game is over, no more guesses are taken.
+    AlreadySolved,
+}
+
+impl fmt::Display for WordleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordleError::WrongLength { word, expected } => {
+                write!(f, "\"{}\" is not a {} letters word", word, expected)
+            }
+            WordleError::UnsupportedLength(size) => write!(
+                f,
+                "words of {} letters are not supported, the limit is {}",
+                size, MAX_WORDLE_SIZE
+            ),
+            WordleError::NoWordList(size) => {
+                write!(f, "there is no bundled list of {} letters words", size)
+            }
+            WordleError::MustKeep { letter, position } => write!(
+                f,
+                "letter {} must be {}",
+                position + 1,
+                letter.to_uppercase()
+            ),
+            WordleError::MustUse { letter } => {
+                write!(f, "guess must contain {}", letter.to_uppercase())
+            }
+            WordleError::TooDeep(depth) => {
+                write!(f, "no strategy finds every answer in {} guesses", depth)
+            }
+            WordleError::NoAnswersLeft => write!(f, "no possible answer is left"),
+            WordleError::UnknownWord(word) => write!(f, "\"{}\" is not in the word list", word),
+            WordleError::BadPrior(word) => write!(f, "\"{}\" has no valid weight", word),
+            WordleError::OutOfGuesses(guesses) => {
+                write!(f, "the answer was not found in {} guesses", guesses)
+            }
+            WordleError::TooManyLetters(limit) => write!(
+                f,
+                "the word list uses more than {} letters outside ASCII",
+                limit
+            ),
+            WordleError::EmptyWordList(size) => {
+                write!(f, "the word list has no {} letters words", size)
+            }
+            WordleError::AlreadySolved => write!(f, "the game is already solved"),
+        }
+    }
+}
+
+impl std::error::Error for WordleError {}
+
+impl From<WordleError> for JsValue {
+    fn from(error: WordleError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
 
 #[wasm_bindgen]
 pub struct Wordle {
     history: Vec<Guess>,
     answer: ByteStr,
+    /// Every allowed guess, the possible answers first.
     words: Vec<ByteStr>,
+    /// How many of `words` can be the answer.
+    answers: usize,
+    /// Relative likelihood of each answer, 1 unless set.
+    priors: Vec<f64>,
+    /// Guesses must keep greens in place and reuse yellows.
+    hard_mode: bool,
+    /// Patterns of every guess against every answer, built on first use.
+    matrix: OnceCell<PatternMatrix>,
+    /// Solver used by `simulate`.
+    strategy: StrategyKind,
+    /// Codes of the letters of `words`.
+    alphabet: Alphabet,
 }
 
 #[wasm_bindgen]
 impl Wordle {
-    pub fn new(words: Option<String>, answer: &str) -> Self {
-        let mut wordle = Wordle {
+    /// The word length is the one of `answer`. `words` may mix lengths, only
+    /// the words as long as the answer are kept; without it the bundled list
+    /// for that length is used.
+    pub fn new(words: Option<String>, answer: &str) -> Result<Wordle, WordleError> {
+        Wordle::with_alphabet(words, answer, Segmentation::Chars, Normalization::None)
+    }
+
+    /// Like `new`, splitting words into letters with `segmentation` and
+    /// comparing them after `normalization`.
+    pub fn with_alphabet(
+        words: Option<String>,
+        answer: &str,
+        segmentation: Segmentation,
+        normalization: Normalization,
+    ) -> Result<Wordle, WordleError> {
+        let mut alphabet = Alphabet::new(segmentation, normalization);
+        let answer = alphabet.learn(answer)?;
+        let mut words = parse_words(words, answer.len(), &mut alphabet)?;
+        if !words.contains(&answer) {
+            words.push(answer.clone())
+        }
+        Ok(Wordle {
             history: Vec::new(),
-            answer: str5(answer),
-            words: words
-                .unwrap_or(WORDS.to_string())
-                .split_whitespace()
-                .map(str5)
-                .collect(),
-        };
-        if !wordle.words.contains(&wordle.answer) {
-            wordle.words.push(wordle.answer)
+            answer,
+            answers: words.len(),
+            priors: vec![1.0; words.len()],
+            words,
+            hard_mode: false,
+            matrix: OnceCell::new(),
+            strategy: StrategyKind::MaxEntropy,
+            alphabet,
+        })
+    }
+
+    /// Like `with_alphabet` with `answers` as the possible answers, and
+    /// `guesses` as extra words that may be guessed but are never the answer.
+    pub fn with_guesses(
+        answers: Option<String>,
+        guesses: &str,
+        answer: &str,
+        segmentation: Segmentation,
+        normalization: Normalization,
+    ) -> Result<Wordle, WordleError> {
+        let mut wordle = Wordle::with_alphabet(answers, answer, segmentation, normalization)?;
+        let mut known: HashSet<ByteStr> = wordle.words.iter().cloned().collect();
+        let guesses = parse_words(
+            Some(guesses.to_string()),
+            wordle.size(),
+            &mut wordle.alphabet,
+        )?;
+        wordle.words.extend(
+            guesses
+                .into_iter()
+                .filter(|word| known.insert(word.clone())),
+        );
+        Ok(wordle)
+    }
+
+    /// Weights answers by likelihood, e.g. by word frequency. `priors` holds
+    /// `word weight` pairs, weights must be positive and finite; unlisted
+    /// answers keep a weight of 1. Words that aren't answers are rejected,
+    /// and nothing changes when any pair is bad.
+    pub fn set_priors(&mut self, priors: &str) -> Result<(), WordleError> {
+        let mut weights = Vec::new();
+        let mut fields = priors.split_whitespace();
+        while let Some(word) = fields.next() {
+            let weight: f64 = fields
+                .next()
+                .and_then(|weight| weight.parse().ok())
+                .filter(|weight: &f64| weight.is_finite() && *weight > 0.0)
+                .ok_or_else(|| WordleError::BadPrior(word.to_string()))?;
+            let index = self
+                .answer_index(&self.alphabet.encode(word))
+                .ok_or_else(|| WordleError::UnknownWord(word.to_string()))?;
+            weights.push((index, weight));
         }
-        wordle
+        for (index, weight) in weights {
+            self.priors[index] = weight;
+        }
+        Ok(())
+    }
+
+    /// Letters per word.
+    pub fn size(&self) -> usize {
+        self.answer.len()
+    }
+
+    pub fn hard_mode(&self) -> bool {
+        self.hard_mode
+    }
+
+    pub fn set_hard_mode(&mut self, hard_mode: bool) {
+        self.hard_mode = hard_mode;
+    }
+
+    pub fn strategy(&self) -> StrategyKind {
+        self.strategy
+    }
+
+    pub fn set_strategy(&mut self, strategy: StrategyKind) {
+        self.strategy = strategy;
     }
 
     fn available_words(&self) -> Vec<ByteStr> {
-        filter_with(&self.words, &self.history)
+        filter_with(&self.words[..self.answers], &self.history)
     }
 
-    pub fn simulate(&mut self, answer: &str) -> usize {
-        let answer = str5(answer);
-        self.history = Vec::new();
-        for i in 0..=16 {
-            let guess = Naive::guess(&self.available_words());
-            if guess == answer {
-                self.reset();
-                return i;
-            }
-            let correcness = Correctness::check(&answer, &guess);
-            self.history.push(Guess {
-                mask: correcness,
-                word: guess,
+    fn available_priors(&self) -> Vec<f64> {
+        self.available_indices()
+            .into_iter()
+            .map(|index| self.priors[index])
+            .collect()
+    }
+
+    fn matrix(&self) -> &PatternMatrix {
+        self.matrix
+            .get_or_init(|| PatternMatrix::new(&self.words, &self.words[..self.answers]))
+    }
+
+    /// Indices of the words that can still be the answer.
+    fn available_indices(&self) -> Vec<usize> {
+        (0..self.answers)
+            .filter(|index| {
+                let word = &self.words[*index];
+                self.history
+                    .iter()
+                    .all(|guess| guess.matches(word) && &guess.word != word)
+            })
+            .collect()
+    }
+
+    /// Guesses the selected strategy needs to find `answer`, the final one
+    /// included.
+    pub fn simulate(&self, answer: &str) -> Result<usize, WordleError> {
+        let answer = self.index_of(answer)?;
+        self.simulate_with(answer, self.strategy.strategy().as_ref())
+    }
+
+    /// Guesses every strategy needs for `answer`, as `[name, guesses]` pairs.
+    pub fn compare_strategies(&self, answer: &str) -> Result<JsValue, WordleError> {
+        let answer = self.index_of(answer)?;
+        let results = StrategyKind::ALL
+            .iter()
+            .map(|kind| {
+                let strategy = kind.strategy();
+                Ok((
+                    strategy.name(),
+                    self.simulate_with(answer, strategy.as_ref())?,
+                ))
             })
+            .collect::<Result<Vec<_>, WordleError>>()?;
+        Ok(serde_wasm_bindgen::to_value(&results)
+            .expect("could not turn the results into a js value"))
+    }
+
+    /// Plays the selected strategy against every word of the list: how many
+    /// answers took each number of guesses, how many weren't found within
+    /// `max_guesses` and the guesses per word. The report is a plain object
+    /// `{ strategy, histogram: { [guesses]: answers }, failures, average,
+    /// results: [{ word, guesses }] }` where `guesses` is null for the
+    /// answers that weren't found.
+    pub fn simulate_all(&self, max_guesses: usize) -> JsValue {
+        self.report(self.strategy, max_guesses)
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .expect("could not turn the report into a js value")
+    }
+
+    pub fn play(&mut self, guess_word: &str) -> Result<JsValue, WordleError> {
+        let (correcness, information_gain) = self.play_word(guess_word)?;
+        let mask: Vec<u32> = correcness.iter().map(|value| *value as u32).collect();
+        Ok(serde_wasm_bindgen::to_value(&(mask, information_gain))
+            .expect("could not turn the result into a js value"))
+    }
+
+    /// Forgets the guesses made so far, the answer and word list stay.
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    /// The 10 best guesses out of every allowed word, in hard mode only the
+    /// legal ones.
+    pub fn calc_best_guesses(&self) -> JsValue {
+        let guesses: Vec<_> = self
+            .best_guesses()
+            .into_iter()
+            .map(|(v, score)| (self.alphabet.decode(&v), score))
+            .collect();
+        serde_wasm_bindgen::to_value(&guesses).expect("could not turn guess into js value")
+    }
+
+    /// Decision tree minimizing the expected number of guesses over the
+    /// answers still possible, as JSON with its average and worst number of
+    /// guesses. Only the `breadth` guesses with the most entropy are tried at
+    /// each step.
+    pub fn decision_tree(&self, max_depth: usize, breadth: usize) -> Result<String, WordleError> {
+        let answers = self.available_indices();
+        if answers.is_empty() {
+            return Err(WordleError::NoAnswersLeft);
         }
-        self.reset();
-        panic!("Max guesses reached");
+        let candidates: Vec<usize> = (0..self.words.len()).collect();
+        let options = TreeOptions { max_depth, breadth };
+        let tree = DecisionTree::new(self.matrix(), &answers, &candidates, options)
+            .ok_or(WordleError::TooDeep(max_depth))?;
+        Ok(tree.to_json(&self.words, &self.alphabet, self.size()))
+    }
+
+    pub fn distribution_of(&self, guess: &str) -> Result<Vec<usize>, WordleError> {
+        let valid_words = &self.available_words();
+        Ok(Guess::calc_distribution(
+            valid_words,
+            &to_word(guess, self.size(), &self.alphabet)?,
+        ))
     }
 
-    pub fn play(&mut self, guess_word: &str) -> JsValue {
-        let guess_word = str5(guess_word);
+    pub fn entropy_of(&self, word: &str) -> Result<f64, WordleError> {
+        Ok(Naive::entropy_of(
+            &to_word(word, self.size(), &self.alphabet)?,
+            &self.available_words(),
+            &self.available_priors(),
+        ))
+    }
+}
+
+impl Wordle {
+    /// [`Wordle::play`] without the conversion to js: the mask and the bits
+    /// of information the guess gave.
+    fn play_word(&mut self, guess_word: &str) -> Result<(Vec<Correctness>, f64), WordleError> {
+        let guess_word = to_word(guess_word, self.size(), &self.alphabet)?;
+        if self.hard_mode {
+            if let Some(error) = self
+                .history
+                .iter()
+                .find_map(|guess| guess.hard_mode_violation(&guess_word, &self.alphabet))
+            {
+                return Err(error);
+            }
+        }
         let correcness = Correctness::check(&self.answer, &guess_word);
         let guess = Guess {
             mask: correcness.clone(),
             word: guess_word,
         };
-        let information_gain = Naive::guess_information(&self.available_words(), &guess);
+        let information_gain =
+            Naive::guess_information(&self.available_words(), &self.available_priors(), &guess);
         self.history.push(guess);
-        let mask: Vec<u32> = correcness.iter().map(|value| *value as u32).collect();
-        JsValue::from_serde(&(mask, information_gain))
-            .expect("could not turn the result into a js value")
+        Ok((correcness, information_gain))
     }
 
-    pub fn reset(&mut self) {
-        *self = Wordle::new(None, "hello");
+    fn answer_index(&self, word: &[u8]) -> Option<usize> {
+        self.words[..self.answers]
+            .iter()
+            .position(|other| other == word)
     }
 
-    pub fn calc_best_guesses(&self) -> JsValue {
-        let words = self.available_words();
-        let random_words: Vec<_> = (0..500)
-            .filter_map(|_| get_random_word(&words))
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect();
-        let guesses: Vec<_> = Naive::calc_best_guesses(&words, &random_words)
-            .into_iter()
-            .map(|(v, score)| {
-                let string = String::from_utf8(v.to_vec()).expect("could not decode utf8 string");
-                (string, score)
-            })
-            .collect();
-        JsValue::from_serde(&guesses).expect("could not turn guess into js value")
+    fn index_of(&self, word: &str) -> Result<usize, WordleError> {
+        self.answer_index(&to_word(word, self.size(), &self.alphabet)?)
+            .ok_or(WordleError::UnknownWord(word.to_string()))
     }
 
-    pub fn distribution_of(&self, guess: &str) -> Vec<usize> {
-        let valid_words = &filter_with(&self.words, &self.history);
-        Guess::calc_distribution(valid_words, &str5(guess)).into()
+    fn simulate_with(&self, answer: usize, strategy: &dyn Strategy) -> Result<usize, WordleError> {
+        let answers: Vec<usize> = (0..self.answers).collect();
+        let candidates: Vec<usize> = (0..self.words.len()).collect();
+        let player = self.player(&candidates, strategy);
+        play_out(&player, &answers, answer, MAX_SIMULATED_GUESSES)
+            .ok_or(WordleError::OutOfGuesses(MAX_SIMULATED_GUESSES))
     }
 
-    pub fn entropy_of(&self, word: &str) -> f64 {
-        Naive::entropy_of(&str5(word), &self.available_words())
+    fn report(&self, kind: StrategyKind, max_guesses: usize) -> SimulationReport {
+        let answers: Vec<usize> = (0..self.answers).collect();
+        let candidates: Vec<usize> = (0..self.words.len()).collect();
+        let strategy = kind.strategy();
+        let player = self.player(&candidates, strategy.as_ref());
+        SimulationReport::new(&player, &self.words, &self.alphabet, &answers, max_guesses)
+    }
+
+    /// A simulated player guessing among `candidates` with this game's priors.
+    fn player<'a>(&'a self, candidates: &'a [usize], strategy: &'a dyn Strategy) -> Player<'a> {
+        Player {
+            matrix: self.matrix(),
+            candidates,
+            priors: &self.priors,
+            strategy,
+        }
     }
-}
 
-fn get_random_word(words: &[ByteStr]) -> Option<ByteStr> {
-    let index = (words.len() as f64 * random()).round() as usize;
-    words.get(index).map(|str| *str)
+    fn best_guesses(&self) -> Vec<(ByteStr, f64)> {
+        let candidates: Vec<usize> = if self.hard_mode {
+            Naive::hard_mode_guesses(&self.words, &self.history, &self.alphabet)
+        } else {
+            (0..self.words.len()).collect()
+        };
+        Naive::calc_best_guesses(
+            self.matrix(),
+            &self.available_indices(),
+            &candidates,
+            &self.priors,
+        )
+        .into_iter()
+        .map(|(guess, score)| (self.words[guess].clone(), score))
+        .collect()
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -110,97 +430,129 @@ pub enum Correctness {
     Correct = 2,
 }
 
-struct CharCounter {
-    chars: ByteStr,
-    count: ByteStr,
-}
-
-impl CharCounter {
-    pub fn new(word: &ByteStr) -> Self {
-        let mut chars = [0u8; WORDLE_SIZE];
-        let mut count = [0u8; WORDLE_SIZE];
-        word.iter().for_each(|char| {
-            for (index, item) in chars.iter_mut().enumerate() {
-                if *item == 0 {
-                    (*item) = *char
-                }
-                if *item == *char {
-                    count[index] += 1;
-                    break;
-                }
-            }
-        });
-        CharCounter { chars, count }
+impl Correctness {
+    /// Both words must have the same length, at most [`MAX_WORDLE_SIZE`].
+    pub fn check(answer: &[u8], guess: &[u8]) -> Vec<Self> {
+        let mut mask = vec![Correctness::Wrong; guess.len()];
+        Correctness::check_into(answer, guess, &mut mask);
+        mask
     }
 
-    pub fn find_count(&self, c: u8) -> usize {
-        self.chars
-            .iter()
-            .enumerate()
-            .find(|(_, &char)| char == c)
-            .map(|(index, _)| self.count[index])
-            .unwrap_or(0) as usize
+    /// Mask radix of [`Correctness::check`] without allocating the mask.
+    pub fn pattern(answer: &[u8], guess: &[u8]) -> usize {
+        let mut mask = [Correctness::Wrong; MAX_WORDLE_SIZE];
+        let mask = &mut mask[..guess.len()];
+        Correctness::check_into(answer, guess, mask);
+        Correctness::mask_radix(mask)
     }
 
-    pub fn decrement(&mut self, c: u8) {
-        if let Some((index, _)) = self.chars.iter().enumerate().find(|(_, &char)| char == c) {
-            self.count[index] -= 1;
-        }
-    }
-}
-
-impl Correctness {
-    pub fn check(answer: &ByteStr, guess: &ByteStr) -> [Self; WORDLE_SIZE] {
-        let mut mask = [Correctness::Wrong; WORDLE_SIZE];
-        let mut not_correct = [0u8; WORDLE_SIZE];
+    fn check_into(answer: &[u8], guess: &[u8], mask: &mut [Correctness]) {
+        debug_assert_eq!(answer.len(), guess.len());
+        //letters of the answer not matched by a green, each one can turn a
+        //single guess letter yellow
+        let mut not_correct = [0u8; MAX_WORDLE_SIZE];
+        let mut left = 0;
         guess.iter().enumerate().for_each(|(index, &c)| {
             if c == answer[index] {
                 mask[index] = Correctness::Correct
             } else {
-                not_correct[index] = answer[index];
+                not_correct[left] = answer[index];
+                left += 1;
             }
         });
-        let mut counter = CharCounter::new(&not_correct);
+        let not_correct = &mut not_correct[..left];
         mask.iter_mut()
             .enumerate()
             .for_each(|(index, correctness)| {
-                if *correctness != Correctness::Correct && counter.find_count(guess[index]) > 0 {
-                    counter.decrement(guess[index]);
+                if *correctness == Correctness::Correct {
+                    return;
+                }
+                if let Some(found) = not_correct.iter_mut().find(|c| **c == guess[index]) {
+                    *found = 0;
                     *correctness = Correctness::Misplaced;
                 }
             });
-        mask
     }
 
-    pub fn mask_radix(mask: &[Correctness; WORDLE_SIZE]) -> usize {
-        mask.iter().enumerate().fold(0, |acc, (index, value)| {
-            acc + *value as usize * 3usize.pow(index as u32)
-        })
+    /// The mask of `size` letters [`Correctness::mask_radix`] gave `radix`.
+    pub fn from_radix(mut radix: usize, size: usize) -> Vec<Self> {
+        (0..size)
+            .map(|_| {
+                let value = match radix % 3 {
+                    0 => Correctness::Wrong,
+                    1 => Correctness::Misplaced,
+                    _ => Correctness::Correct,
+                };
+                radix /= 3;
+                value
+            })
+            .collect()
+    }
+
+    pub fn mask_radix(mask: &[Correctness]) -> usize {
+        mask.iter()
+            .rev()
+            .fold(0, |acc, value| acc * 3 + *value as usize)
     }
 }
 
-pub fn filter_with<'a>(all_words: &[ByteStr], history: &[Guess]) -> Vec<ByteStr> {
+pub fn filter_with(all_words: &[ByteStr], history: &[Guess]) -> Vec<ByteStr> {
     all_words
         .iter()
-        .filter(|&&word| {
+        .filter(|word| {
             history
                 .iter()
-                .all(|guess| guess.matches(&word) && guess.word != word)
+                .all(|guess| guess.matches(word) && &guess.word != *word)
         })
-        .map(|&str| str)
+        .cloned()
         .collect()
 }
 
-pub fn str5(val: &str) -> ByteStr {
-    val.as_bytes()
-        .try_into()
-        .expect("The word passed is not a 5 letters word")
+/// The words of `size` letters in `words`, or in the bundled list for that
+/// length when there is no `words`, coded by `alphabet`.
+pub fn parse_words(
+    words: Option<String>,
+    size: usize,
+    alphabet: &mut Alphabet,
+) -> Result<Vec<ByteStr>, WordleError> {
+    if size == 0 || size > MAX_WORDLE_SIZE {
+        return Err(WordleError::UnsupportedLength(size));
+    }
+    let words = match words {
+        Some(words) => words,
+        None => word_list(size)
+            .ok_or(WordleError::NoWordList(size))?
+            .to_string(),
+    };
+    let mut parsed = Vec::new();
+    for word in words.split_whitespace() {
+        //only the kept words may take codes, there are few to give out
+        if alphabet.split(word).len() == size {
+            parsed.push(alphabet.learn(word)?);
+        }
+    }
+    Ok(parsed)
+}
+
+/// `val` coded by `alphabet`, it must have `size` letters.
+pub fn to_word(val: &str, size: usize, alphabet: &Alphabet) -> Result<ByteStr, WordleError> {
+    let word = alphabet.encode(val);
+    if word.len() != size {
+        return Err(WordleError::WrongLength {
+            word: val.to_string(),
+            expected: size,
+        });
+    }
+    Ok(word)
 }
 
 #[cfg(test)]
 mod test {
-    use super::Correctness;
     use super::Correctness::{Correct, Misplaced, Wrong};
+    use super::{
+        to_word, Correctness, Guess, Normalization, Segmentation, StrategyKind, Wordle, WordleError,
+    };
+    use crate::wordleMod::Patterns::test::WORDS;
 
     #[test]
     fn test_check() {
@@ -214,6 +566,17 @@ mod test {
         assert_eq!(mask, [Wrong, Wrong, Correct, Misplaced, Wrong])
     }
 
+    #[test]
+    fn check_other_lengths() {
+        let mask = Correctness::check(b"moon", b"noon");
+        assert_eq!(mask, [Wrong, Correct, Correct, Correct]);
+        let mask = Correctness::check(b"planets", b"leaflet");
+        assert_eq!(
+            mask,
+            [Misplaced, Misplaced, Correct, Wrong, Wrong, Wrong, Misplaced]
+        );
+    }
+
     #[test]
     fn check_2letters_one_correct() {
         let mask = Correctness::check(b"aabcd", b"afagt");
@@ -221,4 +584,198 @@ mod test {
         let mask = Correctness::check(b"ajbcd", b"afagt");
         assert_eq!(mask, [Correct, Wrong, Wrong, Wrong, Wrong]);
     }
+
+    #[test]
+    fn keeps_words_as_long_as_the_answer() {
+        let wordle = Wordle::new(Some("cats dog planet moose".to_string()), "mice").unwrap();
+        assert_eq!(wordle.size(), 4);
+        assert_eq!(wordle.words, [b"cats".to_vec(), b"mice".to_vec()]);
+        assert_eq!(
+            Wordle::new(None, "planet").err(),
+            Some(WordleError::NoWordList(6))
+        );
+        assert_eq!(
+            Wordle::new(None, "").err(),
+            Some(WordleError::UnsupportedLength(0))
+        );
+    }
+
+    #[test]
+    fn simulates_six_letter_games() {
+        let words = "planet plants planed placed places glance branch stance";
+        let wordle = Wordle::new(Some(words.to_string()), "planet").unwrap();
+        assert!(wordle.simulate("stance").unwrap() < 6);
+        assert_eq!(
+            wordle.entropy_of("plan").err(),
+            Some(WordleError::WrongLength {
+                word: "plan".to_string(),
+                expected: 6
+            })
+        );
+    }
+
+    #[test]
+    fn scores_every_allowed_word() {
+        let words = "crane slate hello jello cello belly world";
+        let wordle = Wordle::new(Some(words.to_string()), "hello").unwrap();
+        let best = wordle.best_guesses();
+        assert_eq!(best.len(), 7);
+        for (word, score) in &best {
+            assert!(
+                (score
+                    - wordle
+                        .entropy_of(std::str::from_utf8(word).unwrap())
+                        .unwrap())
+                .abs()
+                    < 1e-9
+            );
+        }
+        assert!(best.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn strategies_can_be_compared() {
+        let mut wordle = Wordle::new(Some(WORDS.to_string()), "cigar").unwrap();
+        for kind in StrategyKind::ALL {
+            wordle.set_strategy(kind);
+            assert!(wordle.simulate("feign").unwrap() <= 5);
+            let report = wordle.report(kind, 6);
+            assert_eq!(report.failures, 0);
+            assert!(report.average < 3.5);
+        }
+        assert_eq!(
+            wordle.simulate("zebra"),
+            Err(WordleError::UnknownWord("zebra".to_string()))
+        );
+    }
+
+    #[test]
+    fn guesses_are_not_answers() {
+        let wordle = Wordle::with_guesses(
+            Some("kills fills hills pills".to_string()),
+            "fhpzz kills fhpzz",
+            "hills",
+            Segmentation::Chars,
+            Normalization::None,
+        )
+        .unwrap();
+        assert_eq!((wordle.answers, wordle.words.len()), (4, 5));
+        assert_eq!(wordle.best_guesses()[0].0, b"fhpzz".to_vec());
+        assert_eq!(
+            wordle.simulate("fhpzz"),
+            Err(WordleError::UnknownWord("fhpzz".to_string()))
+        );
+        let report = wordle.report(StrategyKind::MaxEntropy, 6);
+        assert_eq!(report.results.len(), 4);
+    }
+
+    #[test]
+    fn priors_weight_the_answers() {
+        let mut wordle = Wordle::new(Some("kills fills hills pills".to_string()), "hills").unwrap();
+        let uniform = wordle.entropy_of("kills").unwrap();
+        assert!((uniform - 0.811278).abs() < 1e-6);
+        wordle
+            .set_priors("kills 97 fills 1 hills 1 pills 1")
+            .unwrap();
+        assert!(wordle.entropy_of("kills").unwrap() < uniform);
+        assert_eq!(wordle.available_priors(), [97.0, 1.0, 1.0, 1.0]);
+        assert_eq!(
+            wordle.set_priors("kills many"),
+            Err(WordleError::BadPrior("kills".to_string()))
+        );
+        for weight in ["inf", "1e999", "NaN", "0", "-1"] {
+            assert_eq!(
+                wordle.set_priors(&format!("fills {}", weight)),
+                Err(WordleError::BadPrior("fills".to_string()))
+            );
+        }
+        assert_eq!(wordle.available_priors(), [97.0, 1.0, 1.0, 1.0]);
+        assert_eq!(
+            wordle.set_priors("fills 5 kills"),
+            Err(WordleError::BadPrior("kills".to_string()))
+        );
+        assert_eq!(
+            wordle.set_priors("fills 5 zebra 2"),
+            Err(WordleError::UnknownWord("zebra".to_string()))
+        );
+        assert_eq!(wordle.available_priors(), [97.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn guesses_use_the_alphabet() {
+        let wordle = Wordle::with_guesses(
+            Some("Limão manga".to_string()),
+            "LIMAO melão",
+            "manga",
+            Segmentation::Chars,
+            Normalization::FoldAccents,
+        )
+        .unwrap();
+        assert_eq!((wordle.answers, wordle.words.len()), (2, 3));
+        assert_eq!(wordle.alphabet.decode(&wordle.words[2]), "melao");
+    }
+
+    #[test]
+    fn plays_words_outside_ascii() {
+        let words = "книга слово город право школа";
+        let mut wordle = Wordle::new(Some(words.to_string()), "город").unwrap();
+        assert_eq!((wordle.size(), wordle.words.len()), (5, 5));
+        assert!(wordle.simulate("школа").unwrap() <= 5);
+        let guess = to_word("книга", 5, &wordle.alphabet).unwrap();
+        wordle.history.push(Guess {
+            mask: Correctness::check(&wordle.answer, &guess),
+            word: guess,
+        });
+        wordle.set_hard_mode(true);
+        let error = wordle.play("слово").err().unwrap();
+        assert_eq!(error.to_string(), "guess must contain Г");
+    }
+
+    #[test]
+    fn only_kept_words_learn_letters() {
+        //six letter words using far more letters than there are codes
+        let long: Vec<String> = (0..40u32)
+            .map(|word| {
+                (0..6)
+                    .filter_map(|letter| char::from_u32(0x4e00 + word * 6 + letter))
+                    .collect()
+            })
+            .collect();
+        let words = format!("{} книга", long.join(" "));
+        let wordle = Wordle::new(Some(words), "книга").unwrap();
+        assert_eq!(wordle.words.len(), 1);
+    }
+
+    #[test]
+    fn folds_accents_when_asked() {
+        let words = "açaí caqui limão manga";
+        let wordle = Wordle::new(Some(words.to_string()), "limão").unwrap();
+        assert_eq!(wordle.words.len(), 3);
+        assert_eq!(
+            wordle.simulate("limao"),
+            Err(WordleError::UnknownWord("limao".to_string()))
+        );
+        let wordle = Wordle::with_alphabet(
+            Some(words.to_string()),
+            "Limão",
+            Segmentation::Chars,
+            Normalization::FoldAccents,
+        )
+        .unwrap();
+        assert_eq!(wordle.words[1], b"limao");
+        assert!(wordle.simulate("LIMAO").is_ok());
+        assert_eq!(wordle.best_guesses().len(), 3);
+    }
+
+    #[test]
+    fn no_tree_once_the_answer_is_found() {
+        let words = "kills fills hills pills bills";
+        let mut wordle = Wordle::new(Some(words.to_string()), "hills").unwrap();
+        assert!(wordle.decision_tree(6, 5).is_ok());
+        wordle.play_word("kills").unwrap();
+        assert!(wordle.decision_tree(6, 5).is_ok());
+        let (mask, _) = wordle.play_word("hills").unwrap();
+        assert_eq!(mask, [Correct; 5]);
+        assert_eq!(wordle.decision_tree(6, 5), Err(WordleError::NoAnswersLeft));
+    }
 }
@@ -3,32 +3,82 @@ extern crate wasm_bindgen;
 
 use wasm_bindgen::prelude::*;
 
+mod Absurdle;
+mod Alphabet;
+mod MultiWordle;
 mod Naive;
+mod Patterns;
+mod Simulation;
+mod Strategy;
+mod Tree;
 mod Wordle;
-use Wordle::{ByteStr, Correctness};
+use Wordle::{ByteStr, Correctness, WordleError};
 
-pub const WORDLE_SIZE: usize = 5;
+/// Longest supported word, masks of this size still index a few thousand buckets.
+pub const MAX_WORDLE_SIZE: usize = 8;
 pub const WORDS: &str = include_str!("./words.txt");
-const MAP_ARR_SIZE: usize = 3usize.pow(WORDLE_SIZE as u32);
+
+/// Bundled word list for words of `size` letters, other lengths need the
+/// list passed to `Wordle::new`.
+pub fn word_list(size: usize) -> Option<&'static str> {
+    match size {
+        5 => Some(WORDS),
+        _ => None,
+    }
+}
+
+/// Number of different masks for words of `size` letters.
+fn map_arr_size(size: usize) -> usize {
+    3usize.pow(size as u32)
+}
 
 pub struct Guess {
     word: ByteStr,
-    mask: [Correctness; WORDLE_SIZE],
+    mask: Vec<Correctness>,
 }
 
 impl Guess {
-    fn matches(&self, word: &ByteStr) -> bool {
+    fn matches(&self, word: &[u8]) -> bool {
         //A potential right word should produce the same mask as
         //the one we currently have in this guess
         Correctness::check(word, &self.word) == self.mask
     }
 
-    fn calc_distribution(valid_words: &[ByteStr], guess_word: &ByteStr) -> [usize; MAP_ARR_SIZE] {
-        let mut map_arr = [0usize; MAP_ARR_SIZE];
+    /// First hard mode rule `word` breaks after this guess: greens must stay
+    /// in place and yellows must be reused.
+    fn hard_mode_violation(
+        &self,
+        word: &[u8],
+        alphabet: &Alphabet::Alphabet,
+    ) -> Option<WordleError> {
+        let revealed = |index: &usize| self.mask[*index] != Correctness::Wrong;
+        if let Some(position) = (0..self.word.len()).find(|index| {
+            self.mask[*index] == Correctness::Correct && word[*index] != self.word[*index]
+        }) {
+            return Some(WordleError::MustKeep {
+                letter: alphabet.letter(self.word[position]),
+                position,
+            });
+        }
+        (0..self.word.len()).filter(revealed).find_map(|index| {
+            let letter = self.word[index];
+            let needed = (0..self.word.len())
+                .filter(revealed)
+                .filter(|other| self.word[*other] == letter)
+                .count();
+            let used = word.iter().filter(|c| **c == letter).count();
+            (used < needed).then_some(WordleError::MustUse {
+                letter: alphabet.letter(letter),
+            })
+        })
+    }
+
+    fn calc_distribution(valid_words: &[ByteStr], guess_word: &[u8]) -> Vec<usize> {
+        let mut map_arr = vec![0usize; map_arr_size(guess_word.len())];
         valid_words.iter().for_each(|word| {
             let mask = Correctness::check(word, guess_word);
             let mask_radix = Correctness::mask_radix(&mask);
-            map_arr[mask_radix] = map_arr[mask_radix] + 1;
+            map_arr[mask_radix] += 1;
         });
         map_arr
     }
@@ -36,8 +86,70 @@ impl Guess {
 
 #[wasm_bindgen]
 pub fn main() {
-    Wordle::Wordle::new(None, "hello").simulate("brick");
+    Wordle::Wordle::new(None, "hello")
+        .and_then(|wordle| wordle.simulate("brick"))
+        .expect("the bundled list has 5 letter words");
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::Correctness::{Correct, Misplaced, Wrong};
+    use super::*;
+
+    fn guess(word: &str, mask: &[Correctness]) -> Guess {
+        Guess {
+            word: word.as_bytes().to_vec(),
+            mask: mask.to_vec(),
+        }
+    }
+
+    #[test]
+    fn hard_mode_keeps_greens_in_place() {
+        let history = guess("crane", &[Wrong, Wrong, Correct, Wrong, Misplaced]);
+        assert_eq!(
+            history.hard_mode_violation(b"beast", &Alphabet::Alphabet::default()),
+            None
+        );
+        assert_eq!(
+            history.hard_mode_violation(b"abbey", &Alphabet::Alphabet::default()),
+            Some(WordleError::MustKeep {
+                letter: "a".to_string(),
+                position: 2
+            })
+        );
+        assert_eq!(
+            history.hard_mode_violation(b"shalt", &Alphabet::Alphabet::default()),
+            Some(WordleError::MustUse {
+                letter: "e".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn hard_mode_counts_repeated_letters() {
+        let history = guess("eerie", &[Misplaced, Misplaced, Wrong, Wrong, Wrong]);
+        assert_eq!(
+            history.hard_mode_violation(b"belts", &Alphabet::Alphabet::default()),
+            Some(WordleError::MustUse {
+                letter: "e".to_string()
+            })
+        );
+        assert_eq!(
+            history.hard_mode_violation(b"sheep", &Alphabet::Alphabet::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn solver_restricts_to_legal_guesses() {
+        let history = [guess("crane", &[Wrong, Wrong, Correct, Wrong, Misplaced])];
+        let words: Vec<ByteStr> = ["beast", "abbey", "shalt", "heart"]
+            .iter()
+            .map(|w| w.as_bytes().to_vec())
+            .collect();
+        assert_eq!(
+            Naive::hard_mode_guesses(&words, &history, &Alphabet::Alphabet::default()),
+            [0, 3]
+        );
+    }
+}